@@ -0,0 +1,52 @@
+//! Micro-benchmarks of the checks called in tight loops by the ghost and the
+//! route searches. Run them by `cargo bench`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use tetrice::Game;
+
+fn bench(name: &str, mut f: impl FnMut()) {
+    for _ in 0..100 {
+        f();
+    }
+    // Check the time per batch, which is slower than the fast checks.
+    let start = Instant::now();
+    let mut iterations = 0;
+    while start.elapsed() < Duration::from_secs(1) {
+        for _ in 0..100 {
+            f();
+        }
+        iterations += 100;
+    }
+    println!("{name:<20} {:?}/iter", start.elapsed() / iterations);
+}
+
+fn main() {
+    // Stack some tetriminos on both sides to make the searches work.
+    let mut game = Game::with_seed(10, 20, 5, 0);
+    for i in 0..12 {
+        if i % 2 == 0 {
+            game.rotate();
+        }
+        if i % 3 == 0 {
+            game.move_left_to_wall();
+        } else if i % 3 == 1 {
+            game.move_right_to_wall();
+        }
+        game.hard_drop_and_lock();
+    }
+
+    bench("touch_down", || {
+        black_box(black_box(&game).touches_down());
+    });
+    bench("overlap", || {
+        black_box(black_box(&game).overlaps());
+    });
+    bench("ghost", || {
+        black_box(black_box(&game).ghost());
+    });
+    bench("legal_placements", || {
+        black_box(black_box(&game).legal_placements(true));
+    });
+}
@@ -0,0 +1,50 @@
+//! Micro-benchmarks of making and restoring snapshots, which rollback
+//! netcode does many times per frame. Run them by `cargo bench`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use tetrice::Game;
+
+fn bench(name: &str, mut f: impl FnMut()) {
+    for _ in 0..100 {
+        f();
+    }
+    let start = Instant::now();
+    let mut iterations = 0;
+    while start.elapsed() < Duration::from_secs(1) {
+        for _ in 0..100 {
+            f();
+        }
+        iterations += 100;
+    }
+    println!("{name:<20} {:?}/iter", start.elapsed() / iterations);
+}
+
+fn main() {
+    let mut game = Game::with_seed(10, 20, 5, 0);
+    for i in 0..12 {
+        if i % 3 == 0 {
+            game.move_left_to_wall();
+        } else if i % 3 == 1 {
+            game.move_right_to_wall();
+        }
+        game.hard_drop_and_lock();
+    }
+
+    let snapshot = game.snapshot();
+    bench("snapshot", || {
+        black_box(black_box(&game).snapshot());
+    });
+    bench("restore", || {
+        black_box(&mut game).restore(black_box(&snapshot));
+    });
+
+    let mut rollback = game.save_snapshot();
+    bench("save_snapshot_into", || {
+        black_box(&game).save_snapshot_into(black_box(&mut rollback));
+    });
+    bench("restore_snapshot", || {
+        black_box(&mut game).restore_snapshot(black_box(&rollback));
+    });
+}
@@ -0,0 +1,54 @@
+/* The C API of tetrice, enabled by the `ffi` feature. */
+
+#ifndef TETRICE_H
+#define TETRICE_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* A game with the 7-bag randomizer. */
+typedef struct TetriceGame TetriceGame;
+
+/* Cells and block kinds: 0 is empty, 1 to 7 are O, I, Z, S, L, T and J,
+ * 8 is garbage, 9 is solid and 10 is a line being cleared. */
+
+/* Returns NULL if the field is too small or too wide. */
+TetriceGame *tetrice_game_new(size_t width, size_t height, size_t queue_size, uint64_t seed);
+void tetrice_game_free(TetriceGame *game);
+
+bool tetrice_game_move_left(TetriceGame *game);
+bool tetrice_game_move_right(TetriceGame *game);
+bool tetrice_game_soft_drop(TetriceGame *game);
+bool tetrice_game_hard_drop(TetriceGame *game);
+bool tetrice_game_rotate_cw(TetriceGame *game);
+bool tetrice_game_rotate_ccw(TetriceGame *game);
+bool tetrice_game_hold(TetriceGame *game);
+bool tetrice_game_lock(TetriceGame *game);
+/* Returns the number of lines removed during the time. */
+size_t tetrice_game_update(TetriceGame *game, uint32_t millis);
+
+size_t tetrice_game_width(const TetriceGame *game);
+size_t tetrice_game_height(const TetriceGame *game);
+/* Writes up to `len` cells and returns the number of the visible cells. */
+size_t tetrice_game_field_cells(const TetriceGame *game, uint8_t *out, size_t len);
+uint8_t tetrice_game_tetrimino_kind(const TetriceGame *game);
+/* `out` must have room for 8 numbers. */
+void tetrice_game_tetrimino_blocks(const TetriceGame *game, int32_t *out);
+void tetrice_game_ghost_blocks(const TetriceGame *game, int32_t *out);
+/* Writes up to `len` kinds and returns the number of the next tetriminos. */
+size_t tetrice_game_queue(const TetriceGame *game, uint8_t *out, size_t len);
+uint8_t tetrice_game_held(const TetriceGame *game);
+uint64_t tetrice_game_score(const TetriceGame *game);
+size_t tetrice_game_lines(const TetriceGame *game);
+bool tetrice_game_is_end(const TetriceGame *game);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* TETRICE_H */
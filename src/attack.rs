@@ -0,0 +1,72 @@
+//! Garbage lines sent to the opponent in versus play.
+
+use alloc::{vec, vec::Vec};
+
+use crate::{LockResult, SpinKind};
+
+/// A table deciding how many garbage lines each clear sends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttackTable {
+    /// Lines sent by normal clears, indexed by the number of removed lines.
+    pub clear: [u32; 5],
+    /// Lines sent by T-spins, indexed by the number of removed lines.
+    pub t_spin: [u32; 4],
+    /// Lines sent by mini T-spins, indexed by the number of removed lines.
+    pub t_spin_mini: [u32; 3],
+    /// Lines added by combos, indexed by the number of consecutive clears
+    /// before the current one. The last value is used for longer combos.
+    pub combo: Vec<u32>,
+    /// Lines added by back-to-back difficult clears.
+    pub back_to_back: u32,
+    /// Lines added by perfect clears.
+    pub perfect_clear: u32,
+}
+
+impl Default for AttackTable {
+    /// The table used by the Guideline games.
+    fn default() -> Self {
+        Self {
+            clear: [0, 0, 1, 2, 4],
+            t_spin: [0, 2, 4, 6],
+            t_spin_mini: [0, 0, 1],
+            combo: vec![0, 0, 1, 1, 1, 2, 2, 3, 3, 4, 4, 4, 5],
+            back_to_back: 1,
+            perfect_clear: 10,
+        }
+    }
+}
+
+impl AttackTable {
+    /// Get the number of lines sent by the lock, including the bonuses for
+    /// `LockResult::combo` and `LockResult::back_to_back`.
+    pub fn lines(&self, result: &LockResult) -> u32 {
+        let kind = result.clear_kind;
+        let lines = kind.lines();
+        if lines == 0 {
+            return 0;
+        }
+
+        let mut attack = match kind.spin() {
+            None => self.clear[lines],
+            Some(SpinKind::Mini) => self.t_spin_mini[lines],
+            Some(SpinKind::Full) => self.t_spin[lines],
+        };
+        if result.back_to_back {
+            attack += self.back_to_back;
+        }
+        attack += self.combo_lines(result.combo.unwrap_or(0));
+        if result.is_perfect_clear {
+            attack += self.perfect_clear;
+        }
+        attack
+    }
+
+    fn combo_lines(&self, combo: usize) -> u32 {
+        self.combo
+            .get(combo)
+            .or(self.combo.last())
+            .copied()
+            .unwrap_or(0)
+    }
+}
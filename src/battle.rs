@@ -0,0 +1,180 @@
+//! A versus battle between two games.
+
+use alloc::{boxed::Box, collections::VecDeque};
+use core::time::Duration;
+
+use crate::attack::AttackTable;
+use crate::random::Rng;
+use crate::{Game, LockResult, PieceSelector, TickResult};
+
+/// One of the two players of a battle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Player {
+    First,
+    Second,
+}
+
+impl Player {
+    /// Get the other player.
+    pub fn opponent(&self) -> Self {
+        match self {
+            Self::First => Self::Second,
+            Self::Second => Self::First,
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            Self::First => 0,
+            Self::Second => 1,
+        }
+    }
+}
+
+/// The rules of a battle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BattleRules {
+    /// The table converting clears into garbage lines.
+    pub attack_table: AttackTable,
+    /// Whether an attack cancels the garbage waiting to be sent to the
+    /// attacker before reaching the opponent.
+    pub cancelling: bool,
+    /// The number of locks of the receiver before the garbage can be
+    /// inserted. The garbage is inserted only when a lock doesn't remove any
+    /// lines.
+    pub garbage_delay: usize,
+    /// The seed deciding the holes of the garbage lines.
+    pub seed: u64,
+}
+
+impl Default for BattleRules {
+    fn default() -> Self {
+        Self {
+            attack_table: AttackTable::default(),
+            cancelling: true,
+            garbage_delay: 1,
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct PendingGarbage {
+    pub(crate) lines: u32,
+    pub(crate) delay: usize,
+}
+
+/// A battle manager, which sends the garbage lines between two games.
+///
+/// Save the tetriminos through `save` or `update` of the battle instead of the
+/// games, so that the attacks are sent to the opponent. Other operations can
+/// be done by `game_mut`.
+pub struct Battle<S = Box<dyn PieceSelector>> {
+    games: [Game<S>; 2],
+    pub(crate) pending: [VecDeque<PendingGarbage>; 2],
+    rules: BattleRules,
+    rng: Rng,
+}
+
+impl<S: PieceSelector> Battle<S> {
+    /// Create a battle between two games.
+    pub fn new(first: Game<S>, second: Game<S>, rules: BattleRules) -> Self {
+        Self {
+            games: [first, second],
+            pending: [VecDeque::new(), VecDeque::new()],
+            rng: Rng::new(rules.seed),
+            rules,
+        }
+    }
+
+    /// Get the game of the player.
+    pub fn game(&self, player: Player) -> &Game<S> {
+        &self.games[player.index()]
+    }
+
+    /// Get the game of the player mutably.
+    pub fn game_mut(&mut self, player: Player) -> &mut Game<S> {
+        &mut self.games[player.index()]
+    }
+
+    /// Get the number of garbage lines waiting to be inserted to the game of
+    /// the player.
+    pub fn pending_garbage(&self, player: Player) -> u32 {
+        self.pending[player.index()].iter().map(|p| p.lines).sum()
+    }
+
+    /// Returns the winner if the other player's game has ended.
+    pub fn winner(&self) -> Option<Player> {
+        match (self.games[0].is_end(), self.games[1].is_end()) {
+            (false, true) => Some(Player::First),
+            (true, false) => Some(Player::Second),
+            _ => None,
+        }
+    }
+
+    /// Save the current tetrimino of the player, and send the attack to the
+    /// opponent. No attacks are sent after either game has ended, and
+    /// nothing happens if the game can't save now, such as during ARE.
+    pub fn save(&mut self, player: Player) -> LockResult {
+        let is_over = self.winner().is_some();
+        let game = &mut self.games[player.index()];
+        let locked = game.is_active();
+        let result = game.save();
+        if locked && !is_over {
+            self.after_lock(player, &result);
+        }
+        result
+    }
+
+    /// Call `Game::update` of the player's game, and send the attack if the
+    /// tetrimino has been saved.
+    pub fn update(&mut self, player: Player, delta: Duration) -> TickResult {
+        let is_over = self.winner().is_some();
+        let result = self.games[player.index()].update(delta);
+        if let Some(lock) = &result.locked {
+            if !is_over {
+                self.after_lock(player, lock);
+            }
+        }
+        result
+    }
+
+    fn after_lock(&mut self, player: Player, result: &LockResult) {
+        let mut attack = self.rules.attack_table.lines(result);
+
+        let own = &mut self.pending[player.index()];
+        if self.rules.cancelling {
+            while attack > 0 {
+                let Some(first) = own.front_mut() else {
+                    break;
+                };
+                let cancelled = attack.min(first.lines);
+                attack -= cancelled;
+                first.lines -= cancelled;
+                if first.lines == 0 {
+                    own.pop_front();
+                }
+            }
+        }
+
+        // The garbage comes in when a tetrimino is saved without clearing.
+        for garbage in own.iter_mut() {
+            garbage.delay = garbage.delay.saturating_sub(1);
+        }
+        if result.lines() == 0 {
+            while own.front().is_some_and(|g| g.delay == 0) {
+                let garbage = own.pop_front().unwrap();
+                let game = &mut self.games[player.index()];
+                let hole = self.rng.below(game.field().width());
+                game.add_garbage(garbage.lines as usize, hole);
+            }
+        }
+
+        if attack > 0 {
+            self.pending[player.opponent().index()].push_back(PendingGarbage {
+                lines: attack,
+                delay: self.rules.garbage_delay,
+            });
+        }
+    }
+}
@@ -0,0 +1,214 @@
+//! A simple bot playing the game, enabled by the `bot` feature.
+
+use alloc::vec::Vec;
+
+use crate::Set;
+
+use crate::eval::Weights;
+use crate::{Cell, Checker, Game, LockResult, Move, PieceSelector, Tetrimino};
+
+/// A place to lock the tetrimino, and how to get there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placement {
+    /// Whether to hold before moving.
+    pub hold: bool,
+    /// The tetrimino at the place.
+    pub tetrimino: Tetrimino,
+    /// The moves from where the tetrimino appears to the place.
+    pub path: Vec<Move>,
+}
+
+impl Placement {
+    /// Hold if needed, move the tetrimino along the path and save it.
+    pub fn play<S: PieceSelector>(&self, game: &mut Game<S>) -> LockResult {
+        if self.hold {
+            game.hold();
+        }
+        for m in &self.path {
+            match m {
+                Move::Left => game.move_left(),
+                Move::Right => game.move_right(),
+                Move::Down => game.soft_drop() > 0,
+                Move::RotateCw => game.rotate().rotated,
+                Move::Rotate180 => game.rotate_180().rotated,
+                Move::RotateCcw => game.rotate_ccw().rotated,
+            };
+        }
+        game.save()
+    }
+}
+
+/// A bot choosing the best placement of the current tetrimino only, without
+/// looking ahead.
+#[derive(Debug, Clone, Default)]
+pub struct GreedyBot {
+    /// The weights to evaluate placements.
+    pub weights: Weights,
+    /// Whether to consider holding.
+    pub use_hold: bool,
+}
+
+impl GreedyBot {
+    /// Create a bot with the weights.
+    pub fn new(weights: Weights, use_hold: bool) -> Self {
+        Self { weights, use_hold }
+    }
+
+    /// Find the best placement. Returns `None` if there are no placements,
+    /// for example after the game ends.
+    pub fn best_move<S: PieceSelector>(&self, game: &Game<S>) -> Option<Placement> {
+        let current = game.tetrimino();
+        let best = game
+            .legal_placements(self.use_hold)
+            .into_iter()
+            .map(|t| (self.weights.evaluate(game.field(), &t), t))
+            .max_by(|(a, _), (b, _)| a.total_cmp(b))?
+            .1;
+
+        let hold = best.kind() != current.kind();
+        let start = if hold {
+            game.spawn_position_after_hold()?
+        } else {
+            current.clone()
+        };
+        let path = Checker(game.field(), &best).route_path(&start)?;
+        Some(Placement {
+            hold,
+            tetrimino: best,
+            path,
+        })
+    }
+
+    /// Find the best placement and lock the tetrimino there. Returns `None`
+    /// if there are no placements.
+    pub fn play<S: PieceSelector>(&self, game: &mut Game<S>) -> Option<LockResult> {
+        let placement = self.best_move(game)?;
+        Some(placement.play(game))
+    }
+}
+
+/// A solver searching for placements which end in a perfect clear, using
+/// only the current, next and held tetriminos.
+#[derive(Debug, Clone)]
+pub struct PcSolver {
+    /// The highest number of rows the perfect clear can take.
+    pub max_height: usize,
+    /// Whether to consider holding.
+    pub use_hold: bool,
+}
+
+impl Default for PcSolver {
+    /// Up to 4 rows with holding.
+    fn default() -> Self {
+        Self {
+            max_height: 4,
+            use_hold: true,
+        }
+    }
+}
+
+impl PcSolver {
+    /// Find the placements in the order to play them. Returns `None` if there
+    /// is no perfect clear within the known tetriminos.
+    ///
+    /// The lower perfect clears are tried first.
+    pub fn solve<S: PieceSelector>(&self, game: &Game<S>) -> Option<Vec<Placement>> {
+        if game.is_end() {
+            return None;
+        }
+        let field = game.field();
+        let filled: usize = field
+            .visible()
+            .iter()
+            .flatten()
+            .filter(|cell| **cell != Cell::Empty)
+            .count();
+        let stack_height = field.column_heights().into_iter().max().unwrap_or(0);
+        let pieces = 1 + game.queue().len() + game.held_all().len();
+
+        for height in stack_height.max(1)..=self.max_height.min(field.height()) {
+            let empty = height * field.width() - filled;
+            if !empty.is_multiple_of(4) || empty / 4 > pieces {
+                continue;
+            }
+            let mut search = PcSearch {
+                solver: self,
+                known: game.queue().len(),
+                failed: Set::new(),
+                path: Vec::new(),
+            };
+            if search.run(&game.fork(), height, 0) {
+                return Some(search.path);
+            }
+        }
+        None
+    }
+}
+
+struct PcSearch<'a> {
+    solver: &'a PcSolver,
+    // The number of tetriminos in the queue, which are known.
+    known: usize,
+    failed: Set<(u64, usize, usize)>,
+    path: Vec<Placement>,
+}
+
+impl PcSearch<'_> {
+    // Search placements within `height` rows from the bottom, having taken
+    // `drawn` tetriminos from the queue.
+    fn run(&mut self, game: &Game, height: usize, drawn: usize) -> bool {
+        let key = (game.position_hash(), height, drawn);
+        if self.failed.contains(&key) {
+            return false;
+        }
+
+        let top = (game.field().height() - height) as isize;
+        let current = game.tetrimino();
+        for tetrimino in game.legal_placements(self.solver.use_hold) {
+            if tetrimino.blocks().iter().any(|(_, y)| *y < top) {
+                continue;
+            }
+            let hold = tetrimino.kind() != current.kind();
+            // Holding into an empty slot takes another tetrimino, which must
+            // be known.
+            let fills_hold = hold && game.held_all().len() < game.hold_rule().slots;
+            let drawn = drawn + fills_hold as usize;
+            if drawn > self.known {
+                continue;
+            }
+            let start = if hold {
+                game.spawn_position_after_hold()
+            } else {
+                Some(current.clone())
+            };
+            let Some(path) =
+                start.and_then(|start| Checker(game.field(), &tetrimino).route_path(&start))
+            else {
+                continue;
+            };
+
+            let placement = Placement {
+                hold,
+                tetrimino,
+                path,
+            };
+            let mut next = game.fork();
+            let result = placement.play(&mut next);
+            self.path.push(placement);
+            if result.is_perfect_clear {
+                return true;
+            }
+            let lines = result.lines();
+            if lines < height
+                && drawn < self.known
+                && !next.is_end()
+                && self.run(&next, height - lines, drawn + 1)
+            {
+                return true;
+            }
+            self.path.pop();
+        }
+        self.failed.insert(key);
+        false
+    }
+}
@@ -0,0 +1,307 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::time::Duration;
+
+use crate::cheese::garbage_rows;
+use crate::field::DEFAULT_HIDDEN_HEIGHT;
+use crate::random::Rng;
+use crate::scoring::{Guideline, ScoringTable};
+use crate::{
+    Cell, Field, Game, GhostMode, Gravity, HoldRule, LevelTable, LockDelay, NearSearch,
+    PieceSelector, RisingFloor, RotationSystem, Score, SpawnRule, Visibility,
+};
+
+/// A builder of `Game`, made by `Game::builder`.
+///
+/// ```
+/// use tetrice::{Game, Srs};
+/// use tetrice::selectors::Bag;
+///
+/// let game = Game::builder()
+///     .size(10, 40)
+///     .queue_size(5)
+///     .rotation_system(Srs)
+///     .build(Bag::seven(0))
+///     .unwrap();
+/// ```
+pub struct GameBuilder {
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    hidden_height: usize,
+    pub(crate) queue_size: usize,
+    hold_rule: HoldRule,
+    rotation_system: Box<dyn RotationSystem>,
+    scoring_table: Box<dyn ScoringTable>,
+    gravity: Gravity,
+    lock_delay: LockDelay,
+    level_table: Option<LevelTable>,
+    spawn_rule: SpawnRule,
+    ghost_mode: GhostMode,
+    cascade: bool,
+    rising_floor: Option<RisingFloor>,
+    line_clear_delay: Duration,
+    are: Duration,
+    visibility: Visibility,
+    big: bool,
+    mirrored: bool,
+    starting_field: Option<Field>,
+    starting_garbage: Option<(usize, f64, u64)>,
+}
+
+impl GameBuilder {
+    /// Create a builder with the default options: a 10x20 field, 3 next
+    /// tetriminos, holding enabled, and the default rules.
+    pub fn new() -> Self {
+        Self {
+            width: 10,
+            height: 20,
+            hidden_height: DEFAULT_HIDDEN_HEIGHT,
+            queue_size: 3,
+            hold_rule: HoldRule::standard(),
+            rotation_system: Box::new(NearSearch),
+            scoring_table: Box::new(Guideline),
+            gravity: Gravity::default(),
+            lock_delay: LockDelay::default(),
+            level_table: None,
+            spawn_rule: SpawnRule::default(),
+            ghost_mode: GhostMode::default(),
+            cascade: false,
+            rising_floor: None,
+            line_clear_delay: Duration::ZERO,
+            are: Duration::ZERO,
+            visibility: Visibility::default(),
+            big: false,
+            mirrored: false,
+            starting_field: None,
+            starting_garbage: None,
+        }
+    }
+
+    /// Set the size of the visible area of the field.
+    pub fn size(mut self, width: usize, height: usize) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Set the height of the non-visible area above the visible area. The
+    /// default is 7.
+    pub fn hidden_height(mut self, hidden_height: usize) -> Self {
+        self.hidden_height = hidden_height;
+        self
+    }
+
+    /// Set the number of next tetriminos.
+    pub fn queue_size(mut self, queue_size: usize) -> Self {
+        self.queue_size = queue_size;
+        self
+    }
+
+    /// Enable or disable holding.
+    pub fn hold_enabled(mut self, enabled: bool) -> Self {
+        self.hold_rule = if enabled {
+            HoldRule::standard()
+        } else {
+            HoldRule::disabled()
+        };
+        self
+    }
+
+    /// Set the rule of holding.
+    pub fn hold_rule(mut self, rule: HoldRule) -> Self {
+        self.hold_rule = rule;
+        self
+    }
+
+    /// Set the rotation system.
+    pub fn rotation_system(mut self, rotation_system: impl RotationSystem + 'static) -> Self {
+        self.rotation_system = Box::new(rotation_system);
+        self
+    }
+
+    /// Set the scoring table.
+    pub fn scoring_table(mut self, scoring_table: impl ScoringTable + 'static) -> Self {
+        self.scoring_table = Box::new(scoring_table);
+        self
+    }
+
+    /// Set the gravity.
+    pub fn gravity(mut self, gravity: Gravity) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    /// Set the lock delay.
+    pub fn lock_delay(mut self, lock_delay: LockDelay) -> Self {
+        self.lock_delay = lock_delay;
+        self
+    }
+
+    /// Set the level table, which overrides the gravity and the lock delay.
+    pub fn level_table(mut self, table: LevelTable) -> Self {
+        self.level_table = Some(table);
+        self
+    }
+
+    /// Set the rule deciding where each tetrimino appears.
+    pub fn spawn_rule(mut self, rule: SpawnRule) -> Self {
+        self.spawn_rule = rule;
+        self
+    }
+
+    /// Set how the ghost is found.
+    pub fn ghost_mode(mut self, mode: GhostMode) -> Self {
+        self.ghost_mode = mode;
+        self
+    }
+
+    /// Enable or disable the cascade after clears.
+    pub fn cascade(mut self, enabled: bool) -> Self {
+        self.cascade = enabled;
+        self
+    }
+
+    /// Make the floor rise by itself.
+    pub fn rising_floor(mut self, rule: RisingFloor) -> Self {
+        self.rising_floor = Some(rule);
+        self
+    }
+
+    /// Set the time the cleared lines stay before they are removed.
+    pub fn line_clear_delay(mut self, delay: Duration) -> Self {
+        self.line_clear_delay = delay;
+        self
+    }
+
+    /// Set the entry delay (ARE) before each tetrimino appears.
+    pub fn are(mut self, are: Duration) -> Self {
+        self.are = are;
+        self
+    }
+
+    /// Set whether the saved blocks are shown.
+    pub fn visibility(mut self, visibility: Visibility) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
+    /// Enable big mode, where each block takes 2x2 cells as in TGM. The game
+    /// is played on a field of half the size set by `size` and
+    /// `hidden_height`, rounding down the width and the height and rounding
+    /// up the hidden height, so line clears take double rows. `Game::render`
+    /// draws it enlarged.
+    ///
+    /// The rules, and `Game::field`, work on this half-size field, where
+    /// each cell is one 2x2 block on the screen. Big mode can't be combined
+    /// with `starting_field`.
+    pub fn big(mut self, enabled: bool) -> Self {
+        self.big = enabled;
+        self
+    }
+
+    /// Enable mirror mode, where the tetriminos are the mirror images of
+    /// the ones from the selector.
+    pub fn mirrored(mut self, enabled: bool) -> Self {
+        self.mirrored = enabled;
+        self
+    }
+
+    /// Start with the field instead of an empty one, which also decides the
+    /// size and overrides `size` and `hidden_height`. `build` returns an
+    /// error if big mode is also enabled.
+    pub fn starting_field(mut self, field: Field) -> Self {
+        self.starting_field = Some(field);
+        self
+    }
+
+    /// Start with `rows` garbage rows at the bottom, pushed up from below the
+    /// starting field. Each row has a single hole, which moves to another
+    /// column with the probability `messiness` like `CheeseRules`, and the
+    /// holes are decided by `seed`. `build` returns an error if any blocks,
+    /// including the garbage, would be pushed out of the top of the field.
+    pub fn starting_garbage(mut self, rows: usize, messiness: f64, seed: u64) -> Self {
+        self.starting_garbage = Some((rows, messiness, seed));
+        self
+    }
+
+    /// Create a game using the selector. Returns an error if the options
+    /// don't work together.
+    ///
+    /// Panics if the field is too small, like `Game::new`.
+    pub fn build<S: PieceSelector>(self, selector: S) -> Result<Game<S>, BuildError> {
+        let big = self.big;
+        let mut field = match self.starting_field {
+            Some(_) if big => return Err(BuildError::BigWithStartingField),
+            Some(field) => field,
+            None if big => Field::new(
+                self.width / 2,
+                self.height / 2,
+                self.hidden_height.div_ceil(2),
+            ),
+            None => Field::new(self.width, self.height, self.hidden_height),
+        };
+        if let Some((rows, messiness, seed)) = self.starting_garbage {
+            let width = field.width();
+            let rows: Vec<_> = garbage_rows(&mut Rng::new(seed), &mut None, width, rows, messiness)
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|b| if b { Cell::Garbage } else { Cell::Empty })
+                        .collect()
+                })
+                .collect();
+            let fits = rows.len() <= field.height() + field.hidden_height();
+            if !fits || !field.push_up(rows) {
+                return Err(BuildError::GarbageTooTall);
+            }
+        }
+        let mut game = Game::unstarted(field, selector);
+        game.big = big;
+        game.set_spawn_rule(self.spawn_rule);
+        game.set_hold_rule(self.hold_rule);
+        game.set_rotation_system(self.rotation_system);
+        *game.score_mut() = Score::new(self.scoring_table);
+        game.set_gravity(self.gravity);
+        game.set_lock_delay(self.lock_delay);
+        game.set_level_table(self.level_table);
+        game.set_ghost_mode(self.ghost_mode);
+        game.set_cascade(self.cascade);
+        game.set_rising_floor(self.rising_floor);
+        game.set_line_clear_delay(self.line_clear_delay);
+        game.set_are(self.are);
+        game.set_visibility(self.visibility);
+        game.set_mirrored(self.mirrored);
+        game.start(self.queue_size);
+        Ok(game)
+    }
+}
+
+/// An error returned by `GameBuilder::build` for options which don't work
+/// together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BuildError {
+    /// Big mode is enabled with a starting field, which can't be halved.
+    BigWithStartingField,
+    /// The starting garbage pushes blocks out of the top of the field.
+    GarbageTooTall,
+}
+
+impl core::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BuildError::BigWithStartingField => {
+                write!(f, "big mode can't start with a field")
+            }
+            BuildError::GarbageTooTall => {
+                write!(f, "the starting garbage doesn't fit in the field")
+            }
+        }
+    }
+}
+
+impl core::error::Error for BuildError {}
+
+impl Default for GameBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
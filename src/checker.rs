@@ -1,78 +1,295 @@
-use std::collections::{HashSet, VecDeque};
+use alloc::{
+    collections::{BinaryHeap, VecDeque},
+    vec,
+    vec::Vec,
+};
+use core::cmp::Reverse;
 
-use crate::field::{Cell, Field};
+use crate::Map;
+
+use crate::field::Field;
 use crate::tetrimino::Tetrimino;
 
 /// Checks the state of a tetrimino, for example whether it touches to another block.
+///
+/// Use `Game::check` for the current tetrimino of a game, or `Checker::new`
+/// for any other tetrimino. The simple checks are also available on `Game`,
+/// such as `Game::touches_down`.
 pub struct Checker<'game>(pub &'game Field, pub &'game Tetrimino);
 
 impl<'game> Checker<'game> {
-    fn block_existence(&self, map: impl Fn(&(isize, isize)) -> (isize, isize)) -> bool {
-        self.1
-            .blocks()
-            .iter()
-            .any(|pos| self.0.get_cell(map(pos)) != Cell::Empty)
+    /// Create a checker of the tetrimino in the field.
+    pub fn new(field: &'game Field, tetrimino: &'game Tetrimino) -> Self {
+        Self(field, tetrimino)
+    }
+
+    // Returns true if any block is occupied after moving the tetrimino by
+    // the offset. This is called in tight loops by the searches, so it loops
+    // over the blocks directly.
+    fn occupied_at(&self, (dx, dy): (isize, isize)) -> bool {
+        for (x, y) in self.1.blocks() {
+            if self.0.is_occupied((x + dx, y + dy)) {
+                return true;
+            }
+        }
+        false
     }
 
     /// Returns true if there are the border or other blocks on the left side of the tetrimino.
     pub fn touch_left(&self) -> bool {
-        self.block_existence(|(x, y)| (x - 1, *y))
+        self.occupied_at((-1, 0))
     }
 
     /// Same as `touch_left` but checks the right side.
     pub fn touch_right(&self) -> bool {
-        self.block_existence(|(x, y)| (x + 1, *y))
+        self.occupied_at((1, 0))
     }
 
     /// Same as `touch_left` but checks the bottom.
     pub fn touch_down(&self) -> bool {
-        self.block_existence(|(x, y)| (*x, y + 1))
+        self.occupied_at((0, 1))
+    }
+
+    /// Same as `touch_left` but checks the top.
+    pub fn touch_up(&self) -> bool {
+        self.occupied_at((0, -1))
+    }
+
+    /// Returns true if the tetrimino can't move left, right, up or down.
+    ///
+    /// This is the rule to detect spins of any tetriminos, used together
+    /// with the kick reported by `Game::rotate`.
+    pub fn immobile(&self) -> bool {
+        self.touch_left() && self.touch_right() && self.touch_up() && self.touch_down()
+    }
+
+    /// Get the number of the faces of the tetrimino's blocks touching other
+    /// blocks or the borders. A larger number means the tetrimino fits better.
+    pub fn contact_cells(&self) -> usize {
+        let blocks = self.1.blocks();
+        blocks
+            .iter()
+            .flat_map(|(x, y)| [(x - 1, *y), (x + 1, *y), (*x, y - 1), (*x, y + 1)])
+            .filter(|pos| !blocks.contains(pos) && self.0.is_occupied(*pos))
+            .count()
     }
 
     /// Returns true if the tetrimino overlaps to other blocks, or if any
     /// blocks of the tetrimino is outside the field.
     pub fn overlap(&self) -> bool {
-        self.block_existence(|p| *p)
+        self.occupied_at((0, 0))
     }
 
     /// Returns true if `start` can reach the tetrimino of `self`. The route
     /// includes moving left, right and down, or rotation.
     pub fn route_from(&self, start: &Tetrimino) -> bool {
-        route_exists(self.0, start, self.1)
+        search(self.0, start, self.1, usize::MAX).path.is_some()
     }
 
     /// Same as `route_from` but swaps the start and the goal.
     pub fn route_to(&self, goal: &Tetrimino) -> bool {
-        route_exists(self.0, self.1, goal)
+        search(self.0, self.1, goal, usize::MAX).path.is_some()
+    }
+
+    /// Find one of the shortest routes from `start` to the tetrimino of
+    /// `self`. Returns `None` if the tetrimino can't be reached.
+    ///
+    /// Rotations in the route don't need kicks, so the route can be followed
+    /// by calling the methods of `Game`, such as `Game::rotate` for
+    /// `Move::RotateCw`.
+    pub fn route_path(&self, start: &Tetrimino) -> Option<Vec<Move>> {
+        search(self.0, start, self.1, usize::MAX).path
+    }
+
+    /// Same as `route_path`, but gives up after visiting `limit` positions,
+    /// so that searching a huge field doesn't take too long.
+    ///
+    /// The positions vertically closer to the goal are visited first.
+    pub fn search_route(&self, start: &Tetrimino, limit: usize) -> RouteSearch {
+        search(self.0, start, self.1, limit)
     }
 }
 
-fn route_exists(field: &Field, start: &Tetrimino, goal: &Tetrimino) -> bool {
-    let mut seen = HashSet::from([start.clone()]);
-    let mut queue = VecDeque::from([start.clone()]);
+/// The result of `Checker::search_route`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteSearch {
+    /// One of the shortest routes, or `None` if it wasn't found.
+    pub path: Option<Vec<Move>>,
+    /// The number of positions visited.
+    pub visited: usize,
+    /// Whether the search gave up by the limit. If this is false and `path`
+    /// is `None`, the goal can't be reached.
+    pub limited: bool,
+}
+
+// Search the shortest route by A*, using the vertical distance to the goal
+// as the estimate. Each position is stored once and referred by its index.
+fn search(field: &Field, start: &Tetrimino, goal: &Tetrimino, limit: usize) -> RouteSearch {
+    let estimate = |t: &Tetrimino| (t.position().1 - goal.position().1).unsigned_abs();
+
+    let mut nodes = vec![start.clone()];
+    let mut indices = Map::from([(start.clone(), 0)]);
+    let mut came_from: Vec<Option<(usize, Move)>> = vec![None];
+    let mut costs = vec![0];
+    let mut open = BinaryHeap::from([Reverse((estimate(start), 0, 0))]);
+    let mut visited = 0;
+
+    while let Some(Reverse((_, cost, index))) = open.pop() {
+        if cost > costs[index] {
+            // Already visited by a shorter route.
+            continue;
+        }
+        if nodes[index] == *goal {
+            let mut path = Vec::new();
+            let mut current = index;
+            while let Some((prev, m)) = came_from[current] {
+                path.push(m);
+                current = prev;
+            }
+            path.reverse();
+            return RouteSearch {
+                path: Some(path),
+                visited,
+                limited: false,
+            };
+        }
+        if visited == limit {
+            return RouteSearch {
+                path: None,
+                visited,
+                limited: true,
+            };
+        }
+        visited += 1;
+
+        for m in Move::ALL {
+            let t = m.apply(&nodes[index]);
+            let next_cost = cost + 1;
+            let next = match indices.get(&t) {
+                Some(&i) if costs[i] <= next_cost => continue,
+                Some(&i) => i,
+                None if Checker(field, &t).overlap() => continue,
+                None => {
+                    nodes.push(t.clone());
+                    indices.insert(t.clone(), nodes.len() - 1);
+                    came_from.push(None);
+                    costs.push(next_cost);
+                    nodes.len() - 1
+                }
+            };
+            costs[next] = next_cost;
+            came_from[next] = Some((index, m));
+            open.push(Reverse((next_cost + estimate(&t), next_cost, next)));
+        }
+    }
+
+    RouteSearch {
+        path: None,
+        visited,
+        limited: false,
+    }
+}
+
+/// A move of a tetrimino in a route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Move {
+    /// Move left by one cell.
+    Left,
+    /// Move right by one cell.
+    Right,
+    /// Move down by one cell.
+    Down,
+    /// Rotate clockwise.
+    RotateCw,
+    /// Rotate by 180 degrees.
+    Rotate180,
+    /// Rotate counter-clockwise.
+    RotateCcw,
+}
 
-    let moves: Vec<fn(&Tetrimino) -> Tetrimino> = vec![
-        |t| t.move_left(1),
-        |t| t.move_right(1),
-        |t| t.move_down(1),
-        |t| t.rotate(1),
-        |t| t.rotate(2),
-        |t| t.rotate(3),
+impl Move {
+    const ALL: [Move; 6] = [
+        Move::Left,
+        Move::Right,
+        Move::Down,
+        Move::RotateCw,
+        Move::Rotate180,
+        Move::RotateCcw,
     ];
 
-    while let Some(elem) = queue.pop_front() {
-        if elem == *goal {
-            return true;
+    fn apply(self, t: &Tetrimino) -> Tetrimino {
+        match self {
+            Move::Left => t.move_left(1),
+            Move::Right => t.move_right(1),
+            Move::Down => t.move_down(1),
+            Move::RotateCw => t.rotate(1),
+            Move::Rotate180 => t.rotate(2),
+            Move::RotateCcw => t.rotate(3),
         }
+    }
+}
 
-        for f in &moves {
-            let t = f(&elem);
+/// Get all the positions which `start` can reach, including `start` itself.
+pub(crate) fn reachable(field: &Field, start: &Tetrimino) -> Vec<Tetrimino> {
+    let mut seen = Visited::new(field);
+    seen.insert(start);
+    let mut queue = VecDeque::from([start.clone()]);
+    let mut found = Vec::new();
+
+    while let Some(elem) = queue.pop_front() {
+        for m in Move::ALL {
+            let t = m.apply(&elem);
             if !seen.contains(&t) && !Checker(field, &t).overlap() {
-                queue.push_back(t.clone());
-                seen.insert(t.clone());
+                seen.insert(&t);
+                queue.push_back(t);
             }
         }
+        found.push(elem);
     }
 
-    false
+    found
+}
+
+// A set of the positions of tetriminos in a field, which is much faster than
+// hashing them. The positions where the tetrimino overlaps the borders
+// aren't stored, and are never contained.
+struct Visited {
+    bits: Vec<bool>,
+    width: isize,
+    height: isize,
+    top: isize,
+}
+
+// How far the position of a tetrimino can be out of its blocks.
+const MARGIN: isize = 3;
+
+impl Visited {
+    fn new(field: &Field) -> Self {
+        let width = field.width() as isize + MARGIN;
+        let height = (field.height() + field.hidden_height()) as isize + MARGIN;
+        Self {
+            bits: vec![false; (4 * width * height) as usize],
+            width,
+            height,
+            top: -(field.hidden_height() as isize) - MARGIN,
+        }
+    }
+
+    fn index(&self, t: &Tetrimino) -> Option<usize> {
+        let (x, y) = t.position();
+        let (x, y) = (x + MARGIN, y - self.top);
+        ((0..self.width).contains(&x) && (0..self.height).contains(&y))
+            .then(|| ((t.rotation() as isize * self.height + y) * self.width + x) as usize)
+    }
+
+    fn contains(&self, t: &Tetrimino) -> bool {
+        self.index(t).is_some_and(|i| self.bits[i])
+    }
+
+    fn insert(&mut self, t: &Tetrimino) {
+        if let Some(i) = self.index(t) {
+            self.bits[i] = true;
+        }
+    }
 }
@@ -0,0 +1,182 @@
+//! Cheese races, where the player digs through garbage rows to the bottom.
+
+use alloc::{boxed::Box, vec::Vec};
+use core::time::Duration;
+
+use crate::random::Rng;
+use crate::{Cell, Game, LockResult, PieceSelector, TickResult};
+
+/// The rules of a cheese race.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CheeseRules {
+    /// The total number of garbage rows to remove.
+    pub lines: usize,
+    /// The number of garbage rows kept in the field. When they are removed,
+    /// new ones come from the bottom until all the lines have appeared.
+    pub height: usize,
+    /// The probability that the hole of a row is in a different column from
+    /// the row below it, from 0 (a straight well) to 1 (always moves).
+    pub messiness: f64,
+    /// The seed deciding the holes.
+    pub seed: u64,
+}
+
+impl Default for CheeseRules {
+    /// 18 lines with 10 rows at a time and the holes always moving.
+    fn default() -> Self {
+        Self {
+            lines: 18,
+            height: 10,
+            messiness: 1.0,
+            seed: 0,
+        }
+    }
+}
+
+/// A mode to remove all the garbage rows, each of which has a single hole.
+///
+/// Operate the tetrimino through `game_mut`, but save it and advance the time
+/// through the race, so that the removed rows are refilled.
+pub struct CheeseRace<S = Box<dyn PieceSelector>> {
+    game: Game<S>,
+    rules: CheeseRules,
+    rng: Rng,
+    // The column of the hole of the last row, which is at the bottom.
+    hole: Option<usize>,
+    // The number of rows which haven't appeared yet.
+    undealt: usize,
+    elapsed: Duration,
+}
+
+impl<S: PieceSelector> CheeseRace<S> {
+    /// Start a cheese race with the game, filling the bottom of the field.
+    pub fn new(game: Game<S>, rules: CheeseRules) -> Self {
+        let mut race = Self {
+            game,
+            rules,
+            rng: Rng::new(rules.seed),
+            hole: None,
+            undealt: rules.lines,
+            elapsed: Duration::ZERO,
+        };
+        race.refill();
+        race
+    }
+
+    /// Get the game.
+    pub fn game(&self) -> &Game<S> {
+        &self.game
+    }
+
+    /// Get the game mutably.
+    pub fn game_mut(&mut self) -> &mut Game<S> {
+        &mut self.game
+    }
+
+    /// Get the rules.
+    pub fn rules(&self) -> CheeseRules {
+        self.rules
+    }
+
+    /// Get the number of garbage rows left to remove, including the ones
+    /// which haven't appeared yet.
+    pub fn lines_left(&self) -> usize {
+        self.undealt + self.rows_in_field()
+    }
+
+    /// Get the time passed since the start, which stops at the end.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Returns true if all the garbage rows have been removed.
+    pub fn is_complete(&self) -> bool {
+        self.lines_left() == 0
+    }
+
+    /// Returns true if the race has been completed or the game has ended.
+    pub fn is_finished(&self) -> bool {
+        self.is_complete() || self.game.is_end()
+    }
+
+    /// Advance the time and call `Game::update`. Does nothing after finishing.
+    pub fn update(&mut self, delta: Duration) -> TickResult {
+        if self.is_finished() {
+            return TickResult::default();
+        }
+        self.elapsed += delta;
+        let result = self.game.update(delta);
+        if result.locked.is_some() {
+            self.refill();
+        }
+        result
+    }
+
+    /// Call `Game::save` and refill the removed garbage rows. Does nothing
+    /// after finishing, and returns an empty result.
+    pub fn save(&mut self) -> LockResult {
+        if self.is_finished() {
+            return LockResult::default();
+        }
+        let result = self.game.save();
+        self.refill();
+        result
+    }
+
+    fn rows_in_field(&self) -> usize {
+        self.game
+            .field()
+            .rows()
+            .filter(|(_, row)| row.contains(&Cell::Garbage))
+            .count()
+    }
+
+    // Push up new rows until the field has as many garbage rows as the rules
+    // say.
+    fn refill(&mut self) {
+        let count = self
+            .rules
+            .height
+            .saturating_sub(self.rows_in_field())
+            .min(self.undealt);
+        if count == 0 || self.game.is_end() {
+            return;
+        }
+
+        let width = self.game.field().width();
+        let rows = garbage_rows(
+            &mut self.rng,
+            &mut self.hole,
+            width,
+            count,
+            self.rules.messiness,
+        );
+        self.undealt -= count;
+        self.game.add_garbage_rows(rows);
+    }
+}
+
+// Make garbage rows with a single hole each, from the top. `hole` is the
+// column of the hole of the row below them, which becomes the one of the last
+// row.
+pub(crate) fn garbage_rows(
+    rng: &mut Rng,
+    hole: &mut Option<usize>,
+    width: usize,
+    count: usize,
+    messiness: f64,
+) -> Vec<Vec<bool>> {
+    let mut rows = Vec::with_capacity(count);
+    for _ in 0..count {
+        let next = match *hole {
+            Some(last) if !rng.chance(messiness) => last,
+            // Choose one of the other columns.
+            Some(last) if width > 1 => (last + 1 + rng.below(width - 1)) % width,
+            _ => rng.below(width),
+        };
+        *hole = Some(next);
+        rows.push((0..width).map(|x| x != next).collect());
+    }
+    rows
+}
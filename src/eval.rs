@@ -0,0 +1,185 @@
+//! Heuristic evaluation of placements, for building bots.
+//!
+//! The features are the ones by Pierre Dellacherie, which are also used by
+//! El-Tetris.
+//!
+//! ```
+//! use tetrice::eval::Weights;
+//! use tetrice::{BlockKind, Game};
+//!
+//! let game = Game::new(10, 20, 3, || BlockKind::T);
+//! let weights = Weights::default();
+//! let best = game
+//!     .legal_placements(false)
+//!     .into_iter()
+//!     .max_by(|a, b| {
+//!         let a = weights.evaluate(game.field(), a);
+//!         let b = weights.evaluate(game.field(), b);
+//!         a.total_cmp(&b)
+//!     });
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{Cell, Field, Tetrimino};
+
+/// The features of the field after a placement.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Features {
+    /// The height where the tetrimino is placed, which is the middle of its
+    /// lowest and highest rows. The bottom row is at height 0.
+    pub landing_height: f64,
+    /// The number of removed lines multiplied by the number of blocks of the
+    /// tetrimino in the removed lines.
+    pub eroded_cells: f64,
+    /// The number of horizontal changes between filled and empty cells. The
+    /// walls are regarded as filled, and the rows without blocks are skipped.
+    pub row_transitions: f64,
+    /// The number of vertical changes between filled and empty cells. The
+    /// floor is regarded as filled.
+    pub column_transitions: f64,
+    /// The number of holes, like `Field::holes`.
+    pub holes: f64,
+    /// The sum of the depths of the wells, where a well of depth 3 counts as
+    /// 1 + 2 + 3.
+    pub wells: f64,
+}
+
+impl Features {
+    /// Compute the features of placing the tetrimino on the field. The
+    /// tetrimino is not checked whether it can be placed there.
+    pub fn new(field: &Field, placement: &Tetrimino) -> Self {
+        let mut field = field.clone();
+        let blocks = placement.blocks();
+        for pos in blocks {
+            field.set(pos, placement.kind());
+        }
+        let removed = field.remove_filled_lines();
+        let eroded_blocks = blocks.iter().filter(|(_, y)| removed.contains(y)).count();
+
+        let height_of = |y: isize| (field.height() as isize - 1 - y) as f64;
+        let lowest = blocks.iter().map(|(_, y)| *y).max().unwrap();
+        let highest = blocks.iter().map(|(_, y)| *y).min().unwrap();
+
+        Self {
+            landing_height: (height_of(lowest) + height_of(highest)) / 2.0,
+            eroded_cells: (removed.len() * eroded_blocks) as f64,
+            row_transitions: row_transitions(&field) as f64,
+            column_transitions: column_transitions(&field) as f64,
+            holes: field.holes() as f64,
+            wells: wells(&field) as f64,
+        }
+    }
+}
+
+fn is_filled(cell: Cell) -> bool {
+    cell != Cell::Empty
+}
+
+fn row_transitions(field: &Field) -> usize {
+    field
+        .as_vec_full()
+        .iter()
+        .filter(|row| row.iter().copied().any(is_filled))
+        .map(|row| {
+            let cells: Vec<_> = [true]
+                .into_iter()
+                .chain(row.iter().copied().map(is_filled))
+                .chain([true])
+                .collect();
+            cells.windows(2).filter(|w| w[0] != w[1]).count()
+        })
+        .sum()
+}
+
+fn column_transitions(field: &Field) -> usize {
+    (0..field.width() as isize)
+        .map(|x| {
+            let cells: Vec<_> = field
+                .column(x)
+                .map(|(_, cell)| is_filled(cell))
+                .chain([true])
+                .collect();
+            cells.windows(2).filter(|w| w[0] != w[1]).count()
+        })
+        .sum()
+}
+
+fn wells(field: &Field) -> usize {
+    let filled = |x: isize, y: isize| is_filled(field.get_cell((x, y)));
+    (0..field.width() as isize)
+        .map(|x| {
+            let mut depth = 0;
+            let mut sum = 0;
+            for (y, cell) in field.column(x) {
+                if !is_filled(cell) && filled(x - 1, y) && filled(x + 1, y) {
+                    depth += 1;
+                    sum += depth;
+                } else {
+                    depth = 0;
+                }
+            }
+            sum
+        })
+        .sum()
+}
+
+/// The weights of the features, which make a score of a placement.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Weights {
+    /// The weight of `Features::landing_height`.
+    pub landing_height: f64,
+    /// The weight of `Features::eroded_cells`.
+    pub eroded_cells: f64,
+    /// The weight of `Features::row_transitions`.
+    pub row_transitions: f64,
+    /// The weight of `Features::column_transitions`.
+    pub column_transitions: f64,
+    /// The weight of `Features::holes`.
+    pub holes: f64,
+    /// The weight of `Features::wells`.
+    pub wells: f64,
+}
+
+impl Default for Weights {
+    /// The original weights by Dellacherie.
+    fn default() -> Self {
+        Self {
+            landing_height: -1.0,
+            eroded_cells: 1.0,
+            row_transitions: -1.0,
+            column_transitions: -1.0,
+            holes: -4.0,
+            wells: -1.0,
+        }
+    }
+}
+
+impl Weights {
+    /// The weights tuned by El-Tetris.
+    pub fn el_tetris() -> Self {
+        Self {
+            landing_height: -4.500158825082766,
+            eroded_cells: 3.4181268101392694,
+            row_transitions: -3.2178882868487753,
+            column_transitions: -9.348695305445199,
+            holes: -7.899265427351652,
+            wells: -3.3855972247263626,
+        }
+    }
+
+    /// Get the weighted sum of the features. Higher is better.
+    pub fn score(&self, features: &Features) -> f64 {
+        self.landing_height * features.landing_height
+            + self.eroded_cells * features.eroded_cells
+            + self.row_transitions * features.row_transitions
+            + self.column_transitions * features.column_transitions
+            + self.holes * features.holes
+            + self.wells * features.wells
+    }
+
+    /// Compute the features of the placement and get their weighted sum.
+    pub fn evaluate(&self, field: &Field, placement: &Tetrimino) -> f64 {
+        self.score(&Features::new(field, placement))
+    }
+}
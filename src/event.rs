@@ -0,0 +1,102 @@
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::{BlockKind, LockResult};
+
+/// The reason why a game has ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EndReason {
+    /// A tetrimino was saved completely in the non-visible area.
+    LockOut,
+    /// A new tetrimino couldn't appear because the blocks were in the way.
+    BlockOut,
+    /// Garbage lines pushed blocks out of the field.
+    GarbageOut,
+}
+
+/// Something happened in a game, taken by `Game::take_events`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameEvent {
+    /// A new tetrimino appeared.
+    PieceSpawned(BlockKind),
+    /// The tetrimino moved by the distance, by the player or the gravity.
+    PieceMoved { dx: isize, dy: isize },
+    /// The tetrimino rotated, moved by the kick.
+    PieceRotated { kick: (isize, isize) },
+    /// The tetrimino was hard-dropped by the distance.
+    HardDropped { distance: usize },
+    /// The tetrimino was saved to the field.
+    Locked(LockResult),
+    /// Lines were removed. The rows are the y coordinates before removing.
+    /// Each chain of the cascade is reported separately.
+    LinesCleared { rows: Vec<isize> },
+    /// The tetrimino was held.
+    Held(BlockKind),
+    /// Garbage lines were inserted.
+    GarbageReceived { lines: usize },
+    /// The game ended.
+    GameOver { reason: EndReason },
+}
+
+// The listener of a game. Clones of the game don't have it, since the
+// listener can't be cloned.
+#[derive(Default)]
+pub(crate) struct ListenerSlot(pub(crate) Option<Box<dyn GameListener>>);
+
+impl Clone for ListenerSlot {
+    fn clone(&self) -> Self {
+        Self(None)
+    }
+}
+
+/// An observer of a game, set by `Game::set_listener`, which is called as
+/// soon as something happens instead of polling `Game::take_events`.
+///
+/// Every method does nothing by default, so implement only the ones needed.
+/// Listeners must be `Send` and `Sync`, as explained in
+/// [`SendGame`](crate::SendGame).
+pub trait GameListener: Send + Sync {
+    /// Called for every event, in the same order as `Game::take_events`.
+    /// By default, this calls the method for the event below.
+    fn on_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::PieceSpawned(kind) => self.on_spawn(*kind),
+            GameEvent::PieceMoved { dx, dy } => self.on_move(*dx, *dy),
+            GameEvent::PieceRotated { kick } => self.on_rotate(*kick),
+            GameEvent::HardDropped { distance } => self.on_hard_drop(*distance),
+            GameEvent::Locked(result) => self.on_lock(result),
+            GameEvent::LinesCleared { rows } => self.on_clear(rows),
+            GameEvent::Held(kind) => self.on_hold(*kind),
+            GameEvent::GarbageReceived { lines } => self.on_garbage(*lines),
+            GameEvent::GameOver { reason } => self.on_top_out(*reason),
+        }
+    }
+
+    /// A new tetrimino appeared.
+    fn on_spawn(&mut self, _kind: BlockKind) {}
+
+    /// The tetrimino moved by the distance, by the player or the gravity.
+    fn on_move(&mut self, _dx: isize, _dy: isize) {}
+
+    /// The tetrimino rotated, moved by the kick.
+    fn on_rotate(&mut self, _kick: (isize, isize)) {}
+
+    /// The tetrimino was hard-dropped by the distance.
+    fn on_hard_drop(&mut self, _distance: usize) {}
+
+    /// The tetrimino was saved to the field.
+    fn on_lock(&mut self, _result: &LockResult) {}
+
+    /// Lines were removed, like `GameEvent::LinesCleared`.
+    fn on_clear(&mut self, _rows: &[isize]) {}
+
+    /// The tetrimino was held.
+    fn on_hold(&mut self, _kind: BlockKind) {}
+
+    /// Garbage lines were inserted.
+    fn on_garbage(&mut self, _lines: usize) {}
+
+    /// The game ended.
+    fn on_top_out(&mut self, _reason: EndReason) {}
+}
@@ -0,0 +1,285 @@
+//! Bindings for C, enabled by the `ffi` feature.
+//!
+//! A game is created by `tetrice_game_new` and must be freed by
+//! `tetrice_game_free`. The other functions take the pointer to the game,
+//! which must be valid. The header is `include/tetrice.h`.
+//!
+//! Cells and block kinds are numbers: 0 is empty, 1 to 7 are O, I, Z, S, L,
+//! T and J, 8 is garbage, 9 is solid and 10 is a line being cleared.
+
+use std::panic::catch_unwind;
+use std::time::Duration;
+
+use crate::selectors::Bag;
+use crate::{Game, Input, Tetrimino};
+
+/// A game with the 7-bag randomizer, opaque to C.
+pub struct TetriceGame(Game<Bag>);
+
+/// Create a game with the 7-bag randomizer using the seed. Returns null if
+/// the field is too small or too wide.
+#[no_mangle]
+pub extern "C" fn tetrice_game_new(
+    width: usize,
+    height: usize,
+    queue_size: usize,
+    seed: u64,
+) -> *mut TetriceGame {
+    match catch_unwind(|| Game::with_seed(width, height, queue_size, seed)) {
+        Ok(game) => Box::into_raw(Box::new(TetriceGame(game))),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Free a game. Does nothing if it is null.
+///
+/// # Safety
+///
+/// `game` must be null or made by `tetrice_game_new`, and must not be used
+/// after this.
+#[no_mangle]
+pub unsafe extern "C" fn tetrice_game_free(game: *mut TetriceGame) {
+    if !game.is_null() {
+        drop(Box::from_raw(game));
+    }
+}
+
+unsafe fn apply(game: *mut TetriceGame, input: Input) -> bool {
+    (*game).0.apply(input).applied
+}
+
+/// Move the tetrimino to the left. Returns true if it moved.
+///
+/// # Safety
+///
+/// `game` must be a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn tetrice_game_move_left(game: *mut TetriceGame) -> bool {
+    apply(game, Input::Left)
+}
+
+/// Move the tetrimino to the right. Returns true if it moved.
+///
+/// # Safety
+///
+/// `game` must be a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn tetrice_game_move_right(game: *mut TetriceGame) -> bool {
+    apply(game, Input::Right)
+}
+
+/// Move down the tetrimino. Returns true if it moved.
+///
+/// # Safety
+///
+/// `game` must be a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn tetrice_game_soft_drop(game: *mut TetriceGame) -> bool {
+    apply(game, Input::SoftDrop)
+}
+
+/// Drop the tetrimino to the position of the ghost.
+///
+/// # Safety
+///
+/// `game` must be a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn tetrice_game_hard_drop(game: *mut TetriceGame) -> bool {
+    apply(game, Input::HardDrop)
+}
+
+/// Rotate the tetrimino clockwise. Returns true if it rotated.
+///
+/// # Safety
+///
+/// `game` must be a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn tetrice_game_rotate_cw(game: *mut TetriceGame) -> bool {
+    apply(game, Input::RotateCw)
+}
+
+/// Rotate the tetrimino counter-clockwise. Returns true if it rotated.
+///
+/// # Safety
+///
+/// `game` must be a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn tetrice_game_rotate_ccw(game: *mut TetriceGame) -> bool {
+    apply(game, Input::RotateCcw)
+}
+
+/// Hold the tetrimino. Returns true if it was held.
+///
+/// # Safety
+///
+/// `game` must be a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn tetrice_game_hold(game: *mut TetriceGame) -> bool {
+    apply(game, Input::Hold)
+}
+
+/// Save the tetrimino to the field. Returns true if it was saved.
+///
+/// # Safety
+///
+/// `game` must be a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn tetrice_game_lock(game: *mut TetriceGame) -> bool {
+    apply(game, Input::Lock)
+}
+
+/// Advance the time by the milliseconds. Returns the number of lines
+/// removed by the tetrimino locked during the time.
+///
+/// # Safety
+///
+/// `game` must be a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn tetrice_game_update(game: *mut TetriceGame, millis: u32) -> usize {
+    let result = (*game).0.update(Duration::from_millis(millis as u64));
+    result.locked.map_or(0, |locked| locked.lines())
+}
+
+/// Get the width of the field.
+///
+/// # Safety
+///
+/// `game` must be a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn tetrice_game_width(game: *const TetriceGame) -> usize {
+    (*game).0.field().width()
+}
+
+/// Get the height of the visible area.
+///
+/// # Safety
+///
+/// `game` must be a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn tetrice_game_height(game: *const TetriceGame) -> usize {
+    (*game).0.field().height()
+}
+
+/// Write the cells of the visible area row by row from the top into `out`,
+/// up to `len` cells. Returns the number of the cells in the visible area,
+/// so passing null and 0 gets the size of the buffer needed.
+///
+/// # Safety
+///
+/// `game` must be a valid game, and `out` must be valid for `len` writes.
+#[no_mangle]
+pub unsafe extern "C" fn tetrice_game_field_cells(
+    game: *const TetriceGame,
+    out: *mut u8,
+    len: usize,
+) -> usize {
+    let field = (*game).0.field();
+    let cells = field.visible().iter().flatten().map(|cell| cell.to_code());
+    write(cells, out, len);
+    field.width() * field.height()
+}
+
+/// Get the kind of the current tetrimino.
+///
+/// # Safety
+///
+/// `game` must be a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn tetrice_game_tetrimino_kind(game: *const TetriceGame) -> u8 {
+    (*game).0.tetrimino().kind() as u8 + 1
+}
+
+/// Write the positions of the blocks of the current tetrimino into `out` as
+/// `x0, y0, x1, y1, ...`, which needs 8 numbers.
+///
+/// # Safety
+///
+/// `game` must be a valid game, and `out` must be valid for 8 writes.
+#[no_mangle]
+pub unsafe extern "C" fn tetrice_game_tetrimino_blocks(game: *const TetriceGame, out: *mut i32) {
+    write_blocks((*game).0.tetrimino(), out);
+}
+
+/// Same as `tetrice_game_tetrimino_blocks`, but for the ghost.
+///
+/// # Safety
+///
+/// `game` must be a valid game, and `out` must be valid for 8 writes.
+#[no_mangle]
+pub unsafe extern "C" fn tetrice_game_ghost_blocks(game: *const TetriceGame, out: *mut i32) {
+    write_blocks(&(*game).0.ghost(), out);
+}
+
+/// Write the kinds of the next tetriminos into `out`, up to `len` kinds.
+/// Returns the number of the next tetriminos.
+///
+/// # Safety
+///
+/// `game` must be a valid game, and `out` must be valid for `len` writes.
+#[no_mangle]
+pub unsafe extern "C" fn tetrice_game_queue(
+    game: *const TetriceGame,
+    out: *mut u8,
+    len: usize,
+) -> usize {
+    let game = &(*game).0;
+    write(game.queue_shapes().map(|kind| kind as u8 + 1), out, len);
+    game.queue_shapes().count()
+}
+
+/// Get the kind of the held tetrimino, or 0 if nothing is held.
+///
+/// # Safety
+///
+/// `game` must be a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn tetrice_game_held(game: *const TetriceGame) -> u8 {
+    (*game).0.held().map_or(0, |t| t.kind() as u8 + 1)
+}
+
+/// Get the points.
+///
+/// # Safety
+///
+/// `game` must be a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn tetrice_game_score(game: *const TetriceGame) -> u64 {
+    (*game).0.score().points()
+}
+
+/// Get the number of removed lines.
+///
+/// # Safety
+///
+/// `game` must be a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn tetrice_game_lines(game: *const TetriceGame) -> usize {
+    (*game).0.removed_lines()
+}
+
+/// Returns true if the game has ended.
+///
+/// # Safety
+///
+/// `game` must be a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn tetrice_game_is_end(game: *const TetriceGame) -> bool {
+    (*game).0.is_end()
+}
+
+unsafe fn write<T>(values: impl Iterator<Item = T>, out: *mut T, len: usize) {
+    if out.is_null() {
+        return;
+    }
+    for (i, value) in values.take(len).enumerate() {
+        out.add(i).write(value);
+    }
+}
+
+unsafe fn write_blocks(tetrimino: &Tetrimino, out: *mut i32) {
+    let coords = tetrimino
+        .blocks()
+        .into_iter()
+        .flat_map(|(x, y)| [x as i32, y as i32]);
+    write(coords, out, 8);
+}
@@ -1,29 +1,178 @@
+use alloc::{string::String, vec, vec::Vec};
+
+use crate::random::Rng;
 use crate::BlockKind;
 
-const HEIGHT_NEG: usize = 7;
+/// The default height of the non-visible area.
+pub(crate) const DEFAULT_HIDDEN_HEIGHT: usize = 7;
+
+/// The maximum width of fields, which is the number of bits of a row mask.
+pub const MAX_WIDTH: usize = 64;
 
 /// A game field.
 ///
 /// This consists of the visible (y>0) and non-visible (y<0) areas.
+///
+/// Fields are equal if they have the same cells in both areas, whatever
+/// their revisions are.
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "RawField", into = "RawField")
+)]
 pub struct Field {
     state: Vec<Vec<Cell>>,
+    // The occupancy of each row, where bit `x` is set if the cell at `x` is
+    // not empty.
+    masks: Vec<u64>,
+    hidden_height: usize,
+    revision: u64,
+    // The revision when each row was changed last.
+    row_revisions: Vec<u64>,
+}
+
+// The serialized form of `Field`, which doesn't contain the masks and the
+// revisions.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct RawField {
+    state: Vec<Vec<Cell>>,
+    hidden_height: usize,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawField> for Field {
+    type Error = ParseFieldError;
+
+    fn try_from(raw: RawField) -> Result<Self, Self::Error> {
+        let width = raw.state.first().map_or(0, Vec::len);
+        if width == 0 {
+            return Err(ParseFieldError::Empty);
+        } else if width > MAX_WIDTH {
+            return Err(ParseFieldError::TooWide);
+        }
+        if let Some(line) = raw.state.iter().position(|row| row.len() != width) {
+            return Err(ParseFieldError::UnevenRows { line });
+        }
+        if raw.hidden_height > raw.state.len() {
+            return Err(ParseFieldError::TooManyHiddenRows);
+        }
+        Ok(Self::from_rows(raw.state, raw.hidden_height))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Field> for RawField {
+    fn from(field: Field) -> Self {
+        Self {
+            state: field.state,
+            hidden_height: field.hidden_height,
+        }
+    }
+}
+
+fn mask_of(row: &[Cell]) -> u64 {
+    row.iter()
+        .enumerate()
+        .filter(|(_, cell)| **cell != Cell::Empty)
+        .fold(0, |mask, (x, _)| mask | 1 << x)
 }
 
 impl Field {
-    pub(crate) fn new(width: usize, height: usize) -> Self {
-        Self::from_vec(vec![vec![Cell::Empty; width]; height + HEIGHT_NEG])
+    /// Create an empty field with the size of the visible area and the height
+    /// of the non-visible area.
+    ///
+    /// Panics if the width is greater than `MAX_WIDTH`.
+    pub fn new(width: usize, height: usize, hidden_height: usize) -> Self {
+        Self::from_rows(
+            vec![vec![Cell::Empty; width]; height + hidden_height],
+            hidden_height,
+        )
+    }
+
+    pub(crate) fn from_rows(state: Vec<Vec<Cell>>, hidden_height: usize) -> Self {
+        if state.first().map_or(0, Vec::len) > MAX_WIDTH {
+            panic!("too much width")
+        }
+        Self {
+            masks: state.iter().map(|row| mask_of(row)).collect(),
+            row_revisions: vec![0; state.len()],
+            state,
+            hidden_height,
+            revision: 0,
+        }
+    }
+
+    /// Get the revision, which increases every time this field is changed.
+    /// A new field starts from revision 0.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Get the y coordinates of the rows changed after the revision, from the
+    /// top. This includes the rows moved by removing or pushing up lines.
+    pub fn dirty_rows_since(&self, revision: u64) -> Vec<isize> {
+        (0..)
+            .zip(&self.row_revisions)
+            .filter(|(_, rev)| **rev > revision)
+            .map(|(y, _)| y - self.hidden_height as isize)
+            .collect()
+    }
+
+    /// Get the Zobrist hash of the cells. Fields with the same cells have the
+    /// same hash, and different fields have different hashes in most cases.
+    pub fn zobrist_hash(&self) -> u64 {
+        let mut hash = 0;
+        for (y, row) in self.state.iter().enumerate() {
+            if self.masks[y] == 0 {
+                continue;
+            }
+            for (x, cell) in row.iter().enumerate() {
+                let kind = match cell {
+                    Cell::Block(kind) => *kind as u64,
+                    Cell::Garbage => 7,
+                    Cell::Solid => 8,
+                    Cell::Clearing => 9,
+                    Cell::Empty | Cell::Outside => continue,
+                };
+                hash ^= Rng::key((y as u64) << 32 | (x as u64) << 8 | kind);
+            }
+        }
+        hash
+    }
+
+    // Start a new revision and mark the rows in `range` as changed.
+    fn touch(&mut self, range: core::ops::Range<usize>) {
+        self.revision += 1;
+        self.row_revisions[range].fill(self.revision);
     }
 
+    #[cfg(test)]
     pub(crate) fn from_vec(state: Vec<Vec<Cell>>) -> Self {
-        Self { state }
+        Self::from_rows(state, DEFAULT_HIDDEN_HEIGHT)
     }
 
-    /// Get the visible area as an 2D-Vec.
-    ///
-    /// The value at every position is one of the tetrimino colors if a block
-    /// exists, and an empty string otherwise.
+    /// Get the visible area as an 2D-Vec. This is the same as `visible`.
     pub fn as_vec(&self) -> &[Vec<Cell>] {
-        &self.state[HEIGHT_NEG..]
+        self.visible()
+    }
+
+    /// Get the whole field as an 2D-Vec, including the non-visible area. The
+    /// first row is at y = `-hidden_height`.
+    pub fn as_vec_full(&self) -> &[Vec<Cell>] {
+        &self.state
+    }
+
+    /// Get the visible area, whose first row is at y = 0.
+    pub fn visible(&self) -> &[Vec<Cell>] {
+        &self.state[self.hidden_height..]
+    }
+
+    /// Get the non-visible area above the visible area, whose first row is at
+    /// y = `-hidden_height`.
+    pub fn hidden(&self) -> &[Vec<Cell>] {
+        &self.state[..self.hidden_height]
     }
 
     /// Get the width.
@@ -33,52 +182,635 @@ impl Field {
 
     /// Get the height of the visible area.
     pub fn height(&self) -> usize {
-        self.state.len() - HEIGHT_NEG
+        self.state.len() - self.hidden_height
+    }
+
+    /// Get the height of the non-visible area.
+    pub fn hidden_height(&self) -> usize {
+        self.hidden_height
+    }
+
+    /// Iterate over the rows from the top of the non-visible area, with their
+    /// y coordinates.
+    pub fn rows(&self) -> impl Iterator<Item = (isize, &[Cell])> + '_ {
+        let top = -(self.hidden_height as isize);
+        (top..).zip(self.state.iter().map(Vec::as_slice))
+    }
+
+    /// Iterate over all the cells row by row from the top left, including the
+    /// non-visible area.
+    pub fn iter_cells(&self) -> impl Iterator<Item = ((isize, isize), Cell)> + '_ {
+        self.rows()
+            .flat_map(|(y, row)| (0..).zip(row.iter()).map(move |(x, cell)| ((x, y), *cell)))
+    }
+
+    /// Iterate over the cells in the column from the top of the non-visible
+    /// area, with their y coordinates.
+    ///
+    /// Panics if `x` is out of this field.
+    pub fn column(&self, x: isize) -> impl Iterator<Item = (isize, Cell)> + '_ {
+        assert!(
+            (0..self.width() as isize).contains(&x),
+            "the column is out of the field"
+        );
+        self.rows().map(move |(y, row)| (y, row[x as usize]))
+    }
+
+    /// Get the height of each column, which is the distance from the bottom
+    /// to the top of the highest block. Blocks in the non-visible area are
+    /// counted, so a height can be greater than the height of the visible area.
+    pub fn column_heights(&self) -> Vec<usize> {
+        (0..self.width())
+            .map(|x| {
+                self.state
+                    .iter()
+                    .position(|row| row[x] != Cell::Empty)
+                    .map_or(0, |y| self.state.len() - y)
+            })
+            .collect()
+    }
+
+    /// Get the sum of the heights of the columns.
+    pub fn aggregate_height(&self) -> usize {
+        self.column_heights().iter().sum()
+    }
+
+    /// Get the number of empty cells which have a block somewhere above them.
+    pub fn holes(&self) -> usize {
+        let heights = self.column_heights();
+        heights
+            .iter()
+            .enumerate()
+            .map(|(x, height)| {
+                self.state[self.state.len() - height..]
+                    .iter()
+                    .filter(|row| row[x] == Cell::Empty)
+                    .count()
+            })
+            .sum()
+    }
+
+    /// Get the sum of the height differences between adjacent columns.
+    pub fn bumpiness(&self) -> usize {
+        self.column_heights()
+            .windows(2)
+            .map(|pair| pair[0].abs_diff(pair[1]))
+            .sum()
+    }
+
+    /// Get the depth of the well in each column, which is how much lower the
+    /// column is than the lower of its neighbors. The walls are regarded as
+    /// infinitely high.
+    pub fn wells(&self) -> Vec<usize> {
+        let heights = self.column_heights();
+        (0..heights.len())
+            .map(|x| {
+                let left = x.checked_sub(1).map_or(usize::MAX, |l| heights[l]);
+                let right = heights.get(x + 1).copied().unwrap_or(usize::MAX);
+                left.min(right).saturating_sub(heights[x])
+            })
+            .collect()
     }
 
     /// Get the color at the given position. If there are no blocks, returns
     /// `Cell::None`. If the position is out of this field, returns `Cell::Outside`.
     pub fn get_cell(&self, (x, y): (isize, isize)) -> Cell {
         let width = self.width() as isize;
-        let height_min = -(HEIGHT_NEG as isize);
+        let height_min = -(self.hidden_height as isize);
         let height_max = self.height() as isize;
         if (0..width).contains(&x) && (height_min..height_max).contains(&y) {
             let x = x as usize;
-            let y = (y + HEIGHT_NEG as isize) as usize;
+            let y = (y + self.hidden_height as isize) as usize;
             self.state[y][x]
         } else {
             Cell::Outside
         }
     }
 
+    /// Get the occupancy of the row, where bit `x` is set if the cell at `x`
+    /// is not empty. The rows out of this field are regarded as filled, so
+    /// all the bits are set.
+    pub fn row_mask(&self, y: isize) -> u64 {
+        let y = y + self.hidden_height as isize;
+        if (0..self.state.len() as isize).contains(&y) {
+            self.masks[y as usize]
+        } else {
+            u64::MAX
+        }
+    }
+
+    // The mask of a row whose cells are all filled.
+    fn full_mask(&self) -> u64 {
+        u64::MAX >> (MAX_WIDTH - self.width())
+    }
+
+    /// Returns true if the cell at the position isn't empty, including
+    /// outside this field. This is faster than `get_cell`.
+    pub(crate) fn is_occupied(&self, (x, y): (isize, isize)) -> bool {
+        !(0..self.width() as isize).contains(&x) || self.row_mask(y) & 1 << x != 0
+    }
+
     pub(crate) fn set(&mut self, (x, y): (isize, isize), kind: BlockKind) {
         let x = x as usize;
-        let y = (y + HEIGHT_NEG as isize) as usize;
+        let y = (y + self.hidden_height as isize) as usize;
         self.state[y][x] = Cell::Block(kind);
+        self.masks[y] |= 1 << x;
+        self.touch(y..y + 1);
+    }
+
+    // The index of the row at `y`, or `None` if it is out of this field.
+    fn row_index(&self, y: isize) -> Option<usize> {
+        let y = y + self.hidden_height as isize;
+        (0..self.state.len() as isize)
+            .contains(&y)
+            .then_some(y as usize)
+    }
+
+    /// Set the cell at the position, for editing fields. `Cell::Outside` is
+    /// set as `Cell::Empty`. Returns false and does nothing if the position
+    /// is out of this field.
+    pub fn set_cell(&mut self, (x, y): (isize, isize), cell: Cell) -> bool {
+        let Some(index) = self.row_index(y) else {
+            return false;
+        };
+        if !(0..self.width() as isize).contains(&x) {
+            return false;
+        }
+        let cell = if cell == Cell::Outside {
+            Cell::Empty
+        } else {
+            cell
+        };
+        self.put((x, y), cell);
+        self.touch(index..index + 1);
+        true
     }
 
-    pub(crate) fn remove_filled_lines(&mut self) -> usize {
-        let lines_not_filled: Vec<_> = self
+    /// Make the cell at the position empty. Returns false and does nothing if
+    /// the position is out of this field.
+    pub fn clear_cell(&mut self, pos: (isize, isize)) -> bool {
+        self.set_cell(pos, Cell::Empty)
+    }
+
+    /// Set all the cells in the row at `y`. Returns false and does nothing if
+    /// the row is out of this field.
+    pub fn fill_row(&mut self, y: isize, cell: Cell) -> bool {
+        let Some(index) = self.row_index(y) else {
+            return false;
+        };
+        let cell = if cell == Cell::Outside {
+            Cell::Empty
+        } else {
+            cell
+        };
+        self.state[index].fill(cell);
+        self.masks[index] = mask_of(&self.state[index]);
+        self.touch(index..index + 1);
+        true
+    }
+
+    /// Make a copy with each cell enlarged to `factor` by `factor` cells,
+    /// including the non-visible area. Big mode draws the field with this.
+    ///
+    /// Panics if the copy would be wider than `MAX_WIDTH`.
+    pub fn scaled(&self, factor: usize) -> Field {
+        let state = self
             .state
             .iter()
-            .filter(|line| !line.iter().all(|cell| *cell != Cell::Empty))
-            .cloned()
+            .flat_map(|row| {
+                let row: Vec<_> = row
+                    .iter()
+                    .flat_map(|cell| core::iter::repeat_n(*cell, factor))
+                    .collect();
+                core::iter::repeat_n(row, factor)
+            })
             .collect();
-        let count = self.state.len() - lines_not_filled.len();
+        Field::from_rows(state, self.hidden_height * factor)
+    }
 
-        self.state = [vec![vec![Cell::Empty; 10]; count], lines_not_filled].concat();
+    /// Mirror the whole field horizontally. The blocks become the kinds of
+    /// `BlockKind::mirrored`, like the tetriminos of mirror mode.
+    pub fn mirror(&mut self) {
+        for (row, mask) in self.state.iter_mut().zip(&mut self.masks) {
+            row.reverse();
+            for cell in row.iter_mut() {
+                if let Cell::Block(kind) = cell {
+                    *kind = kind.mirrored();
+                }
+            }
+            *mask = mask_of(row);
+        }
+        self.touch(0..self.state.len());
+    }
 
-        count
+    /// Make all the cells empty, including the non-visible area.
+    pub fn clear(&mut self) {
+        for row in &mut self.state {
+            row.fill(Cell::Empty);
+        }
+        self.masks.fill(0);
+        self.touch(0..self.state.len());
+    }
+
+    /// Push up the whole field and put `rows` at the bottom. Returns false if
+    /// any blocks are pushed out of the top.
+    pub(crate) fn push_up(&mut self, rows: Vec<Vec<Cell>>) -> bool {
+        let count = rows.len().min(self.state.len());
+        let survived = self.masks[..count].iter().all(|mask| *mask == 0);
+        self.state.drain(..count);
+        self.masks.drain(..count);
+        for row in rows.into_iter().rev().take(count).rev() {
+            self.masks.push(mask_of(&row));
+            self.state.push(row);
+        }
+        if count > 0 {
+            self.touch(0..self.state.len());
+        }
+        survived
+    }
+
+    // Returns true if there are no blocks except solid ones.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.state
+            .iter()
+            .flatten()
+            .all(|cell| matches!(cell, Cell::Empty | Cell::Solid))
+    }
+
+    // Remove the filled lines and returns their y coordinates from top to
+    // bottom. The remaining rows are moved down in place, and the removed
+    // rows are reused as the new empty rows at the top.
+    pub(crate) fn remove_filled_lines(&mut self) -> Vec<isize> {
+        let full = self.full_mask();
+        let mut filled = Vec::new();
+        let mut write = self.state.len();
+        for read in (0..self.state.len()).rev() {
+            // A line is removable if it is filled and has no solid blocks.
+            if self.masks[read] == full && !self.state[read].contains(&Cell::Solid) {
+                filled.push(read as isize - self.hidden_height as isize);
+                continue;
+            }
+            write -= 1;
+            if write != read {
+                self.state.swap(read, write);
+                self.masks[write] = self.masks[read];
+            }
+        }
+        if filled.is_empty() {
+            return filled;
+        }
+
+        for y in 0..write {
+            self.state[y].fill(Cell::Empty);
+            self.masks[y] = 0;
+        }
+        filled.reverse();
+        // The rows above the lowest removed line are moved down.
+        let lowest = (*filled.last().unwrap() + self.hidden_height as isize) as usize;
+        self.touch(0..lowest + 1);
+
+        filled
+    }
+
+    /// Get the groups of blocks connected vertically or horizontally, each of
+    /// which is a list of positions. Solid blocks don't belong to any group.
+    pub fn connected_groups(&self) -> Vec<Vec<(isize, isize)>> {
+        let width = self.width();
+        let top = self.hidden_height as isize;
+        let mut visited = vec![false; self.state.len() * width];
+        let mut groups = Vec::new();
+        for (y, row) in self.state.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                if visited[y * width + x] || matches!(cell, Cell::Empty | Cell::Solid) {
+                    continue;
+                }
+                visited[y * width + x] = true;
+                let mut group = Vec::new();
+                let mut stack = vec![(x as isize, y as isize - top)];
+                while let Some((x, y)) = stack.pop() {
+                    group.push((x, y));
+                    for next in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+                        if matches!(self.get_cell(next), Cell::Block(_) | Cell::Garbage) {
+                            let index = (next.1 + top) as usize * width + next.0 as usize;
+                            if !visited[index] {
+                                visited[index] = true;
+                                stack.push(next);
+                            }
+                        }
+                    }
+                }
+                groups.push(group);
+            }
+        }
+        groups
+    }
+
+    // Drop the groups of blocks which are floating until they land on
+    // something, keeping each group in its shape. Returns the groups which
+    // fell and their distances, in the order they fell.
+    pub(crate) fn drop_floating_groups(&mut self) -> Vec<(Vec<(isize, isize)>, isize)> {
+        let top = self.hidden_height as isize;
+        let mut lowest = None;
+        let mut moves = Vec::new();
+        loop {
+            let mut groups = self.connected_groups();
+            // The lower groups fall first so that the ones above can land on
+            // them.
+            groups.sort_by_key(|group| core::cmp::Reverse(group.iter().map(|p| p.1).max()));
+            let mut fell = false;
+            for group in groups {
+                let cells: Vec<_> = group.iter().map(|pos| self.get_cell(*pos)).collect();
+                for pos in &group {
+                    self.put(*pos, Cell::Empty);
+                }
+                let distance = (0..)
+                    .take_while(|d| {
+                        group
+                            .iter()
+                            .all(|(x, y)| !self.is_occupied((*x, y + d + 1)))
+                    })
+                    .count() as isize;
+                for ((x, y), cell) in group.iter().zip(cells) {
+                    self.put((*x, y + distance), cell);
+                }
+                if distance > 0 {
+                    fell = true;
+                    let bottom = group.iter().map(|p| p.1).max().unwrap() + distance;
+                    lowest = lowest.max(Some((bottom + top) as usize));
+                    moves.push((group, distance));
+                }
+            }
+            if !fell {
+                break;
+            }
+        }
+
+        if let Some(lowest) = lowest {
+            self.touch(0..lowest + 1);
+        }
+        moves
+    }
+
+    /// Parse the visible area from a map string used by web clients, which
+    /// lists the cells row by row from the top without separators:
+    ///
+    /// ```
+    /// use tetrice::Field;
+    ///
+    /// let field = Field::from_map_string("____T_____#TTT######", 10).unwrap();
+    /// assert_eq!(field.height(), 2);
+    /// assert_eq!(field.to_map_string(), "____T_____#TTT######");
+    /// ```
+    ///
+    /// The characters are the same as `from_str`, and `G` is also garbage.
+    /// Anything after `?`, such as the queue, is ignored. The non-visible area
+    /// has the default height and is empty.
+    pub fn from_map_string(map: &str, width: usize) -> Result<Self, ParseFieldError> {
+        if width > MAX_WIDTH {
+            return Err(ParseFieldError::TooWide);
+        }
+        let board = map.split('?').next().unwrap_or("").trim();
+        if width == 0 || board.is_empty() {
+            return Err(ParseFieldError::Empty);
+        }
+
+        let cells = board
+            .chars()
+            .enumerate()
+            .map(|(index, c)| match c {
+                'G' => Ok(Cell::Garbage),
+                _ => parse_cell(c).ok_or(ParseFieldError::UnknownChar {
+                    line: index / width,
+                    char: c,
+                }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if !cells.len().is_multiple_of(width) {
+            return Err(ParseFieldError::UnevenRows {
+                line: cells.len() / width,
+            });
+        }
+        let visible = cells.chunks(width).map(<[Cell]>::to_vec);
+        Ok(Self::from_rows(
+            vec![vec![Cell::Empty; width]; DEFAULT_HIDDEN_HEIGHT]
+                .into_iter()
+                .chain(visible)
+                .collect(),
+            DEFAULT_HIDDEN_HEIGHT,
+        ))
+    }
+
+    /// Write the visible area as a map string read by `from_map_string`.
+    /// Empty cells are `_`, garbage is `#` and lines being cleared are `-`.
+    pub fn to_map_string(&self) -> String {
+        self.visible()
+            .iter()
+            .flatten()
+            .map(|cell| match cell {
+                Cell::Block(kind) => kind.to_char(),
+                Cell::Garbage => '#',
+                Cell::Solid => '=',
+                Cell::Clearing => '-',
+                Cell::Empty | Cell::Outside => '_',
+            })
+            .collect()
+    }
+
+    // Copy the cells row by row and the masks into the buffers, reusing
+    // their memory.
+    pub(crate) fn copy_into(&self, cells: &mut Vec<Cell>, masks: &mut Vec<u64>) {
+        cells.clear();
+        for row in &self.state {
+            cells.extend_from_slice(row);
+        }
+        masks.clone_from(&self.masks);
+    }
+
+    // Restore the cells copied by `copy_into`. If the size is the same, only
+    // the rows which differ are copied and marked as changed.
+    pub(crate) fn copy_from(&mut self, cells: &[Cell], masks: &[u64], hidden_height: usize) {
+        let width = cells.len() / masks.len();
+        if self.masks.len() != masks.len()
+            || self.width() != width
+            || self.hidden_height != hidden_height
+        {
+            let revision = self.revision + 1;
+            *self = Self::from_rows(
+                cells.chunks(width).map(<[Cell]>::to_vec).collect(),
+                hidden_height,
+            );
+            self.revision = revision;
+            self.row_revisions.fill(revision);
+            return;
+        }
+
+        let revision = self.revision + 1;
+        for (y, row) in cells.chunks(width).enumerate() {
+            // Rows without blocks are the same if both masks are zero.
+            if self.masks[y] != masks[y] || (masks[y] != 0 && self.state[y] != row) {
+                self.state[y].copy_from_slice(row);
+                self.masks[y] = masks[y];
+                self.row_revisions[y] = revision;
+                self.revision = revision;
+            }
+        }
+    }
+
+    // Set the cell without touching the row.
+    fn put(&mut self, (x, y): (isize, isize), cell: Cell) {
+        let x = x as usize;
+        let y = (y + self.hidden_height as isize) as usize;
+        self.state[y][x] = cell;
+        if cell == Cell::Empty {
+            self.masks[y] &= !(1 << x);
+        } else {
+            self.masks[y] |= 1 << x;
+        }
+    }
+}
+
+impl core::str::FromStr for Field {
+    type Err = ParseFieldError;
+
+    /// Parse the visible area from a diagram like this:
+    ///
+    /// ```text
+    /// |          |
+    /// |    T     |
+    /// |   TTT  xx|
+    /// |IIII xxxxx|
+    /// ```
+    ///
+    /// Each row is enclosed by `|`. The letters of the block kinds are blocks,
+    /// `#` and `x` are garbage, `=` is a solid block, and a space, `_` and `.`
+    /// are empty. Blank lines are ignored. The non-visible area has the
+    /// default height and is empty.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut visible = Vec::new();
+        for (index, line) in s.lines().map(str::trim).enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let row = line
+                .strip_prefix('|')
+                .and_then(|line| line.strip_suffix('|'))
+                .ok_or(ParseFieldError::MissingBorder { line: index })?;
+            let row = row
+                .chars()
+                .map(|c| {
+                    parse_cell(c).ok_or(ParseFieldError::UnknownChar {
+                        line: index,
+                        char: c,
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if visible
+                .first()
+                .is_some_and(|first: &Vec<Cell>| first.len() != row.len())
+            {
+                return Err(ParseFieldError::UnevenRows { line: index });
+            }
+            visible.push(row);
+        }
+
+        let width = visible.first().map_or(0, Vec::len);
+        if width == 0 {
+            return Err(ParseFieldError::Empty);
+        } else if width > MAX_WIDTH {
+            return Err(ParseFieldError::TooWide);
+        }
+        Ok(Self::from_rows(
+            [
+                vec![vec![Cell::Empty; width]; DEFAULT_HIDDEN_HEIGHT],
+                visible,
+            ]
+            .concat(),
+            DEFAULT_HIDDEN_HEIGHT,
+        ))
     }
 }
 
-impl std::fmt::Debug for Field {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+fn parse_cell(c: char) -> Option<Cell> {
+    if let Some(kind) = BlockKind::from_char(c) {
+        return Some(Cell::Block(kind));
+    }
+    let cell = match c {
+        '#' | 'x' => Cell::Garbage,
+        '=' => Cell::Solid,
+        '-' => Cell::Clearing,
+        ' ' | '_' | '.' => Cell::Empty,
+        _ => return None,
+    };
+    Some(cell)
+}
+
+/// An error returned when parsing a field fails. The line numbers start
+/// from 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseFieldError {
+    /// There are no rows, or the rows have no cells.
+    Empty,
+    /// The rows are wider than `MAX_WIDTH`.
+    TooWide,
+    /// The row isn't enclosed by `|`.
+    MissingBorder { line: usize },
+    /// The row has a different width from the first row.
+    UnevenRows { line: usize },
+    /// The row has a character which isn't a cell.
+    UnknownChar { line: usize, char: char },
+    /// The height of the non-visible area is greater than the number of
+    /// rows, when deserializing a field.
+    TooManyHiddenRows,
+}
+
+impl core::fmt::Display for ParseFieldError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParseFieldError::Empty => write!(f, "the field has no cells"),
+            ParseFieldError::TooWide => write!(f, "the field is too wide"),
+            ParseFieldError::MissingBorder { line } => {
+                write!(f, "line {} is not enclosed by '|'", line)
+            }
+            ParseFieldError::UnevenRows { line } => {
+                write!(f, "line {} has a different width", line)
+            }
+            ParseFieldError::UnknownChar { line, char } => {
+                write!(f, "line {} has an unknown character {:?}", line, char)
+            }
+            ParseFieldError::TooManyHiddenRows => {
+                write!(f, "the non-visible area is taller than the field")
+            }
+        }
+    }
+}
+
+impl core::error::Error for ParseFieldError {}
+
+impl PartialEq for Field {
+    fn eq(&self, other: &Self) -> bool {
+        self.hidden_height == other.hidden_height && self.state == other.state
+    }
+}
+
+impl Eq for Field {}
+
+impl core::hash::Hash for Field {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.hidden_height.hash(state);
+        self.state.hash(state);
+    }
+}
+
+impl core::fmt::Debug for Field {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         self.as_vec().iter().try_for_each(|row| {
             write!(f, "|").and_then(|()| {
                 row.iter()
                     .try_for_each(|cell| match cell {
                         Cell::Block(kind) => kind.fmt(f),
+                        Cell::Garbage => write!(f, "#"),
+                        Cell::Solid => write!(f, "="),
+                        Cell::Clearing => write!(f, "-"),
                         Cell::Empty => write!(f, "_"),
                         Cell::Outside => write!(f, " "),
                     })
@@ -89,12 +821,47 @@ impl std::fmt::Debug for Field {
 }
 
 /// A state of cells in the field.
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Cell {
     /// Indicates there is a block in the cell. The kind is included as the value.
     Block(BlockKind),
+    /// Indicates there is a garbage block sent from the opponent.
+    Garbage,
+    /// Indicates there is a solid block, which is never removed even if the
+    /// line is filled.
+    Solid,
+    /// Indicates the cell is in a line being cleared, which stays visible
+    /// during the line clear delay (see `Game::set_line_clear_delay`).
+    Clearing,
     /// Indicates there is no block in the cell.
     Empty,
     /// Indicates the specified cell is out of the field.
     Outside,
 }
+
+impl Cell {
+    // A number for compact encodings: 0 is empty, 1 to 7 are the block kinds
+    // in the order of `BlockKind`, 8 is garbage, 9 is solid and 10 is a line
+    // being cleared.
+    pub(crate) fn to_code(self) -> u8 {
+        match self {
+            Cell::Empty | Cell::Outside => 0,
+            Cell::Block(kind) => kind as u8 + 1,
+            Cell::Garbage => 8,
+            Cell::Solid => 9,
+            Cell::Clearing => 10,
+        }
+    }
+
+    pub(crate) fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(Cell::Empty),
+            1..=7 => Some(Cell::Block(BlockKind::all_as_array()[code as usize - 1])),
+            8 => Some(Cell::Garbage),
+            9 => Some(Cell::Solid),
+            10 => Some(Cell::Clearing),
+            _ => None,
+        }
+    }
+}
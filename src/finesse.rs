@@ -0,0 +1,153 @@
+//! Finesse, which is placing tetriminos with as few key presses as possible.
+
+use alloc::{collections::VecDeque, vec};
+
+use crate::Map;
+
+use crate::{BlockKind, Field, Game, Input, PieceSelector, Tetrimino};
+
+/// A placement which took more key presses than needed, reported by
+/// `Game::apply` when the tetrimino is saved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FinesseFault {
+    /// The fewest key presses to reach the placement.
+    pub optimal: usize,
+    /// The key presses actually used.
+    pub presses: usize,
+}
+
+// The presses from where a tetrimino appears.
+#[derive(Debug, Clone, Copy)]
+enum Press {
+    Tap(isize),
+    Shift(isize),
+    Rotate(usize),
+}
+
+/// The fewest key presses to reach each placement on an empty field, by
+/// tapping and holding the direction keys and rotating.
+///
+/// Placements occupying the same cells, like the two horizontal states of an
+/// I-tetrimino, share the same count.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FinesseTable {
+    presses: Map<(BlockKind, [(isize, isize); 4]), usize>,
+}
+
+impl FinesseTable {
+    /// Compute the table for the field size, the rotation system and the
+    /// spawn rule of the game. If `use_180` is false, rotating by 180 degrees
+    /// isn't considered.
+    pub fn new<S: PieceSelector>(game: &Game<S>, use_180: bool) -> Self {
+        let mut probe = game.fork();
+        let field = game.field();
+        probe.field = Field::new(field.width(), field.height(), field.hidden_height());
+        probe.end_reason = None;
+        // The probe moves freely, even if the game is waiting for a delay or
+        // paused.
+        probe.delay = None;
+        probe.clearing_field = None;
+        probe.buffered_inputs.clear();
+        probe.paused = false;
+        probe.clear_lock_timer();
+        probe.set_events_enabled(false);
+
+        let mut presses_list = vec![
+            Press::Tap(-1),
+            Press::Tap(1),
+            Press::Shift(-1),
+            Press::Shift(1),
+            Press::Rotate(1),
+            Press::Rotate(3),
+        ];
+        if use_180 {
+            presses_list.push(Press::Rotate(2));
+        }
+
+        let mut presses = Map::new();
+        for kind in BlockKind::all_as_array() {
+            let start = probe.spawn_rule.place(kind, &probe.field);
+            let mut seen = Map::from([(start.clone(), 0)]);
+            let mut queue = VecDeque::from([start]);
+            while let Some(t) = queue.pop_front() {
+                let count = seen[&t];
+                presses.entry(key(&t)).or_insert(count);
+                for press in &presses_list {
+                    probe.tetrimino = t.clone();
+                    match press {
+                        Press::Tap(-1) => probe.move_left(),
+                        Press::Tap(_) => probe.move_right(),
+                        Press::Shift(-1) => probe.move_left_to_wall() > 0,
+                        Press::Shift(_) => probe.move_right_to_wall() > 0,
+                        Press::Rotate(1) => probe.rotate().rotated,
+                        Press::Rotate(2) => probe.rotate_180().rotated,
+                        Press::Rotate(_) => probe.rotate_ccw().rotated,
+                    };
+                    if !seen.contains_key(&probe.tetrimino) {
+                        seen.insert(probe.tetrimino.clone(), count + 1);
+                        queue.push_back(probe.tetrimino.clone());
+                    }
+                }
+            }
+        }
+        Self { presses }
+    }
+
+    /// Get the fewest key presses to place a tetrimino of `kind` in the
+    /// rotation state with its leftmost block at `column`. Returns `None` if
+    /// the placement is out of the field.
+    pub fn optimal_presses(
+        &self,
+        kind: BlockKind,
+        column: isize,
+        rotation: usize,
+    ) -> Option<usize> {
+        let t = Tetrimino::new(kind).rotate(rotation).move_to((column, 0));
+        self.optimal_presses_for(&t)
+    }
+
+    /// Same as `optimal_presses`, but for the placement of the tetrimino. Its
+    /// height doesn't matter.
+    pub fn optimal_presses_for(&self, tetrimino: &Tetrimino) -> Option<usize> {
+        self.presses.get(&key(tetrimino)).copied()
+    }
+}
+
+// The kind and the cells moved to the top, sorted.
+fn key(tetrimino: &Tetrimino) -> (BlockKind, [(isize, isize); 4]) {
+    let blocks = tetrimino.blocks();
+    let top = blocks.iter().map(|(_, y)| *y).min().unwrap();
+    let mut cells = blocks.map(|(x, y)| (x, y - top));
+    cells.sort();
+    (tetrimino.kind(), cells)
+}
+
+/// Count the key presses of the inputs applied to a tetrimino, with whether
+/// each of them changed the game. Only moves and rotations are counted.
+///
+/// A run of moves in the same direction which bumps into the wall counts as
+/// one press, as holding the key does. So does moving to the wall, which is
+/// part of the press before it if that moved in the same direction.
+pub fn count_presses(inputs: &[(Input, bool)]) -> usize {
+    let mut count = 0;
+    let mut previous = None;
+    let mut rest = inputs;
+    while let Some(((input, _), _)) = rest.split_first() {
+        let run = rest.iter().take_while(|(i, _)| i == input).count();
+        let (same, tail) = rest.split_at(run);
+        count += match input {
+            Input::Left | Input::Right if same.iter().any(|(_, applied)| !applied) => 1,
+            Input::LeftToWall if previous == Some(Input::Left) => 0,
+            Input::RightToWall if previous == Some(Input::Right) => 0,
+            Input::LeftToWall | Input::RightToWall => 1,
+            Input::Left | Input::Right | Input::RotateCw | Input::RotateCcw | Input::Rotate180 => {
+                run
+            }
+            _ => 0,
+        };
+        previous = Some(*input);
+        rest = tail;
+    }
+    count
+}
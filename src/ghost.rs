@@ -0,0 +1,13 @@
+/// How the ghost is found, which is also where hard drops land.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GhostMode {
+    /// The deepest place the tetrimino can reach by moving left, right or
+    /// down and rotating, like `Game::ghost` describes.
+    #[default]
+    Reachable,
+    /// The place right below the tetrimino, like a shadow.
+    StraightDown,
+    /// The ghost isn't drawn, and hard drops go straight down.
+    Disabled,
+}
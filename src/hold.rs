@@ -0,0 +1,53 @@
+/// A rule of holding tetriminos.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HoldRule {
+    /// The number of tetriminos which can be held at once. Holding is
+    /// disabled if this is 0.
+    ///
+    /// While the slots aren't full, holding takes the next tetrimino from the
+    /// queue. After that, the tetrimino held first comes out.
+    pub slots: usize,
+    /// Whether holding is allowed again before saving the tetrimino.
+    pub unlimited: bool,
+}
+
+impl HoldRule {
+    /// The standard rule, with one slot and one holding per tetrimino.
+    pub fn standard() -> Self {
+        Self {
+            slots: 1,
+            unlimited: false,
+        }
+    }
+
+    /// The rule disabling holding.
+    pub fn disabled() -> Self {
+        Self {
+            slots: 0,
+            unlimited: false,
+        }
+    }
+
+    /// The rule with one slot allowing any number of holdings, for practice.
+    pub fn unlimited() -> Self {
+        Self {
+            slots: 1,
+            unlimited: true,
+        }
+    }
+
+    /// The rule with `slots` slots and one holding per tetrimino.
+    pub fn with_slots(slots: usize) -> Self {
+        Self {
+            slots,
+            unlimited: false,
+        }
+    }
+}
+
+impl Default for HoldRule {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
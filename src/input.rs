@@ -0,0 +1,242 @@
+//! Inputs from the player.
+
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::finesse::FinesseFault;
+use crate::{Gravity, LockResult};
+
+/// An operation of the player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Input {
+    /// Move the tetrimino to the left.
+    Left,
+    /// Move the tetrimino to the right.
+    Right,
+    /// Move down the tetrimino.
+    SoftDrop,
+    /// Drop the tetrimino to the position of the ghost.
+    HardDrop,
+    /// Rotate the tetrimino clockwise.
+    RotateCw,
+    /// Rotate the tetrimino counter-clockwise.
+    RotateCcw,
+    /// Rotate the tetrimino by 180 degrees.
+    Rotate180,
+    /// Hold the tetrimino.
+    Hold,
+    /// Save the tetrimino to the field.
+    Lock,
+    /// Move the tetrimino straight down to the floor without saving it.
+    SonicDrop,
+    /// Move the tetrimino to the left as far as possible.
+    LeftToWall,
+    /// Move the tetrimino to the right as far as possible.
+    RightToWall,
+}
+
+impl Input {
+    pub(crate) const ALL: [Input; 12] = [
+        Input::Left,
+        Input::Right,
+        Input::SoftDrop,
+        Input::HardDrop,
+        Input::RotateCw,
+        Input::RotateCcw,
+        Input::Rotate180,
+        Input::Hold,
+        Input::Lock,
+        Input::SonicDrop,
+        Input::LeftToWall,
+        Input::RightToWall,
+    ];
+}
+
+/// What happened in `Game::apply`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActionResult {
+    /// Whether the input changed the game. Moving into a wall or anything
+    /// after end does nothing.
+    pub applied: bool,
+    /// The number of rows the tetrimino dropped.
+    pub dropped: usize,
+    /// The result of saving the tetrimino if the input saved it.
+    pub locked: Option<LockResult>,
+    /// The finesse fault of the saved tetrimino, if finesse is tracked and
+    /// the tetrimino took more key presses than needed.
+    pub finesse_fault: Option<FinesseFault>,
+    /// Whether the input was buffered during a delay, to be applied when the
+    /// next tetrimino appears (see `Game::set_are`).
+    pub buffered: bool,
+}
+
+/// The settings of the auto-repeat of keys, used by `AutoRepeat`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Handling {
+    /// Delayed auto shift, the time to hold a direction key until the
+    /// tetrimino starts moving repeatedly.
+    pub das: Duration,
+    /// Auto repeat rate, the time between the repeated moves. If it is zero,
+    /// the tetrimino moves to the wall instantly by `Input::LeftToWall` or
+    /// `Input::RightToWall`.
+    pub arr: Duration,
+    /// Soft drop factor, how many times faster than the gravity the
+    /// tetrimino falls while soft-dropping. If it is infinite, the tetrimino
+    /// falls to the floor instantly.
+    pub sdf: f64,
+}
+
+impl Default for Handling {
+    /// DAS of 10 frames, ARR of 2 frames and SDF of 20 at 60 FPS.
+    fn default() -> Self {
+        Self {
+            das: Duration::from_micros(166_667),
+            arr: Duration::from_micros(33_333),
+            sdf: 20.0,
+        }
+    }
+}
+
+/// A helper turning key presses and releases into the inputs to apply, which
+/// repeats moves and soft drops while the keys are held.
+///
+/// The times are measured from any fixed point, such as the start of the
+/// game, and must not go backwards.
+///
+/// ```
+/// use core::time::Duration;
+/// use tetrice::{Game, Input};
+/// use tetrice::input::AutoRepeat;
+///
+/// let mut game = Game::with_seed(10, 20, 3, 0);
+/// let mut auto_repeat = AutoRepeat::default();
+/// auto_repeat.key_down(Input::Left, Duration::ZERO);
+/// for input in auto_repeat.tick(Duration::from_millis(500), game.gravity()) {
+///     game.apply(input);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AutoRepeat {
+    handling: Handling,
+    keys: Vec<(Duration, Input, bool)>,
+    // The direction keys held, the last pressed at the end.
+    directions: Vec<Input>,
+    // The direction moving and the time of the next move.
+    shift: Option<(Input, Duration)>,
+    // The time of the next soft drop while the key is held.
+    soft_drop: Option<Duration>,
+}
+
+impl AutoRepeat {
+    /// Create a helper with the settings.
+    pub fn new(handling: Handling) -> Self {
+        Self {
+            handling,
+            ..Self::default()
+        }
+    }
+
+    /// Get the settings.
+    pub fn handling(&self) -> Handling {
+        self.handling
+    }
+
+    /// Change the settings, which affects the keys held already.
+    pub fn set_handling(&mut self, handling: Handling) {
+        self.handling = handling;
+    }
+
+    /// Record that the key for the input was pressed at the time.
+    pub fn key_down(&mut self, input: Input, at: Duration) {
+        self.keys.push((at, input, true));
+    }
+
+    /// Record that the key for the input was released at the time.
+    pub fn key_up(&mut self, input: Input, at: Duration) {
+        self.keys.push((at, input, false));
+    }
+
+    /// Get the inputs to apply until the time, in order. `gravity` decides
+    /// the speed of soft drops.
+    pub fn tick(&mut self, now: Duration, gravity: Gravity) -> Vec<Input> {
+        let mut inputs = Vec::new();
+        for (at, input, down) in core::mem::take(&mut self.keys) {
+            self.repeat(at, gravity, &mut inputs);
+            if down {
+                self.press(input, at, &mut inputs);
+            } else {
+                self.release(input, at);
+            }
+        }
+        self.repeat(now, gravity, &mut inputs);
+        inputs
+    }
+
+    fn press(&mut self, input: Input, at: Duration, inputs: &mut Vec<Input>) {
+        match input {
+            Input::Left | Input::Right => {
+                self.directions.retain(|i| *i != input);
+                self.directions.push(input);
+                self.shift = Some((input, at + self.handling.das));
+                inputs.push(input);
+            }
+            Input::SoftDrop => self.soft_drop = Some(at),
+            _ => inputs.push(input),
+        }
+    }
+
+    fn release(&mut self, input: Input, at: Duration) {
+        match input {
+            Input::Left | Input::Right => {
+                self.directions.retain(|i| *i != input);
+                if self.shift.is_some_and(|(i, _)| i == input) {
+                    // The other direction held starts charging again.
+                    self.shift = self.directions.last().map(|i| (*i, at + self.handling.das));
+                }
+            }
+            Input::SoftDrop => self.soft_drop = None,
+            _ => {}
+        }
+    }
+
+    // Push the repeated inputs until the time.
+    fn repeat(&mut self, until: Duration, gravity: Gravity, inputs: &mut Vec<Input>) {
+        if let Some((input, next)) = &mut self.shift {
+            if self.handling.arr.is_zero() {
+                let to_wall = match input {
+                    Input::Left => Input::LeftToWall,
+                    _ => Input::RightToWall,
+                };
+                if *next <= until && inputs.last() != Some(&to_wall) {
+                    inputs.push(to_wall);
+                }
+            } else {
+                while *next <= until {
+                    inputs.push(*input);
+                    *next += self.handling.arr;
+                }
+            }
+        }
+
+        let rate = gravity.as_cells_per_second() * self.handling.sdf;
+        if let Some(next) = &mut self.soft_drop {
+            if rate.is_infinite() {
+                if *next <= until {
+                    inputs.push(Input::SonicDrop);
+                }
+            } else if rate > 0.0 {
+                let interval = Duration::from_secs_f64(1.0 / rate);
+                while *next <= until {
+                    inputs.push(Input::SoftDrop);
+                    *next += interval;
+                }
+            } else if *next <= until {
+                // Without the gravity, the key drops the tetrimino just once.
+                inputs.push(Input::SoftDrop);
+                *next = Duration::MAX;
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use alloc::{vec, vec::Vec};
+use core::time::Duration;
+
+use crate::{Gravity, LockDelay};
+
+/// A table deciding the level from the removed lines, and the speed at each
+/// level.
+///
+/// Set it by `Game::set_level_table` or `GameBuilder::level_table`. Then the
+/// game updates the level of the score, the gravity and the lock delay every
+/// time a tetrimino is saved.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LevelTable {
+    /// The level at the start.
+    pub start_level: u64,
+    /// The number of lines to remove to go up one level.
+    pub lines_per_level: usize,
+    /// The gravity at each level, from level 1. The last value is used for
+    /// higher levels.
+    pub gravity: Vec<Gravity>,
+    /// The lock delay at each level, from level 1. The last value is used for
+    /// higher levels.
+    pub lock_delay: Vec<LockDelay>,
+}
+
+impl LevelTable {
+    /// The table of the Guideline, with 10 lines per level up to level 20.
+    ///
+    /// The tetrimino falls one row in (0.8 - (level - 1) * 0.007) ^ (level - 1)
+    /// seconds.
+    pub fn guideline() -> Self {
+        Self {
+            start_level: 1,
+            lines_per_level: 10,
+            gravity: (0..20)
+                .map(|n| {
+                    // `powi` needs `std`.
+                    let base = 0.8 - n as f64 * 0.007;
+                    let seconds = (0..n).fold(1.0, |s, _| s * base);
+                    Gravity::cells_per_second(1.0 / seconds)
+                })
+                .collect(),
+            lock_delay: vec![LockDelay::default()],
+        }
+    }
+
+    /// The table of the NES game, with 10 lines per level. The tetrimino is
+    /// saved as soon as it touches down.
+    ///
+    /// The levels of the NES game start from 0, so NES level 0 is level 1 here.
+    pub fn nes() -> Self {
+        const FRAMES_PER_ROW: [u32; 30] = [
+            48, 43, 38, 33, 28, 23, 18, 13, 8, 6, 5, 5, 5, 4, 4, 4, 3, 3, 3, 2, 2, 2, 2, 2, 2, 2,
+            2, 2, 2, 1,
+        ];
+        Self {
+            start_level: 1,
+            lines_per_level: 10,
+            gravity: FRAMES_PER_ROW
+                .iter()
+                .map(|frames| Gravity::from_g(1.0 / *frames as f64))
+                .collect(),
+            lock_delay: vec![LockDelay {
+                delay: Duration::ZERO,
+                max_resets: 0,
+            }],
+        }
+    }
+
+    /// Get the level after removing `lines` lines.
+    pub fn level(&self, lines: usize) -> u64 {
+        self.start_level + (lines / self.lines_per_level.max(1)) as u64
+    }
+
+    /// Get the gravity at the level.
+    pub fn gravity(&self, level: u64) -> Gravity {
+        lookup(&self.gravity, level).unwrap_or_default()
+    }
+
+    /// Get the lock delay at the level.
+    pub fn lock_delay(&self, level: u64) -> LockDelay {
+        lookup(&self.lock_delay, level).unwrap_or_default()
+    }
+}
+
+fn lookup<T: Copy>(table: &[T], level: u64) -> Option<T> {
+    let index = (level.max(1) - 1) as usize;
+    table.get(index).or(table.last()).copied()
+}
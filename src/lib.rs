@@ -3,7 +3,8 @@
 //! # How to use
 //!
 //! 1. Install this crate.
-//! 2. Make a block kind selector.
+//! 2. Make a block kind selector, or use one of the randomizers in
+//!    [`selectors`] such as the 7-bag.
 //!    ```
 //!    use tetrice::BlockKind;
 //!
@@ -18,91 +19,646 @@
 //!    # use tetrice::BlockKind;
 //!    # fn selector() -> BlockKind { BlockKind::T }
 //!
-//!    fn main() {
-//!        // Create a game which has a 10x20 field and provides 3 next tetriminos
-//!        let mut game = Game::new(10, 20, 3, Box::new(selector));
+//!    // Create a game which has a 10x20 field and provides 3 next tetriminos
+//!    let mut game = Game::new(10, 20, 3, Box::new(selector));
 //!
-//!        // Now you can move, rotate, etc. using `game`!
-//!    }
+//!    // Now you can move, rotate, etc. using `game`!
 //!    ```
+//!
+//! # Features
+//!
+//! The `std` feature is enabled by default. Without it, this crate is
+//! `no_std` and only needs `alloc`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
 
+pub mod attack;
+pub mod battle;
+#[cfg(feature = "bot")]
+pub mod bot;
+mod builder;
 mod checker;
+pub mod cheese;
+pub mod eval;
+mod event;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 mod field;
+pub mod finesse;
+mod ghost;
+mod hold;
+pub mod input;
+mod level;
+mod lock;
+pub mod modes;
+pub mod puzzle;
+mod random;
+mod render;
+pub mod replay;
+mod rotation;
+pub mod scoring;
+pub mod selectors;
+mod spawn;
+mod state;
+mod stats;
 mod tetrimino;
+mod timing;
+mod visibility;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
-#[cfg(test)]
+// The tests use `std`.
+#[cfg(all(test, feature = "std"))]
 mod tests;
 
-use std::cmp::Ordering;
-use std::collections::VecDeque;
+extern crate alloc;
+
+// Hash tables need `std`, so ordered ones are used instead without it.
+#[cfg(not(feature = "std"))]
+pub(crate) use alloc::collections::{BTreeMap as Map, BTreeSet as Set};
+#[cfg(feature = "std")]
+pub(crate) use std::collections::{HashMap as Map, HashSet as Set};
+
+use alloc::{boxed::Box, collections::VecDeque, sync::Arc, vec, vec::Vec};
+use core::time::Duration;
+
+use crate::finesse::{FinesseFault, FinesseTable};
+use crate::random::Rng;
+use crate::selectors::Bag;
 
-pub use checker::Checker;
-pub use field::{Cell, Field};
+pub use builder::{BuildError, GameBuilder};
+pub use checker::{Checker, Move, RouteSearch};
+use event::ListenerSlot;
+pub use event::{EndReason, GameEvent, GameListener};
+pub use field::{Cell, Field, ParseFieldError, MAX_WIDTH};
+pub use ghost::GhostMode;
+pub use hold::HoldRule;
+pub use input::{ActionResult, Input};
+pub use level::LevelTable;
+pub use lock::{ClearKind, HistoryEntry, LockResult};
+pub use render::Glyphs;
+pub use rotation::{Classic, KickSearch, NearSearch, RotationResult, RotationSystem, Srs};
+pub use scoring::{Score, SpinKind};
+pub use selectors::{GameView, PieceSelector};
+pub use spawn::{InitialActions, SpawnRule};
+pub use state::{GameState, RollbackSnapshot, Snapshot, StateDiff, StateError};
+pub use stats::Stats;
 pub use tetrimino::{BlockKind, Tetrimino};
+use timing::Delay;
+pub use timing::{Gravity, LockDelay, Phase, RisingFloor, TickResult};
+use visibility::LockTimes;
+pub use visibility::Visibility;
+
+impl Game<Bag> {
+    /// Create a new game with the 7-bag randomizer using the seed. The same
+    /// seed always gives the same tetriminos.
+    pub fn with_seed(width: usize, height: usize, queue_size: usize, seed: u64) -> Self {
+        Self::new(width, height, queue_size, Bag::seven(seed))
+    }
+
+    /// Create a new game with the 7-bag randomizer seeded by a random number
+    /// generator of the `rand` crate.
+    #[cfg(feature = "rand")]
+    pub fn from_rng(
+        width: usize,
+        height: usize,
+        queue_size: usize,
+        rng: &mut impl rand::RngCore,
+    ) -> Self {
+        Self::new(width, height, queue_size, Bag::from_rng(1, rng))
+    }
+
+    /// Get the kinds in the current bag which haven't appeared in the queue
+    /// yet, sorted in the order of `BlockKind::all_as_array`. This is empty
+    /// if the last kind in the queue was the last one in a bag.
+    pub fn remaining_in_bag(&self) -> Vec<BlockKind> {
+        let size = self.selector.size();
+        // The kinds selected but not appeared yet, after restoring a snapshot.
+        let pending = &self.dealt[self.dealt_pos - self.dealt_start..];
+        let drawn = size - self.selector.remaining().len();
+        let mut remaining = if pending.len() <= drawn {
+            [self.selector.remaining(), pending].concat()
+        } else {
+            let count = (pending.len() - drawn - 1) % size + 1;
+            pending[..count].to_vec()
+        };
+        let order = BlockKind::all_as_array();
+        remaining.sort_by_key(|kind| order.iter().position(|k| k == kind));
+        remaining
+    }
+}
+
+/// A game with a boxed selector which can be sent to other threads.
+///
+/// A game is `Send` if its selector is, and `Sync` if its selector is too,
+/// so games with the built-in randomizers are both. The rest of the game
+/// keeps this by its trait bounds:
+///
+/// - Rotation systems and scoring tables are shared with forks by `Arc`,
+///   which is `Send` only if the shared value is `Send` and `Sync`.
+/// - The listener is owned by the game, so it must be `Send` for the game
+///   to be `Send`, and `Sync` for the game to be `Sync`.
+///
+/// This type is only `Send`, since the boxed selector isn't `Sync`.
+pub type SendGame = Game<Box<dyn PieceSelector + Send>>;
+
+impl Game {
+    /// Create a builder to configure a game with more options.
+    pub fn builder() -> GameBuilder {
+        GameBuilder::new()
+    }
+}
 
 /// A game manager.
 ///
 /// Move or rotate the tetrimino so that it is always inside the field and it
 /// doesn't overlap to other blocks. Also have "next tetrimino" and "hold" system.
-pub struct Game {
+///
+/// `S` is the type of the selector. It is boxed by default, but a game
+/// created with a concrete selector keeps its type, which avoids dynamic
+/// dispatch and makes the game `Clone` if the selector is. Use `into_boxed`
+/// to convert it to the default type.
+#[derive(Clone)]
+pub struct Game<S = Box<dyn PieceSelector>> {
     field: Field,
     tetrimino: Tetrimino,
     queue: VecDeque<Tetrimino>,
-    held: Option<Tetrimino>,
-    selector: Box<dyn FnMut() -> BlockKind>,
+    held: VecDeque<Tetrimino>,
+    selector: S,
+    rotation_system: Arc<dyn RotationSystem>,
+    gravity: Gravity,
+    fall_progress: f64,
+    lock_delay: LockDelay,
+    lock_elapsed: Option<Duration>,
+    lock_resets: usize,
+    rising_floor: Option<RisingFloor>,
+    floor_elapsed: Duration,
+    floor_rng: Rng,
+    paused: bool,
+    paused_time: Duration,
+    line_clear_delay: Duration,
+    // The delay running after saving, with the time passed. The current
+    // tetrimino hasn't appeared yet while it runs.
+    delay: Option<(Delay, Duration)>,
+    // The field shown while lines are being cleared, which still has them.
+    clearing_field: Option<Field>,
+    are: Duration,
+    buffered_inputs: Vec<Input>,
+    visibility: Visibility,
+    lock_times: LockTimes,
+    big: bool,
+    mirrored: bool,
+    score: Score,
+    last_kick: Option<(isize, isize)>,
+    events_enabled: bool,
+    events: Vec<GameEvent>,
+    listener: ListenerSlot,
+    // The kinds selected so far, from the one at `dealt_start`. Forks drop
+    // the ones before their undo history.
+    dealt: Vec<BlockKind>,
+    dealt_start: usize,
+    dealt_pos: usize,
+    undo_limit: usize,
+    undo_stack: VecDeque<Snapshot>,
+    redo_stack: Vec<Snapshot>,
+    hold_rule: HoldRule,
+    level_table: Option<LevelTable>,
+    initial_actions: InitialActions,
+    spawn_rule: SpawnRule,
+    ghost_mode: GhostMode,
+    cascade: bool,
+    finesse: Option<Arc<FinesseTable>>,
+    // The inputs applied to the current tetrimino, with whether each of them
+    // changed the game. They are recorded only while finesse is tracked.
+    piece_inputs: Vec<(Input, bool)>,
+    finesse_faults: usize,
+    stats: Stats,
+    history_limit: usize,
+    history: VecDeque<HistoryEntry>,
     can_hold: bool,
-    is_end: bool,
+    end_reason: Option<EndReason>,
     removed_lines: usize,
 }
 
-impl Game {
+// Build a `Game` from the fields of `$game`, each cloned or moved by `$take`,
+// except the ones given in the braces. The fields are listed only here, so
+// converting games can't forget one.
+macro_rules! rebuild_game {
+    ($game:ident, $take:tt, { $($field:ident: $value:expr),* $(,)? }) => {
+        rebuild_game!(
+            @build $game, $take, { $($field: $value),* };
+            field, tetrimino, queue, held, rotation_system, gravity, fall_progress,
+            lock_delay, lock_elapsed, lock_resets, rising_floor, floor_elapsed,
+            floor_rng, paused, paused_time, line_clear_delay, delay, clearing_field,
+            are, buffered_inputs, visibility, lock_times, big, mirrored, score,
+            last_kick, events_enabled, dealt_pos, undo_limit, undo_stack, redo_stack,
+            hold_rule, level_table, initial_actions, spawn_rule, ghost_mode, cascade,
+            finesse, piece_inputs, finesse_faults, stats, history_limit, history,
+            can_hold, end_reason, removed_lines
+        )
+    };
+    (@build $game:ident, $take:tt, { $($field:ident: $value:expr),* }; $($same:ident),*) => {
+        Game {
+            $($field: $value,)*
+            $($same: rebuild_game!(@take $take, $game.$same),)*
+        }
+    };
+    (@take clone, $value:expr) => {
+        $value.clone()
+    };
+    (@take move, $value:expr) => {
+        $value
+    };
+}
+
+impl<S: PieceSelector> Game<S> {
     /// Create a new game.
     ///
-    /// `selector` is a function called when creating a new tetrimino.
-    pub fn new(
-        width: usize,
-        height: usize,
-        queue_size: usize,
-        mut selector: Box<dyn FnMut() -> BlockKind>,
+    /// `selector` is called when creating a new tetrimino. It can be a
+    /// function returning a `BlockKind` or one of the randomizers in
+    /// [`selectors`].
+    pub fn new(width: usize, height: usize, queue_size: usize, selector: S) -> Self {
+        Self::with_field(
+            Field::new(width, height, field::DEFAULT_HIDDEN_HEIGHT),
+            queue_size,
+            selector,
+        )
+    }
+
+    /// Create a game resuming from a position, such as a setup imported from
+    /// another tool.
+    ///
+    /// The current tetrimino of the kind `current` appears as usual, followed
+    /// by the ones in `queue`, and `held` is in the hold. The queue keeps the
+    /// size of `queue`, and `selector` is used for the tetriminos after it.
+    ///
+    /// Panics if the field is too small, like `Game::new`.
+    ///
+    /// ```
+    /// use tetrice::{BlockKind, Field, Game};
+    /// use tetrice::selectors::Bag;
+    ///
+    /// let field: Field = "
+    ///     |          |
+    ///     |ZZ    IIII|
+    /// "
+    /// .parse()
+    /// .unwrap();
+    /// let queue = vec![BlockKind::L, BlockKind::J];
+    /// let game = Game::from_parts(field, BlockKind::T, queue, Some(BlockKind::O), Bag::seven(0));
+    /// assert_eq!(game.tetrimino().kind(), BlockKind::T);
+    /// ```
+    pub fn from_parts(
+        field: Field,
+        current: BlockKind,
+        queue: Vec<BlockKind>,
+        held: Option<BlockKind>,
+        selector: S,
     ) -> Self {
-        if width < 4 {
+        let mut game = Self::unstarted(field, selector);
+        game.tetrimino = Tetrimino::new(current);
+        game.queue = queue.into_iter().map(Tetrimino::new).collect();
+        game.held = held.into_iter().map(Tetrimino::new).collect();
+        if !game.init_pos() {
+            game.end(EndReason::BlockOut);
+        }
+        game
+    }
+
+    pub(crate) fn with_field(field: Field, queue_size: usize, selector: S) -> Self {
+        let mut game = Self::unstarted(field, selector);
+        game.start(queue_size);
+        game
+    }
+
+    // Create a game without any tetriminos, which must be started by `start`
+    // after setting the rules.
+    pub(crate) fn unstarted(field: Field, selector: S) -> Self {
+        if field.width() < 4 {
             panic!("not enough width")
-        } else if height < 1 {
+        } else if field.height() < 1 {
             panic!("not enough height")
         }
 
-        let mut game = Game {
-            field: Field::new(width, height),
-            tetrimino: Tetrimino::new(selector()),
+        let state = GameState {
+            field,
+            tetrimino: Tetrimino::new(BlockKind::O),
             queue: VecDeque::new(),
-            held: None,
-            selector,
+            held: VecDeque::new(),
             can_hold: true,
-            is_end: false,
+            end_reason: None,
             removed_lines: 0,
         };
-        game.init_pos();
-        game.queue
-            .resize_with(queue_size, || Tetrimino::new((game.selector)()));
-        game
+        Game::from_valid_state(state, selector)
+    }
+
+    // Make the first tetrimino appear and fill the queue.
+    pub(crate) fn start(&mut self, queue_size: usize) {
+        self.tetrimino = Tetrimino::new(self.next_kind());
+        if !self.init_pos() {
+            self.end(EndReason::BlockOut);
+        }
+        for _ in 0..queue_size {
+            let t = Tetrimino::new(self.next_kind());
+            self.queue.push_back(t);
+        }
+    }
+
+    /// Restore a game from a snapshot made by `to_state`.
+    ///
+    /// `selector` is used for the tetriminos after the ones in the queue of
+    /// `state`. Returns an error if the tetriminos are misplaced (see
+    /// `GameState::validate`).
+    pub fn from_state(state: GameState, selector: S) -> Result<Self, StateError> {
+        state.validate()?;
+        Ok(Self::from_valid_state(state, selector))
+    }
+
+    // The only place giving every field its initial value. Games made from
+    // other games go through `rebuild_game` instead.
+    fn from_valid_state(state: GameState, selector: S) -> Self {
+        Game {
+            field: state.field,
+            tetrimino: state.tetrimino,
+            queue: state.queue,
+            held: state.held,
+            selector,
+            rotation_system: Arc::new(NearSearch),
+            gravity: Gravity::default(),
+            fall_progress: 0.0,
+            lock_delay: LockDelay::default(),
+            lock_elapsed: None,
+            lock_resets: 0,
+            rising_floor: None,
+            floor_elapsed: Duration::ZERO,
+            floor_rng: Rng::new(0),
+            paused: false,
+            paused_time: Duration::ZERO,
+            line_clear_delay: Duration::ZERO,
+            delay: None,
+            clearing_field: None,
+            are: Duration::ZERO,
+            buffered_inputs: Vec::new(),
+            visibility: Visibility::default(),
+            lock_times: LockTimes::default(),
+            big: false,
+            mirrored: false,
+            score: Score::default(),
+            last_kick: None,
+            events_enabled: false,
+            events: Vec::new(),
+            listener: ListenerSlot::default(),
+            dealt: Vec::new(),
+            dealt_start: 0,
+            dealt_pos: 0,
+            undo_limit: 0,
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            hold_rule: HoldRule::default(),
+            level_table: None,
+            initial_actions: InitialActions::default(),
+            spawn_rule: SpawnRule::default(),
+            ghost_mode: GhostMode::default(),
+            cascade: false,
+            finesse: None,
+            piece_inputs: Vec::new(),
+            finesse_faults: 0,
+            stats: Stats::default(),
+            history_limit: 0,
+            history: VecDeque::new(),
+            can_hold: state.can_hold,
+            end_reason: state.end_reason,
+            removed_lines: state.removed_lines,
+        }
+    }
+
+    /// Make a copy of this game for looking ahead, which doesn't affect this
+    /// game.
+    ///
+    /// The copy doesn't know the tetriminos after the queue, so they repeat
+    /// the current queue from its start. Use `fork_with` to give another
+    /// selector instead.
+    pub fn fork(&self) -> Game {
+        let mut known: Vec<_> = self.queue.iter().map(Tetrimino::kind).collect();
+        if known.is_empty() {
+            known.push(self.tetrimino.kind());
+        }
+        let mut index = 0;
+        self.fork_with(Box::new(move || {
+            index += 1;
+            known[(index - 1) % known.len()]
+        }))
+    }
+
+    /// Same as `fork` but uses `selector` for the tetriminos after the queue.
+    pub fn fork_with<T: PieceSelector>(&self, selector: T) -> Game<T> {
+        // The kinds before any snapshot to undo or redo are never dealt again.
+        let oldest = self
+            .undo_stack
+            .iter()
+            .chain(&self.redo_stack)
+            .map(|snapshot| snapshot.dealt_pos)
+            .fold(self.dealt_pos, usize::min);
+        rebuild_game!(self, clone, {
+            selector: selector,
+            events: Vec::new(),
+            listener: ListenerSlot::default(),
+            dealt: self.dealt[oldest - self.dealt_start..].to_vec(),
+            dealt_start: oldest,
+        })
+    }
+
+    /// Convert this game to the one with a boxed selector.
+    pub fn into_boxed(self) -> Game
+    where
+        S: 'static,
+    {
+        rebuild_game!(self, move, {
+            selector: Box::new(self.selector),
+            events: self.events,
+            listener: self.listener,
+            dealt: self.dealt,
+            dealt_start: self.dealt_start,
+        })
+    }
+
+    /// Make a snapshot of this game.
+    pub fn to_state(&self) -> GameState {
+        GameState {
+            field: self.field.clone(),
+            tetrimino: self.tetrimino.clone(),
+            queue: self.queue.clone(),
+            held: self.held.clone(),
+            can_hold: self.can_hold,
+            end_reason: self.end_reason,
+            removed_lines: self.removed_lines,
+        }
+    }
+
+    // Move the current tetrimino to where it appears. Returns false if it
+    // can't appear without overlapping, leaving it overlapping at the usual
+    // position.
+    fn init_pos(&mut self) -> bool {
+        let spawned = self.spawn_position(&self.tetrimino);
+        self.tetrimino = spawned
+            .clone()
+            .unwrap_or_else(|| self.spawn_rule.place(self.tetrimino.kind(), &self.field));
+        self.emit(GameEvent::PieceSpawned(self.tetrimino.kind()));
+        spawned.is_some()
+    }
+
+    // Returns the tetrimino coming out by holding, moved to where it appears.
+    pub(crate) fn spawn_position_after_hold(&self) -> Option<Tetrimino> {
+        if !self.can_hold() {
+            return None;
+        }
+        let next = if self.held.len() < self.hold_rule.slots {
+            self.queue.front()
+        } else {
+            self.held.front()
+        };
+        self.spawn_position(next?)
+    }
+
+    // Returns the tetrimino moved to where it appears, or `None` if it can't
+    // appear without overlapping.
+    fn spawn_position(&self, tetrimino: &Tetrimino) -> Option<Tetrimino> {
+        let t = self.spawn_rule.place(tetrimino.kind(), &self.field);
+        (0..=self.spawn_rule.max_lift)
+            .map(|dist_up| t.move_up(dist_up as isize))
+            .find(|s| !Checker(&self.field, s).overlap())
+    }
+
+    fn emit(&mut self, event: GameEvent) {
+        if let Some(listener) = &mut self.listener.0 {
+            listener.on_event(&event);
+        }
+        if self.events_enabled {
+            self.events.push(event);
+        }
+    }
+
+    /// Start or stop recording events. Events are not recorded by default.
+    ///
+    /// While recording, the events accumulate until `take_events` is called.
+    pub fn set_events_enabled(&mut self, enabled: bool) {
+        self.events_enabled = enabled;
+    }
+
+    /// Set the listener called for every event as soon as it happens, which
+    /// works whether events are recorded or not. Forks and clones of this
+    /// game don't have the listener.
+    pub fn set_listener(&mut self, listener: Box<dyn GameListener>) {
+        self.listener.0 = Some(listener);
+    }
+
+    /// Remove the listener and return it.
+    pub fn take_listener(&mut self) -> Option<Box<dyn GameListener>> {
+        self.listener.0.take()
+    }
+
+    /// Take the events recorded since the last call, in the order they
+    /// happened.
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        core::mem::take(&mut self.events)
+    }
+
+    /// Replace the rotation system, which decides how the tetrimino is kicked
+    /// when rotating. `NearSearch` is used by default.
+    pub fn set_rotation_system(&mut self, rotation_system: Box<dyn RotationSystem>) {
+        self.rotation_system = Arc::from(rotation_system);
+    }
+
+    /// Get the gravity used by `update`.
+    pub fn gravity(&self) -> Gravity {
+        self.gravity
+    }
+
+    /// Set the gravity used by `update`. The default is one cell per second.
+    pub fn set_gravity(&mut self, gravity: Gravity) {
+        self.gravity = gravity;
+    }
+
+    /// Get the lock delay used by `update`.
+    pub fn lock_delay(&self) -> LockDelay {
+        self.lock_delay
+    }
+
+    /// Set the lock delay used by `update`.
+    pub fn set_lock_delay(&mut self, lock_delay: LockDelay) {
+        self.lock_delay = lock_delay;
+    }
+
+    /// Get the rule of the rising floor, if any.
+    pub fn rising_floor(&self) -> Option<RisingFloor> {
+        self.rising_floor
+    }
+
+    /// Set the rule of the rising floor used by `update`, and restart its
+    /// timer. The floor doesn't rise by default.
+    pub fn set_rising_floor(&mut self, rule: Option<RisingFloor>) {
+        self.rising_floor = rule;
+        self.floor_elapsed = Duration::ZERO;
+        self.floor_rng = Rng::new(rule.map_or(0, |rule| rule.seed));
+    }
+
+    /// Get the level table, if any.
+    pub fn level_table(&self) -> Option<&LevelTable> {
+        self.level_table.as_ref()
+    }
+
+    /// Set the level table. While it is set, the level of the score, the
+    /// gravity and the lock delay follow it, and `set_gravity` and
+    /// `set_lock_delay` are overridden.
+    pub fn set_level_table(&mut self, table: Option<LevelTable>) {
+        self.level_table = table;
+        self.apply_level();
+    }
+
+    /// Get the current level, which is the level of the score.
+    pub fn level(&self) -> u64 {
+        self.score.level()
     }
 
-    fn init_pos(&mut self) {
-        let t = self.tetrimino.move_to((
-            (self.field.width() - self.tetrimino.width()) as isize / 2,
-            -(self.tetrimino.height() as isize),
-        ));
-        let lowest = (0..5)
-            .map(|dist_up| t.move_up(dist_up))
-            .find(|s| !Checker(&self.field, s).overlap());
-        if let Some(l) = lowest {
-            self.tetrimino = l;
+    fn apply_level(&mut self) {
+        if let Some(table) = &self.level_table {
+            let level = table.level(self.removed_lines);
+            self.gravity = table.gravity(level);
+            self.lock_delay = table.lock_delay(level);
+            self.score.set_level(level);
         }
     }
 
-    /// Get the field.
+    /// Get the remaining time until the current tetrimino is locked. Returns
+    /// `None` if the tetrimino doesn't touch down.
+    pub fn lock_timer(&self) -> Option<Duration> {
+        self.lock_elapsed
+            .map(|elapsed| self.lock_delay.delay.saturating_sub(elapsed))
+    }
+
+    /// Get the score.
+    pub fn score(&self) -> &Score {
+        &self.score
+    }
+
+    /// Get the score mutably, for example to change the level. The Guideline
+    /// scoring is used by default, and it can be replaced like this:
+    ///
+    /// ```
+    /// # use tetrice::{BlockKind, Game, Score};
+    /// use tetrice::scoring::Nes;
+    ///
+    /// # let mut game = Game::new(10, 20, 3, || BlockKind::T);
+    /// *game.score_mut() = Score::new(Box::new(Nes));
+    /// ```
+    pub fn score_mut(&mut self) -> &mut Score {
+        &mut self.score
+    }
+
+    /// Get the field. In big mode, this is the half-size field the rules
+    /// work on, where each cell is a 2x2 block on the screen (see `is_big`).
     pub fn field(&self) -> &Field {
-        &self.field
+        self.clearing_field.as_ref().unwrap_or(&self.field)
     }
 
     /// Get the current tetrimino.
@@ -115,15 +671,43 @@ impl Game {
         &self.queue
     }
 
+    /// Get the kinds of the next tetriminos, in the same order as `queue`.
+    pub fn queue_shapes(&self) -> impl Iterator<Item = BlockKind> + '_ {
+        self.queue.iter().map(Tetrimino::kind)
+    }
+
     /// Get the held tetrimino. If no tetrimino is held, returns `None`.
+    ///
+    /// If there are multiple slots, this is the one held first.
     pub fn held(&self) -> Option<Tetrimino> {
-        self.held.clone()
+        self.held.front().cloned()
+    }
+
+    /// Get all the held tetriminos, the one held first at the front.
+    pub fn held_all(&self) -> &VecDeque<Tetrimino> {
+        &self.held
+    }
+
+    /// Get the rule of holding.
+    pub fn hold_rule(&self) -> HoldRule {
+        self.hold_rule
+    }
+
+    /// Set the rule of holding. If there are more held tetriminos than the
+    /// new slots, the ones held first are discarded.
+    pub fn set_hold_rule(&mut self, rule: HoldRule) {
+        self.hold_rule = rule;
+        while self.held.len() > rule.slots {
+            self.held.pop_front();
+        }
     }
 
     /// Get the ghost, which shows a tetrimino after the current tetrimino is
     /// hard-dropped.
     ///
-    /// The ghost is located the deepest place the current tetrimino can reach
+    /// With `GhostMode::StraightDown` or `GhostMode::Disabled`, the ghost is
+    /// right below the tetrimino. With `GhostMode::Reachable`, which is the
+    /// default, the ghost is located the deepest place the current tetrimino can reach
     /// by moving left, right or down and rotating. For example, consider
     /// the field like this (`x` is the blocks in the field and `o` is the
     /// blocks of the tetrimino):
@@ -148,32 +732,237 @@ impl Game {
     /// |    o  x|
     /// |   ooo x|
     /// ```
+    ///
+    /// If there is no ghost, such as when garbage pushed the tetrimino into
+    /// blocks, this returns the current tetrimino as it is.
     pub fn ghost(&self) -> Tetrimino {
+        self.try_ghost().unwrap_or_else(|| self.tetrimino.clone())
+    }
+
+    /// Same as `ghost`, but returns `None` if there is no ghost because the
+    /// current tetrimino overlaps blocks.
+    pub fn try_ghost(&self) -> Option<Tetrimino> {
+        if Checker(&self.field, &self.tetrimino).overlap() {
+            return None;
+        }
+        if self.ghost_mode != GhostMode::Reachable {
+            let dist = (0..)
+                .take_while(|d| !Checker(&self.field, &self.tetrimino.move_down(*d)).touch_down())
+                .count();
+            return Some(self.tetrimino.move_down(dist as isize));
+        }
+
         let bottom = self.tetrimino.bottom();
         let dist_down = self.field.height() as isize - bottom;
+        // Searching the whole route is slow, so it's done only when the
+        // tetrimino can't go straight down, and only once.
+        let mut reachable = None;
         (0..dist_down)
             .rev()
-            .map(|dist_y| self.tetrimino.move_down(dist_y))
-            .find(|t| {
+            .map(|dist_y| (dist_y, self.tetrimino.move_down(dist_y)))
+            .find(|(dist_y, t)| {
                 let check = Checker(&self.field, t);
-                check.touch_down() && !check.overlap() && check.route_from(&self.tetrimino)
+                if !check.touch_down() || check.overlap() {
+                    return false;
+                }
+                let straight = (0..*dist_y)
+                    .all(|d| !Checker(&self.field, &self.tetrimino.move_down(d)).overlap());
+                straight
+                    || reachable
+                        .get_or_insert_with(|| {
+                            checker::reachable(&self.field, &self.tetrimino)
+                                .into_iter()
+                                .collect::<Set<_>>()
+                        })
+                        .contains(t)
+            })
+            .map(|(_, t)| t)
+    }
+
+    /// Returns true if the current tetrimino touches down, which is when the
+    /// lock delay runs.
+    pub fn is_grounded(&self) -> bool {
+        self.touches_down()
+    }
+
+    /// Get the number of rows between the current tetrimino and the ghost.
+    pub fn drop_distance(&self) -> usize {
+        (self.ghost().bottom() - self.tetrimino.bottom()) as usize
+    }
+
+    /// Get the y coordinate of the lowest block of the ghost, which is the
+    /// row the tetrimino lands on.
+    pub fn landing_row(&self) -> isize {
+        self.ghost().blocks().iter().map(|(_, y)| *y).max().unwrap()
+    }
+
+    /// Get every place where the current tetrimino can be locked, which is
+    /// reachable by moving left, right or down and rotating, and where it
+    /// touches down. Places occupying the same cells are listed once.
+    ///
+    /// If `include_hold` is true and holding is possible, the places of the
+    /// tetrimino coming out by holding are also listed.
+    pub fn legal_placements(&self, include_hold: bool) -> Vec<Tetrimino> {
+        if !self.has_tetrimino() {
+            return Vec::new();
+        }
+
+        let mut starts = vec![self.tetrimino.clone()];
+        if include_hold {
+            starts.extend(self.spawn_position_after_hold());
+        }
+
+        let mut seen = Set::new();
+        starts
+            .iter()
+            .flat_map(|start| checker::reachable(&self.field, start))
+            .filter(|t| Checker(&self.field, t).touch_down())
+            .filter(|t| {
+                let mut blocks = t.blocks();
+                blocks.sort();
+                seen.insert(blocks)
             })
-            .unwrap()
+            .collect()
+    }
+
+    /// Get a hash of the position, which consists of the field, the current
+    /// tetrimino, the first tetrimino in the queue, and the held tetrimino
+    /// with whether it can be held. This is useful for transposition tables
+    /// and detecting desyncs.
+    pub fn position_hash(&self) -> u64 {
+        // Each part has its own tag in the highest byte, which keeps its keys
+        // apart from the other parts and the field cells. The held
+        // tetriminos also have the index of the slot.
+        const CURRENT: u64 = 1 << 56;
+        const NEXT: u64 = 2 << 56;
+        const HELD: u64 = 3 << 56;
+        const CAN_HOLD: u64 = 4 << 56;
+        let key = |tag: u64, t: &Tetrimino| {
+            let (x, y) = t.position();
+            Rng::key(
+                tag ^ (t.kind() as u64) << 48
+                    ^ (t.rotation() as u64) << 40
+                    ^ (x as u16 as u64) << 16
+                    ^ y as u16 as u64,
+            )
+        };
+        let mut hash = self.field.zobrist_hash() ^ key(CURRENT, &self.tetrimino);
+        if let Some(next) = self.queue.front() {
+            hash ^= key(NEXT, &Tetrimino::new(next.kind()));
+        }
+        for (i, held) in self.held.iter().enumerate() {
+            let slot = (i as u64 & 0xff) << 32;
+            hash ^= key(HELD | slot, &Tetrimino::new(held.kind()));
+        }
+        if self.can_hold {
+            hash ^= Rng::key(CAN_HOLD);
+        }
+        hash
+    }
+
+    /// Get a digest of the whole state, which consists of everything in
+    /// `to_state` and the points. Games in sync have the same digest on any
+    /// platform, so peers can compare digests to detect desyncs, and then
+    /// find where they differ by `GameState::diff`.
+    ///
+    /// Panics if the state doesn't fit in the format of `GameState::encode`.
+    pub fn state_digest(&self) -> u64 {
+        // FNV-1a, which doesn't depend on the platform or the Rust version.
+        let mut bytes = self
+            .to_state()
+            .encode()
+            .expect("the state is too big to encode");
+        bytes.extend(self.score.points().to_le_bytes());
+        bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
     }
 
     /// Create a `Checker` from the field and the current tetrimino.
-    pub fn check(&self) -> Checker {
+    pub fn check(&self) -> Checker<'_> {
         Checker(&self.field, &self.tetrimino)
     }
 
+    /// Returns true if the current tetrimino touches the left border or
+    /// other blocks. Same as `Checker::touch_left`.
+    pub fn touches_left(&self) -> bool {
+        self.check().touch_left()
+    }
+
+    /// Same as `touches_left`, but checks the right side.
+    pub fn touches_right(&self) -> bool {
+        self.check().touch_right()
+    }
+
+    /// Same as `touches_left`, but checks the bottom.
+    pub fn touches_down(&self) -> bool {
+        self.check().touch_down()
+    }
+
+    /// Returns true if the current tetrimino overlaps other blocks or is
+    /// outside the field. Same as `Checker::overlap`.
+    pub fn overlaps(&self) -> bool {
+        self.check().overlap()
+    }
+
     /// Returns true if this game has ended.
     ///
     /// The game ends when a tetrimino is saved completely in the non-visible
     /// area. After the end, this game manager doesn't change the tetrimino
     /// and the field anymore. If even one of the four blocks is saved in the
-    /// visible area, the game continues.
+    /// visible area, the game continues. See `end_reason` for other ways the
+    /// game can end.
     pub fn is_end(&self) -> bool {
-        self.is_end
+        self.end_reason.is_some()
+    }
+
+    // Returns true if the current tetrimino has appeared, which is not after
+    // end or during a delay.
+    fn has_tetrimino(&self) -> bool {
+        !self.is_end() && self.delay.is_none()
+    }
+
+    // Returns true if the current tetrimino can be moved, which is also not
+    // while paused.
+    fn is_active(&self) -> bool {
+        self.has_tetrimino() && !self.paused
+    }
+
+    /// Get why this game has ended, or `None` if it hasn't ended.
+    pub fn end_reason(&self) -> Option<EndReason> {
+        self.end_reason
+    }
+
+    /// Get the number of consecutive clears before the last one, or `None` if
+    /// the last lock didn't remove any lines. Same as `Score::combo`.
+    pub fn combo(&self) -> Option<usize> {
+        self.score.combo()
+    }
+
+    /// Returns true if the last clear was a difficult one, so that the next
+    /// difficult clear continues the back-to-back chain.
+    pub fn back_to_back(&self) -> bool {
+        self.score.back_to_back()
+    }
+
+    /// Get the statistics of this game.
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// Get the saved tetriminos recorded so far, the oldest first. Nothing is
+    /// recorded unless `set_history_limit` is called.
+    pub fn history(&self) -> &VecDeque<HistoryEntry> {
+        &self.history
+    }
+
+    /// Set how many saved tetriminos are kept in the history, dropping the
+    /// oldest ones beyond it. The default is zero, which disables recording.
+    pub fn set_history_limit(&mut self, limit: usize) {
+        self.history_limit = limit;
+        while self.history.len() > limit {
+            self.history.pop_front();
+        }
     }
 
     /// Get the number of lines removed in this game.
@@ -181,17 +970,237 @@ impl Game {
         self.removed_lines
     }
 
+    /// Advance the time by `delta`, letting the tetrimino fall according to
+    /// the gravity and saving it when the lock delay expires. The next
+    /// tetrimino appears here when the line clear delay expires. Doesn't work
+    /// after end.
+    ///
+    /// Returns what happened during the time.
+    pub fn update(&mut self, delta: Duration) -> TickResult {
+        let mut result = TickResult::default();
+        if self.is_end() {
+            return result;
+        }
+        if self.paused {
+            self.paused_time += delta;
+            return result;
+        }
+
+        self.apply_level();
+        self.stats.elapsed += delta;
+        if let Some(rule) = self.rising_floor.filter(|rule| !rule.interval.is_zero()) {
+            self.floor_elapsed += delta;
+            while self.floor_elapsed >= rule.interval {
+                self.floor_elapsed -= rule.interval;
+                self.raise_floor();
+                result.raised += 1;
+                if self.is_end() {
+                    return result;
+                }
+            }
+        }
+        let Some(delta) = self.advance_delay(delta) else {
+            return result;
+        };
+        if self.is_end() {
+            return result;
+        }
+        let was_grounded = self.lock_elapsed.is_some();
+
+        self.fall_progress += delta.as_secs_f64() * self.gravity.as_cells_per_second();
+        while self.fall_progress >= 1.0 {
+            if self.drop_by(1) == 0 {
+                // The fall progress doesn't accumulate while touching down.
+                self.fall_progress = 0.0;
+                break;
+            }
+            self.fall_progress -= 1.0;
+            result.dropped += 1;
+        }
+
+        if self.check().touch_down() {
+            let elapsed = match self.lock_elapsed {
+                Some(elapsed) if was_grounded => elapsed + delta,
+                _ => Duration::ZERO,
+            };
+            if elapsed >= self.lock_delay.delay {
+                result.locked = Some(self.save());
+            } else {
+                self.lock_elapsed = Some(elapsed);
+            }
+        } else {
+            self.lock_elapsed = None;
+        }
+        result
+    }
+
+    /// Pause the game. While paused, `update` only counts the time, so the
+    /// gravity, the lock delay and the other timers stop. Inputs and the
+    /// methods moving, rotating, dropping, holding or saving the tetrimino
+    /// do nothing. Returns false if it was already paused.
+    pub fn pause(&mut self) -> bool {
+        !core::mem::replace(&mut self.paused, true)
+    }
+
+    /// Resume the game paused by `pause`. Returns false if it wasn't paused.
+    pub fn resume(&mut self) -> bool {
+        core::mem::replace(&mut self.paused, false)
+    }
+
+    /// Returns true if the game is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Get what the game is doing. Inputs given to `apply` move the
+    /// tetrimino in `Phase::Falling` and `Phase::LockDelay`, and are
+    /// buffered in `Phase::Clearing` and `Phase::Are`. Pausing doesn't
+    /// change the phase.
+    pub fn phase(&self) -> Phase {
+        match self.delay {
+            _ if self.is_end() => Phase::Over,
+            Some((Delay::Clearing, _)) => Phase::Clearing,
+            Some((Delay::Are, _)) => Phase::Are,
+            None if self.check().touch_down() => Phase::LockDelay,
+            None => Phase::Falling,
+        }
+    }
+
+    /// Get the total time passed in `update` while paused.
+    pub fn paused_duration(&self) -> Duration {
+        self.paused_time
+    }
+
+    // Restart the lock delay after the tetrimino is moved or rotated.
+    fn reset_lock_timer(&mut self) {
+        if self.lock_elapsed.is_some() && self.lock_resets < self.lock_delay.max_resets {
+            self.lock_elapsed = Some(Duration::ZERO);
+            self.lock_resets += 1;
+        }
+    }
+
+    fn clear_lock_timer(&mut self) {
+        self.lock_elapsed = None;
+        self.lock_resets = 0;
+    }
+
+    /// Apply an input from the player by calling the corresponding method.
+    ///
+    /// ```
+    /// use tetrice::{Game, Input};
+    ///
+    /// let mut game = Game::with_seed(10, 20, 3, 0);
+    /// assert!(game.apply(Input::Left).applied);
+    /// assert_eq!(game.apply(Input::HardDrop).dropped, 20);
+    /// assert!(game.apply(Input::Lock).locked.is_some());
+    /// ```
+    pub fn apply(&mut self, input: Input) -> ActionResult {
+        if self.paused || self.is_end() {
+            return ActionResult::default();
+        }
+        if self.delay.is_some() {
+            let buffered = !matches!(
+                input,
+                Input::SoftDrop | Input::HardDrop | Input::SonicDrop | Input::Lock
+            );
+            if buffered {
+                self.buffered_inputs.push(input);
+            }
+            return ActionResult {
+                buffered,
+                ..ActionResult::default()
+            };
+        }
+        let applied = |applied| ActionResult {
+            applied,
+            ..ActionResult::default()
+        };
+        let dropped = |dropped| ActionResult {
+            applied: dropped > 0,
+            dropped,
+            ..ActionResult::default()
+        };
+        let result = match input {
+            Input::Left => applied(self.move_left()),
+            Input::Right => applied(self.move_right()),
+            Input::LeftToWall => applied(self.move_left_to_wall() > 0),
+            Input::RightToWall => applied(self.move_right_to_wall() > 0),
+            Input::SoftDrop => dropped(self.soft_drop()),
+            Input::HardDrop => dropped(self.hard_drop()),
+            Input::SonicDrop => dropped(self.sonic_drop()),
+            Input::RotateCw => applied(self.rotate().rotated),
+            Input::RotateCcw => applied(self.rotate_ccw().rotated),
+            Input::Rotate180 => applied(self.rotate_180().rotated),
+            Input::Hold => return applied(self.hold()),
+            Input::Lock => {
+                if self.is_end() {
+                    return ActionResult::default();
+                }
+                let finesse_fault = self.finesse_fault();
+                if finesse_fault.is_some() {
+                    self.finesse_faults += 1;
+                }
+                return ActionResult {
+                    applied: true,
+                    locked: Some(self.save()),
+                    finesse_fault,
+                    ..ActionResult::default()
+                };
+            }
+        };
+        if self.finesse.is_some() {
+            self.piece_inputs.push((input, result.applied));
+        }
+        result
+    }
+
+    /// Get the table to check finesse, if it is tracked.
+    pub fn finesse_table(&self) -> Option<&FinesseTable> {
+        self.finesse.as_deref()
+    }
+
+    /// Start or stop tracking finesse with the table. While tracking, the
+    /// inputs given to `apply` are counted, and saving by `Input::Lock`
+    /// reports a fault if the tetrimino took more key presses than needed.
+    ///
+    /// Placements using soft drops are not checked.
+    pub fn set_finesse_table(&mut self, table: Option<FinesseTable>) {
+        self.finesse = table.map(Arc::new);
+        self.piece_inputs.clear();
+    }
+
+    /// Get the number of finesse faults since the start.
+    pub fn finesse_faults(&self) -> usize {
+        self.finesse_faults
+    }
+
+    fn finesse_fault(&self) -> Option<FinesseFault> {
+        let table = self.finesse.as_ref()?;
+        let soft_dropped = self.piece_inputs.iter().any(|(input, applied)| {
+            *applied && matches!(input, Input::SoftDrop | Input::SonicDrop)
+        });
+        if soft_dropped {
+            return None;
+        }
+        let optimal = table.optimal_presses_for(&self.tetrimino)?;
+        let presses = finesse::count_presses(&self.piece_inputs);
+        (presses > optimal).then_some(FinesseFault { optimal, presses })
+    }
+
     /// Move the current tetrimino to the left. However, when it touches the
     /// left border or other blocks, or after the game has end, do nothing.
     ///
     /// Returns true when actually moved the tetrimino.
     pub fn move_left(&mut self) -> bool {
-        if self.is_end {
+        if !self.is_active() {
             return false;
         }
 
         if !self.check().touch_left() {
             self.tetrimino = self.tetrimino.move_left(1);
+            self.last_kick = None;
+            self.emit(GameEvent::PieceMoved { dx: -1, dy: 0 });
+            self.reset_lock_timer();
             true
         } else {
             false
@@ -200,139 +1209,889 @@ impl Game {
 
     /// Same as `move_left`, but move the tetrimino to the right.
     pub fn move_right(&mut self) -> bool {
-        if self.is_end {
+        if !self.is_active() {
             return false;
         }
 
         if !self.check().touch_right() {
             self.tetrimino = self.tetrimino.move_right(1);
+            self.last_kick = None;
+            self.emit(GameEvent::PieceMoved { dx: 1, dy: 0 });
+            self.reset_lock_timer();
             true
         } else {
             false
         }
     }
 
+    /// Move the current tetrimino to the left until it touches the left
+    /// border or other blocks. Doesn't work after end.
+    ///
+    /// Returns the number of columns the tetrimino moved.
+    pub fn move_left_to_wall(&mut self) -> usize {
+        self.shift_to_wall(-1)
+    }
+
+    /// Same as `move_left_to_wall`, but move the tetrimino to the right.
+    pub fn move_right_to_wall(&mut self) -> usize {
+        self.shift_to_wall(1)
+    }
+
+    fn shift_to_wall(&mut self, direction: isize) -> usize {
+        if !self.is_active() {
+            return 0;
+        }
+
+        let touches = |t: &Tetrimino| {
+            let check = Checker(&self.field, t);
+            if direction < 0 {
+                check.touch_left()
+            } else {
+                check.touch_right()
+            }
+        };
+        let dist = (0..)
+            .take_while(|d| !touches(&self.tetrimino.move_right(d * direction)))
+            .count();
+        if dist > 0 {
+            let dx = dist as isize * direction;
+            self.tetrimino = self.tetrimino.move_right(dx);
+            self.last_kick = None;
+            self.emit(GameEvent::PieceMoved { dx, dy: 0 });
+            self.reset_lock_timer();
+        }
+        dist
+    }
+
     /// Same as `move_left`, but move down the tetrimino.
-    pub fn soft_drop(&mut self) -> bool {
-        if self.is_end {
-            return false;
+    ///
+    /// Returns the number of rows the tetrimino moved, which is 0 or 1.
+    pub fn soft_drop(&mut self) -> usize {
+        self.soft_drop_by(1)
+    }
+
+    /// Move down the tetrimino by up to `rows` rows, stopping when it touches
+    /// down. Doesn't work after end.
+    ///
+    /// Returns the number of rows the tetrimino moved, which are scored as a
+    /// soft drop.
+    pub fn soft_drop_by(&mut self, rows: usize) -> usize {
+        let dist = self.drop_by(rows);
+        if dist > 0 {
+            self.score.soft_drop(dist);
         }
+        dist
+    }
 
-        if !self.check().touch_down() {
-            self.tetrimino = self.tetrimino.move_down(1);
-            true
-        } else {
-            false
+    // Move down the tetrimino by up to `rows` rows without scoring.
+    fn drop_by(&mut self, rows: usize) -> usize {
+        if !self.is_active() {
+            return 0;
         }
+
+        let dist = (0..rows)
+            .take_while(|d| {
+                !Checker(&self.field, &self.tetrimino.move_down(*d as isize)).touch_down()
+            })
+            .count();
+        if dist > 0 {
+            self.tetrimino = self.tetrimino.move_down(dist as isize);
+            self.last_kick = None;
+            self.emit(GameEvent::PieceMoved {
+                dx: 0,
+                dy: dist as isize,
+            });
+        }
+        dist
     }
 
     /// Rotate the tetrimino clockwise, and move it to where it doesn't
-    /// overlap. However do nothing when such a place doesn't exist nearby or
-    /// after the game has end.
+    /// overlap according to the rotation system. However do nothing when such
+    /// a place doesn't exist or after the game has end.
     ///
-    /// Returns true if actually rotated the tetrimino.
-    pub fn rotate(&mut self) -> bool {
-        if self.is_end {
-            return false;
+    /// Returns whether the tetrimino actually rotated and how it was kicked.
+    pub fn rotate(&mut self) -> RotationResult {
+        self.rotate_to(self.tetrimino.rotate(1))
+    }
+
+    /// Same as `rotate`, but rotate the tetrimino counter-clockwise.
+    pub fn rotate_ccw(&mut self) -> RotationResult {
+        self.rotate_to(self.tetrimino.rotate_ccw(1))
+    }
+
+    /// Same as `rotate`, but rotate the tetrimino by 180 degrees.
+    pub fn rotate_180(&mut self) -> RotationResult {
+        self.rotate_to(self.tetrimino.rotate(2))
+    }
+
+    fn rotate_to(&mut self, new_tetrimino: Tetrimino) -> RotationResult {
+        if !self.is_active() {
+            return RotationResult::default();
         }
 
-        let new_tetrimino = self.tetrimino.rotate(1);
-        let result = near_points()
-            .iter()
-            .map(|p| new_tetrimino.move_right(p.0).move_down(p.1))
-            .find(|t| !Checker(&self.field, t).overlap());
-        if let Some(t) = result {
+        let result = self
+            .rotation_system
+            .kicks(
+                self.tetrimino.kind(),
+                self.tetrimino.rotation(),
+                new_tetrimino.rotation(),
+            )
+            .into_iter()
+            .map(|p| (p, new_tetrimino.move_right(p.0).move_down(p.1)))
+            .find(|(_, t)| !Checker(&self.field, t).overlap());
+        if let Some((kick, t)) = result {
             self.tetrimino = t;
-            true
+            self.last_kick = Some(kick);
+            self.emit(GameEvent::PieceRotated { kick });
+            self.reset_lock_timer();
+            RotationResult {
+                rotated: true,
+                kick: Some(kick).filter(|k| *k != (0, 0)),
+                spin: scoring::detect_t_spin(&self.field, &self.tetrimino, kick),
+            }
         } else {
-            false
+            RotationResult::default()
         }
     }
 
     /// Drop the tetrimino to the position of the ghost. Doesn't work after end.
-    pub fn hard_drop(&mut self) {
-        if self.is_end {
-            return;
+    ///
+    /// Returns the number of rows the tetrimino dropped.
+    pub fn hard_drop(&mut self) -> usize {
+        if !self.is_active() {
+            return 0;
+        }
+
+        let Some(ghost) = self.try_ghost() else {
+            return 0;
+        };
+        if ghost == self.tetrimino {
+            return 0;
         }
+        let dist = (ghost.bottom() - self.tetrimino.bottom()) as usize;
+        self.score.hard_drop(dist);
+        self.tetrimino = ghost;
+        self.last_kick = None;
+        self.emit(GameEvent::HardDropped { distance: dist });
+        dist
+    }
+
+    /// Move the tetrimino straight down until it touches down, without saving
+    /// it and without scoring. Unlike `hard_drop`, it doesn't follow the
+    /// ghost into places reachable only by moving sideways. Doesn't work after
+    /// end.
+    ///
+    /// Returns the number of rows the tetrimino dropped.
+    pub fn sonic_drop(&mut self) -> usize {
+        self.drop_by(usize::MAX)
+    }
 
-        self.tetrimino = self.ghost();
+    /// Drop the tetrimino to the position of the ghost and save it, which is
+    /// the same as calling `hard_drop` and then `save`.
+    pub fn hard_drop_and_lock(&mut self) -> LockResult {
+        self.hard_drop();
+        self.save()
+    }
+
+    // Get the next kind from the selector. After restoring a snapshot, the
+    // kinds selected after it are dealt again.
+    fn next_kind(&mut self) -> BlockKind {
+        if self.dealt_pos == self.dealt_start + self.dealt.len() {
+            let view = GameView {
+                field: &self.field,
+                queue: &self.queue,
+                held: self.held.front(),
+            };
+            let kind = self.selector.select_with_view(&view);
+            self.dealt
+                .push(if self.mirrored { kind.mirrored() } else { kind });
+        }
+        self.dealt_pos += 1;
+        self.dealt[self.dealt_pos - self.dealt_start - 1]
     }
 
     fn shift_queue(&mut self) -> Tetrimino {
-        self.queue.push_back(Tetrimino::new((self.selector)()));
+        let next = Tetrimino::new(self.next_kind());
+        self.queue.push_back(next);
         self.queue.pop_front().unwrap()
     }
 
+    /// Put a tetrimino of the kind at the front of the queue, so that it comes
+    /// next. The last one in the queue is pushed out and comes after the
+    /// queue, before the selector is called again.
+    pub fn push_front_queue(&mut self, kind: BlockKind) {
+        self.queue.push_front(Tetrimino::new(kind));
+        if let Some(last) = self.queue.pop_back() {
+            self.dealt
+                .insert(self.dealt_pos - self.dealt_start, last.kind());
+        }
+        self.redo_stack.clear();
+    }
+
+    /// Replace the next tetriminos with the kinds, in order. The size of the
+    /// queue doesn't change, so the kinds which don't fit come after the
+    /// queue. After all of them, the selector is used again.
+    pub fn set_queue(&mut self, kinds: Vec<BlockKind>) {
+        let size = self.queue.len();
+        self.queue.clear();
+        let pos = self.dealt_pos - self.dealt_start;
+        self.dealt.splice(pos..pos, kinds);
+        for _ in 0..size {
+            let t = Tetrimino::new(self.next_kind());
+            self.queue.push_back(t);
+        }
+        self.redo_stack.clear();
+    }
+
+    /// Make a snapshot of this game, including the score and the position of
+    /// the selector.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            state: self.to_state(),
+            score: self.score.counters(),
+            dealt_pos: self.dealt_pos,
+        }
+    }
+
+    /// Restore a snapshot made by `snapshot` of this game.
+    ///
+    /// The tetriminos selected after the snapshot come again in the same
+    /// order, so the game can be replayed deterministically.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        let state = snapshot.state.clone();
+        self.field = state.field;
+        self.tetrimino = state.tetrimino;
+        self.queue = state.queue;
+        self.held = state.held;
+        self.can_hold = state.can_hold;
+        self.end_reason = state.end_reason;
+        self.removed_lines = state.removed_lines;
+        self.score.set_counters(snapshot.score.clone());
+        // A fork doesn't know the kinds before its undo history.
+        self.dealt_pos = snapshot.dealt_pos.max(self.dealt_start);
+        self.fall_progress = 0.0;
+        self.clear_lock_timer();
+        self.last_kick = None;
+        self.delay = None;
+        self.clearing_field = None;
+        self.buffered_inputs.clear();
+        self.lock_times.reset(&self.field);
+    }
+
+    /// Restart the game with an empty field, keeping the selector and the
+    /// rules. The score, the statistics, the history and the held tetriminos
+    /// are cleared, and the queue is filled again by the selector, so
+    /// randomizers go on from where they were.
+    pub fn reset(&mut self) {
+        let queue_size = self.queue.len();
+        self.field.clear();
+        self.queue.clear();
+        self.held.clear();
+        self.can_hold = true;
+        self.end_reason = None;
+        self.removed_lines = 0;
+        self.score.reset();
+        self.dealt.clear();
+        self.dealt_start = 0;
+        self.dealt_pos = 0;
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.fall_progress = 0.0;
+        self.clear_lock_timer();
+        self.last_kick = None;
+        self.set_rising_floor(self.rising_floor);
+        self.piece_inputs.clear();
+        self.finesse_faults = 0;
+        self.stats = Stats::default();
+        self.paused = false;
+        self.paused_time = Duration::ZERO;
+        self.delay = None;
+        self.clearing_field = None;
+        self.buffered_inputs.clear();
+        self.lock_times.reset(&self.field);
+        self.history.clear();
+        self.events.clear();
+        self.apply_level();
+        self.start(queue_size);
+    }
+
+    /// Same as `reset`, but uses `selector` from now on, for example to
+    /// restart with another seed.
+    pub fn reset_with(&mut self, selector: S) {
+        self.selector = selector;
+        self.reset();
+    }
+
+    /// Make a snapshot for rollback, which is faster than `snapshot` and
+    /// also remembers the timers. Use `save_snapshot_into` to reuse the
+    /// memory of an old snapshot.
+    pub fn save_snapshot(&self) -> RollbackSnapshot {
+        let mut snapshot = RollbackSnapshot {
+            cells: Vec::new(),
+            masks: Vec::new(),
+            hidden_height: 0,
+            tetrimino: self.tetrimino.clone(),
+            queue: VecDeque::new(),
+            held: VecDeque::new(),
+            can_hold: false,
+            end_reason: None,
+            removed_lines: 0,
+            score: self.score.counters(),
+            dealt_pos: 0,
+            fall_progress: 0.0,
+            lock_elapsed: None,
+            lock_resets: 0,
+            last_kick: None,
+            floor_elapsed: Duration::ZERO,
+            floor_rng: self.floor_rng.clone(),
+            piece_inputs: Vec::new(),
+            finesse_faults: 0,
+            stats: Stats::default(),
+            delay: None,
+            clearing_field: None,
+            buffered_inputs: Vec::new(),
+            lock_times: LockTimes::default(),
+        };
+        self.save_snapshot_into(&mut snapshot);
+        snapshot
+    }
+
+    /// Same as `save_snapshot`, but overwrite the snapshot, reusing its
+    /// memory. This doesn't allocate once the snapshot is big enough.
+    pub fn save_snapshot_into(&self, snapshot: &mut RollbackSnapshot) {
+        self.field
+            .copy_into(&mut snapshot.cells, &mut snapshot.masks);
+        snapshot.hidden_height = self.field.hidden_height();
+        snapshot.tetrimino.clone_from(&self.tetrimino);
+        snapshot.queue.clone_from(&self.queue);
+        snapshot.held.clone_from(&self.held);
+        snapshot.can_hold = self.can_hold;
+        snapshot.end_reason = self.end_reason;
+        snapshot.removed_lines = self.removed_lines;
+        snapshot.score = self.score.counters();
+        snapshot.dealt_pos = self.dealt_pos;
+        snapshot.fall_progress = self.fall_progress;
+        snapshot.lock_elapsed = self.lock_elapsed;
+        snapshot.lock_resets = self.lock_resets;
+        snapshot.last_kick = self.last_kick;
+        snapshot.floor_elapsed = self.floor_elapsed;
+        snapshot.floor_rng.clone_from(&self.floor_rng);
+        snapshot.piece_inputs.clone_from(&self.piece_inputs);
+        snapshot.finesse_faults = self.finesse_faults;
+        snapshot.stats.clone_from(&self.stats);
+        snapshot.delay = self.delay;
+        snapshot.clearing_field.clone_from(&self.clearing_field);
+        snapshot.buffered_inputs.clone_from(&self.buffered_inputs);
+        snapshot.lock_times.clone_from(&self.lock_times);
+    }
+
+    /// Restore a snapshot made by `save_snapshot` of this game. Only the rows
+    /// of the field which differ are copied, and the memory of this game is
+    /// reused.
+    pub fn restore_snapshot(&mut self, snapshot: &RollbackSnapshot) {
+        self.field
+            .copy_from(&snapshot.cells, &snapshot.masks, snapshot.hidden_height);
+        self.tetrimino.clone_from(&snapshot.tetrimino);
+        self.queue.clone_from(&snapshot.queue);
+        self.held.clone_from(&snapshot.held);
+        self.can_hold = snapshot.can_hold;
+        self.end_reason = snapshot.end_reason;
+        self.removed_lines = snapshot.removed_lines;
+        self.score.set_counters(snapshot.score.clone());
+        self.dealt_pos = snapshot.dealt_pos.max(self.dealt_start);
+        self.fall_progress = snapshot.fall_progress;
+        self.lock_elapsed = snapshot.lock_elapsed;
+        self.lock_resets = snapshot.lock_resets;
+        self.last_kick = snapshot.last_kick;
+        self.floor_elapsed = snapshot.floor_elapsed;
+        self.floor_rng.clone_from(&snapshot.floor_rng);
+        self.piece_inputs.clone_from(&snapshot.piece_inputs);
+        self.finesse_faults = snapshot.finesse_faults;
+        self.stats.clone_from(&snapshot.stats);
+        self.delay = snapshot.delay;
+        self.clearing_field.clone_from(&snapshot.clearing_field);
+        self.buffered_inputs.clone_from(&snapshot.buffered_inputs);
+        self.lock_times.clone_from(&snapshot.lock_times);
+        self.apply_level();
+    }
+
+    /// Set how many locks can be undone by `undo_last_lock`. The default is
+    /// zero, which disables undoing.
+    pub fn set_undo_limit(&mut self, limit: usize) {
+        self.undo_limit = limit;
+        while self.undo_stack.len() > limit {
+            self.undo_stack.pop_front();
+        }
+    }
+
+    /// Restore the game to just before the last save. Returns false if there
+    /// is nothing to undo.
+    pub fn undo_last_lock(&mut self) -> bool {
+        if let Some(snapshot) = self.undo_stack.pop_back() {
+            self.redo_stack.push(self.snapshot());
+            self.restore(&snapshot);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Restore the game undone by `undo_last_lock`. Returns false if there is
+    /// nothing to redo. Saving a tetrimino clears the redo history.
+    pub fn redo_lock(&mut self) -> bool {
+        if let Some(snapshot) = self.redo_stack.pop() {
+            self.undo_stack.push_back(self.snapshot());
+            self.restore(&snapshot);
+            true
+        } else {
+            false
+        }
+    }
+
     /// Save the current tetrimino to the field and remove the filled lines.
-    /// Returns the number of removed lines.
+    /// Returns what happened, including the removed lines.
     ///
-    /// Doesn't work after end.
-    pub fn save(&mut self) -> usize {
-        if self.is_end {
-            return 0;
+    /// If the next tetrimino can't appear without overlapping the blocks, the
+    /// game ends by `EndReason::BlockOut`.
+    ///
+    /// Doesn't work after end, and returns an empty result.
+    pub fn save(&mut self) -> LockResult {
+        if !self.is_active() {
+            return LockResult::default();
         }
 
+        if self.undo_limit > 0 {
+            if self.undo_stack.len() == self.undo_limit {
+                self.undo_stack.pop_front();
+            }
+            self.undo_stack.push_back(self.snapshot());
+            self.redo_stack.clear();
+        }
+
+        let placed = self.tetrimino.clone();
+        let spin = self
+            .last_kick
+            .and_then(|kick| scoring::detect_t_spin(&self.field, &self.tetrimino, kick));
         for pos in self.tetrimino.blocks() {
             self.field.set(pos, self.tetrimino.kind());
         }
-        if self.tetrimino.bottom() < 0 {
-            self.is_end = true;
+        self.lock_times
+            .record(&self.field, self.tetrimino.blocks(), self.stats.elapsed);
+        let topped_out = self.tetrimino.bottom() < 0;
+        let before_clear = (!self.line_clear_delay.is_zero()).then(|| self.field.clone());
+        let cleared_rows = self.field.remove_filled_lines();
+        self.lock_times.remove_rows(&cleared_rows);
+        let mut chain_rows = Vec::new();
+        if self.cascade && !cleared_rows.is_empty() {
+            loop {
+                let moves = self.field.drop_floating_groups();
+                if moves.is_empty() {
+                    break;
+                }
+                self.lock_times.move_groups(&moves);
+                let rows = self.field.remove_filled_lines();
+                if rows.is_empty() {
+                    break;
+                }
+                self.lock_times.remove_rows(&rows);
+                chain_rows.push(rows);
+            }
         }
-        self.tetrimino = self.shift_queue();
-        self.init_pos();
         self.can_hold = true;
+        self.piece_inputs.clear();
+        self.clear_lock_timer();
+        self.last_kick = None;
+
+        let lines = cleared_rows.len();
+        let is_perfect_clear = self.field.is_empty();
+        self.removed_lines += lines + chain_rows.iter().map(Vec::len).sum::<usize>();
+        let chained = self.score.back_to_back();
+        self.score
+            .lock(lines, spin, is_perfect_clear && chain_rows.is_empty());
+        let back_to_back = lines > 0 && chained && self.score.back_to_back();
+        // Each chain counts as another clear, continuing the combo.
+        for (i, rows) in chain_rows.iter().enumerate() {
+            let last = i + 1 == chain_rows.len();
+            self.score.lock(rows.len(), None, is_perfect_clear && last);
+        }
+        self.apply_level();
+        let result = LockResult {
+            cleared_rows,
+            clear_kind: ClearKind::new(lines, spin),
+            is_perfect_clear,
+            topped_out,
+            spin,
+            combo: self.score.combo(),
+            back_to_back,
+            chain: chain_rows.len(),
+        };
+
+        self.stats.record(placed.kind(), &result);
+        if self.history_limit > 0 {
+            if self.history.len() == self.history_limit {
+                self.history.pop_front();
+            }
+            self.history.push_back(HistoryEntry {
+                tetrimino: placed,
+                result: result.clone(),
+                time: self.stats.elapsed,
+            });
+        }
+        self.emit(GameEvent::Locked(result.clone()));
+        if lines > 0 {
+            self.emit(GameEvent::LinesCleared {
+                rows: result.cleared_rows.clone(),
+            });
+        }
+        for rows in chain_rows {
+            self.emit(GameEvent::LinesCleared { rows });
+        }
+        if topped_out {
+            self.end(EndReason::LockOut);
+            return result;
+        }
 
-        let lines = self.field.remove_filled_lines();
-        self.removed_lines += lines;
-        lines
+        self.tetrimino = self.shift_queue();
+        // With the line clear delay or ARE, the next tetrimino appears after
+        // them.
+        if let Some(mut field) = before_clear.filter(|_| lines > 0) {
+            for y in &result.cleared_rows {
+                field.fill_row(*y, Cell::Clearing);
+            }
+            self.clearing_field = Some(field);
+            self.delay = Some((Delay::Clearing, Duration::ZERO));
+        } else if !self.are.is_zero() {
+            self.delay = Some((Delay::Are, Duration::ZERO));
+        } else if self.init_pos() {
+            self.apply_initial_actions();
+        } else {
+            self.end(EndReason::BlockOut);
+        }
+        result
     }
 
-    /// Hold the current tetrimino. Doesn't work just after another holding or
-    /// after the game has ended. Returns true when holding has been executed.
-    ///
-    /// Note: You can't hold tetriminos twice without saving.
-    pub fn hold(&mut self) {
-        if !self.can_hold || self.is_end {
+    // Make the current tetrimino appear after the delays, then apply the
+    // inputs buffered during them.
+    fn spawn(&mut self) {
+        let inputs = core::mem::take(&mut self.buffered_inputs);
+        if !self.init_pos() {
+            self.end(EndReason::BlockOut);
             return;
         }
+        self.apply_initial_actions();
+        for input in inputs {
+            self.apply(input);
+        }
+    }
+
+    // Advance the running delays by `delta`. Returns the time left after they
+    // end, or `None` if one is still running.
+    fn advance_delay(&mut self, mut delta: Duration) -> Option<Duration> {
+        if self.delay.is_none() {
+            return Some(delta);
+        }
+        while let Some((delay, elapsed)) = self.delay {
+            let elapsed = elapsed + delta;
+            let length = match delay {
+                Delay::Clearing => self.line_clear_delay,
+                Delay::Are => self.are,
+            };
+            if elapsed < length {
+                self.delay = Some((delay, elapsed));
+                return None;
+            }
+            delta = elapsed - length;
+            self.clearing_field = None;
+            // ARE follows the line clear delay.
+            self.delay = match delay {
+                Delay::Clearing if !self.are.is_zero() => Some((Delay::Are, Duration::ZERO)),
+                _ => None,
+            };
+        }
+        self.spawn();
+        Some(delta)
+    }
 
-        let new_held = Tetrimino::new(self.tetrimino.kind()).move_to((0, 0));
-        self.tetrimino = if let Some(current_held) = self.held.clone() {
-            current_held
-        } else {
-            self.shift_queue()
+    /// Get the time the cleared lines stay before they are removed. The
+    /// default is zero, which removes them at once.
+    pub fn line_clear_delay(&self) -> Duration {
+        self.line_clear_delay
+    }
+
+    /// Set the time the cleared lines stay before they are removed, for
+    /// animating them. During the delay, the lines are `Cell::Clearing` in
+    /// `field`, inputs are buffered like during ARE, and the next tetrimino
+    /// appears after it in `update`.
+    pub fn set_line_clear_delay(&mut self, delay: Duration) {
+        self.line_clear_delay = delay;
+    }
+
+    /// Returns true if the cleared lines are staying during the line clear
+    /// delay.
+    pub fn is_clearing(&self) -> bool {
+        matches!(self.delay, Some((Delay::Clearing, _)))
+    }
+
+    /// Get the entry delay (ARE), the time between saving a tetrimino and
+    /// the next one appearing. The default is zero.
+    pub fn are(&self) -> Duration {
+        self.are
+    }
+
+    /// Set the entry delay (ARE). It follows the line clear delay if lines
+    /// are cleared, and the next tetrimino appears after it in `update`.
+    ///
+    /// During the delays, moves, rotations and holding given to `apply` are
+    /// buffered and applied in order as soon as the next tetrimino appears.
+    /// Drops and locks are ignored, so that no tetrimino is dropped before
+    /// the player sees it.
+    pub fn set_are(&mut self, are: Duration) {
+        self.are = are;
+    }
+
+    /// Get the inputs buffered during the delays, in order.
+    pub fn buffered_inputs(&self) -> &[Input] {
+        &self.buffered_inputs
+    }
+
+    /// Get whether the saved blocks are shown.
+    pub fn visibility(&self) -> Visibility {
+        self.visibility
+    }
+
+    /// Set whether the saved blocks are shown, for invisible modes. This
+    /// doesn't change the field, only `is_visible`, `visibility_mask` and
+    /// `render`. The time since saving is counted by `update`.
+    pub fn set_visibility(&mut self, visibility: Visibility) {
+        self.visibility = visibility;
+    }
+
+    /// Returns false if the block of `field` at the position is hidden by
+    /// the visibility. Empty cells and the lines being cleared are visible.
+    pub fn is_visible(&self, (x, y): (isize, isize)) -> bool {
+        let Visibility::HideAfter(after) = self.visibility else {
+            return true;
         };
-        self.held = Some(new_held);
-        self.init_pos();
-        self.can_hold = false;
+        // The lines being cleared are already removed from the times.
+        let y = match &self.clearing_field {
+            Some(field) if field.get_cell((x, y)) == Cell::Clearing => return true,
+            Some(field) => {
+                y + (y + 1..field.height() as isize)
+                    .filter(|y| field.get_cell((0, *y)) == Cell::Clearing)
+                    .count() as isize
+            }
+            None => y,
+        };
+        self.lock_times
+            .get((x, y))
+            .is_none_or(|time| self.stats.elapsed.saturating_sub(time) < after)
+    }
+
+    /// Get whether each cell of the visible area is shown, by `is_visible`,
+    /// in the same layout as `Field::as_vec`.
+    pub fn visibility_mask(&self) -> Vec<Vec<bool>> {
+        let field = self.field();
+        (0..field.height() as isize)
+            .map(|y| {
+                (0..field.width() as isize)
+                    .map(|x| self.is_visible((x, y)))
+                    .collect()
+            })
+            .collect()
     }
-}
 
-const DISTANCE_NEAR: isize = 2;
+    /// Returns true if the game is in big mode, made by `GameBuilder::big`.
+    /// The field of the game is half the displayed size, and
+    /// `Field::scaled` and `Tetrimino::scaled_blocks` give what to display.
+    pub fn is_big(&self) -> bool {
+        self.big
+    }
 
-// Return points "near" the given vector, sorting them by pointIsPrior.
-fn near_points() -> Vec<(isize, isize)> {
-    let mut points: Vec<(isize, isize)> = (-DISTANCE_NEAR..=DISTANCE_NEAR)
-        .flat_map(|x| (-DISTANCE_NEAR..=DISTANCE_NEAR).map(move |y| (x, y)))
-        .collect();
-    points.sort_by(point_is_prior);
-    points
-}
+    /// Returns true if the game is in mirror mode.
+    pub fn is_mirrored(&self) -> bool {
+        self.mirrored
+    }
 
-fn point_is_prior(point: &(isize, isize), other: &(isize, isize)) -> Ordering {
-    let dist1 = point.0.pow(2) + point.1.pow(2);
-    let dist2 = other.0.pow(2) + other.1.pow(2);
-    if point == other {
-        Ordering::Equal
-    } else if dist1 == dist2 {
-        if (point.1 == other.1 && point.0 > 0) || (point.1 != other.1 && point.1 > other.1) {
-            Ordering::Greater
+    /// Enable or disable mirror mode, where the kinds from the selector
+    /// become `BlockKind::mirrored`. Changing it mirrors the field, the
+    /// current tetrimino, the queue and the held tetriminos with
+    /// `Field::mirror` and `Tetrimino::mirrored`, and the locks can't be
+    /// undone beyond it.
+    pub fn set_mirrored(&mut self, enabled: bool) {
+        if self.mirrored == enabled {
+            return;
+        }
+        self.mirrored = enabled;
+        let width = self.field.width();
+        self.field.mirror();
+        if let Some(field) = &mut self.clearing_field {
+            field.mirror();
+        }
+        self.lock_times.mirror();
+        self.tetrimino = self.tetrimino.mirrored(width);
+        for t in &mut self.queue {
+            *t = Tetrimino::new(t.kind().mirrored());
+        }
+        for t in &mut self.held {
+            *t = Tetrimino::new(t.kind().mirrored()).move_to((0, 0));
+        }
+        for kind in &mut self.dealt[self.dealt_pos - self.dealt_start..] {
+            *kind = kind.mirrored();
+        }
+        self.last_kick = self.last_kick.map(|(x, y)| (-x, y));
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+
+    /// Get the rule deciding where each tetrimino appears.
+    pub fn spawn_rule(&self) -> SpawnRule {
+        self.spawn_rule
+    }
+
+    /// Set the rule deciding where each tetrimino appears. It is used from the
+    /// next tetrimino.
+    pub fn set_spawn_rule(&mut self, rule: SpawnRule) {
+        self.spawn_rule = rule;
+    }
+
+    /// Get how the ghost is found.
+    pub fn ghost_mode(&self) -> GhostMode {
+        self.ghost_mode
+    }
+
+    /// Set how the ghost is found, which also changes where hard drops land.
+    pub fn set_ghost_mode(&mut self, mode: GhostMode) {
+        self.ghost_mode = mode;
+    }
+
+    /// Returns true if the cascade is enabled.
+    pub fn cascade(&self) -> bool {
+        self.cascade
+    }
+
+    /// Enable or disable the cascade. While it is enabled, the groups of
+    /// blocks left floating by a clear fall until they land, and the lines
+    /// they fill are removed as chains. It is disabled by default.
+    pub fn set_cascade(&mut self, enabled: bool) {
+        self.cascade = enabled;
+    }
+
+    /// Get the actions applied to the next tetrimino as soon as it appears.
+    pub fn initial_actions(&self) -> InitialActions {
+        self.initial_actions
+    }
+
+    /// Set the actions applied to the next tetrimino as soon as it appears
+    /// after saving, which is how the initial rotation and the initial hold
+    /// work.
+    pub fn set_initial_actions(&mut self, actions: InitialActions) {
+        self.initial_actions = actions;
+    }
+
+    fn apply_initial_actions(&mut self) {
+        let actions = core::mem::take(&mut self.initial_actions);
+        if actions.hold {
+            self.hold();
+        }
+        if !actions.rotation.is_multiple_of(4) {
+            self.rotate_to(self.tetrimino.rotate(actions.rotation));
+        }
+    }
+
+    fn end(&mut self, reason: EndReason) {
+        self.end_reason = Some(reason);
+        self.emit(GameEvent::GameOver { reason });
+    }
+
+    /// Push up the field and insert `lines` garbage lines at the bottom, each
+    /// of which has a hole at `hole_column`. Doesn't work after end.
+    ///
+    /// If the current tetrimino overlaps the pushed blocks, it is pushed up
+    /// as well. If any blocks are pushed out of the field, the game ends.
+    pub fn add_garbage(&mut self, lines: usize, hole_column: usize) {
+        let width = self.field.width();
+        if hole_column >= width {
+            panic!("hole column out of the field")
+        }
+
+        let row: Vec<_> = (0..width).map(|x| x != hole_column).collect();
+        self.add_garbage_rows(vec![row; lines]);
+    }
+
+    /// Raise the floor by a garbage row with a hole at a random column, which
+    /// is decided by the seed of the rising floor. Works like `add_garbage`,
+    /// so the game ends if any blocks are pushed out of the field.
+    pub fn raise_floor(&mut self) {
+        let hole_column = self.floor_rng.below(self.field.width());
+        self.add_garbage(1, hole_column);
+    }
+
+    /// Same as `add_garbage`, but insert the given rows, where `true` means a
+    /// garbage block. The last row comes to the bottom.
+    pub fn add_garbage_rows(&mut self, rows: Vec<Vec<bool>>) {
+        if self.is_end() {
+            return;
+        }
+
+        let width = self.field.width();
+        let lines = rows.len() as isize;
+        let rows = rows
+            .into_iter()
+            .map(|row| {
+                if row.len() != width {
+                    panic!("garbage row width doesn't match the field")
+                }
+                row.into_iter()
+                    .map(|b| if b { Cell::Garbage } else { Cell::Empty })
+                    .collect()
+            })
+            .collect();
+        let survived = self.field.push_up(rows);
+        self.lock_times.push_up(lines as usize);
+        let lifted = (0..=lines)
+            .map(|dist_up| self.tetrimino.move_up(dist_up))
+            .find(|t| !Checker(&self.field, t).overlap());
+        if let Some(t) = lifted {
+            self.tetrimino = t;
+        }
+
+        self.emit(GameEvent::GarbageReceived {
+            lines: lines as usize,
+        });
+        if !survived {
+            self.end(EndReason::GarbageOut);
+        }
+    }
+
+    /// Returns true if the current tetrimino can be held now.
+    pub fn can_hold(&self) -> bool {
+        self.hold_rule.slots > 0 && (self.can_hold || self.hold_rule.unlimited) && self.is_active()
+    }
+
+    /// Hold the current tetrimino. Doesn't work just after another holding
+    /// unless the rule allows it, after the game has ended, or if holding is
+    /// disabled. Returns true when holding has been executed.
+    pub fn hold(&mut self) -> bool {
+        if !self.can_hold() {
+            return false;
+        }
+
+        let new_held = Tetrimino::new(self.tetrimino.kind()).move_to((0, 0));
+        self.emit(GameEvent::Held(new_held.kind()));
+        self.held.push_back(new_held);
+        self.tetrimino = if self.held.len() > self.hold_rule.slots {
+            self.held.pop_front().unwrap()
         } else {
-            Ordering::Less
+            self.shift_queue()
+        };
+        let spawned = self.init_pos();
+        self.can_hold = false;
+        self.piece_inputs.clear();
+        self.clear_lock_timer();
+        self.last_kick = None;
+        if !spawned {
+            self.end(EndReason::BlockOut);
         }
-    } else if dist1 > dist2 {
-        Ordering::Greater
-    } else {
-        Ordering::Less
+        true
     }
 }
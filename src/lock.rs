@@ -0,0 +1,148 @@
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::{SpinKind, Tetrimino};
+
+/// The kind of a clear, decided by the number of removed lines and the
+/// spin, as the Guideline names them. Whether it is a perfect clear is in
+/// `LockResult::is_perfect_clear`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClearKind {
+    /// No lines are removed.
+    #[default]
+    None,
+    /// One line is removed.
+    Single,
+    /// Two lines are removed.
+    Double,
+    /// Three lines are removed.
+    Triple,
+    /// Four lines are removed.
+    Tetris,
+    /// A mini T-spin removing no lines.
+    TSpinMini,
+    /// A mini T-spin removing one line.
+    TSpinMiniSingle,
+    /// A mini T-spin removing two lines.
+    TSpinMiniDouble,
+    /// A T-spin removing no lines.
+    TSpin,
+    /// A T-spin removing one line.
+    TSpinSingle,
+    /// A T-spin removing two lines.
+    TSpinDouble,
+    /// A T-spin removing three lines.
+    TSpinTriple,
+}
+
+impl ClearKind {
+    /// Get the kind from the number of removed lines without spins.
+    pub fn from_lines(lines: usize) -> Self {
+        Self::new(lines, None)
+    }
+
+    /// Get the kind from the number of removed lines and the spin. More
+    /// lines than the kinds have are regarded as the largest one.
+    pub fn new(lines: usize, spin: Option<SpinKind>) -> Self {
+        match (spin, lines) {
+            (None, 0) => Self::None,
+            (None, 1) => Self::Single,
+            (None, 2) => Self::Double,
+            (None, 3) => Self::Triple,
+            (None, _) => Self::Tetris,
+            (Some(SpinKind::Mini), 0) => Self::TSpinMini,
+            (Some(SpinKind::Mini), 1) => Self::TSpinMiniSingle,
+            (Some(SpinKind::Mini), _) => Self::TSpinMiniDouble,
+            (Some(SpinKind::Full), 0) => Self::TSpin,
+            (Some(SpinKind::Full), 1) => Self::TSpinSingle,
+            (Some(SpinKind::Full), 2) => Self::TSpinDouble,
+            (Some(SpinKind::Full), _) => Self::TSpinTriple,
+        }
+    }
+
+    /// Get the number of removed lines.
+    pub fn lines(self) -> usize {
+        match self {
+            Self::None | Self::TSpinMini | Self::TSpin => 0,
+            Self::Single | Self::TSpinMiniSingle | Self::TSpinSingle => 1,
+            Self::Double | Self::TSpinMiniDouble | Self::TSpinDouble => 2,
+            Self::Triple | Self::TSpinTriple => 3,
+            Self::Tetris => 4,
+        }
+    }
+
+    /// Get the kind of the spin, if any.
+    pub fn spin(self) -> Option<SpinKind> {
+        match self {
+            Self::TSpinMini | Self::TSpinMiniSingle | Self::TSpinMiniDouble => Some(SpinKind::Mini),
+            Self::TSpin | Self::TSpinSingle | Self::TSpinDouble | Self::TSpinTriple => {
+                Some(SpinKind::Full)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns true if this is a difficult clear, which is a tetris or a
+    /// spin removing lines, and continues a back-to-back chain.
+    pub fn is_difficult(self) -> bool {
+        self == Self::Tetris || (self.lines() > 0 && self.spin().is_some())
+    }
+}
+
+/// What happened when a tetrimino was saved to the field.
+///
+/// The default value is the result of saving nothing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LockResult {
+    /// The y coordinates of the removed lines, from top to bottom, before the
+    /// field was compacted.
+    pub cleared_rows: Vec<isize>,
+    /// The kind of the clear.
+    pub clear_kind: ClearKind,
+    /// Whether the field has become empty.
+    pub is_perfect_clear: bool,
+    /// Whether the tetrimino was saved completely in the non-visible area,
+    /// which ends the game.
+    pub topped_out: bool,
+    /// The spin performed just before saving.
+    pub spin: Option<SpinKind>,
+    /// The number of consecutive clears before this one, or `None` if no
+    /// lines were removed.
+    pub combo: Option<usize>,
+    /// Whether this clear was a difficult one following another one.
+    pub back_to_back: bool,
+    /// The number of clears caused by the falling blocks after this one,
+    /// which is always 0 unless the cascade is enabled.
+    pub chain: usize,
+}
+
+impl LockResult {
+    /// Get the number of removed lines.
+    pub fn lines(&self) -> usize {
+        self.cleared_rows.len()
+    }
+
+    /// Get the indices of the removed lines in `Field::visible`, from top to
+    /// bottom, for flashing them before they vanish. The lines removed in
+    /// the non-visible area are not included.
+    pub fn cleared_visible_rows(&self) -> Vec<usize> {
+        self.cleared_rows
+            .iter()
+            .filter_map(|y| usize::try_from(*y).ok())
+            .collect()
+    }
+}
+
+/// A saved tetrimino recorded in `Game::history`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HistoryEntry {
+    /// The tetrimino at the position where it was saved.
+    pub tetrimino: Tetrimino,
+    /// What happened by saving it.
+    pub result: LockResult,
+    /// The time passed in `Game::update` until it was saved.
+    pub time: Duration,
+}
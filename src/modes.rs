@@ -0,0 +1,266 @@
+//! Standard game modes built on `Game`.
+//!
+//! Each mode wraps a game and tracks its goal. Operate the tetrimino through
+//! `game_mut`, but save it and advance the time through the mode, so that
+//! the mode can keep track of the progress.
+
+use alloc::boxed::Box;
+use core::time::Duration;
+
+use crate::{Game, LevelTable, LockResult, PieceSelector, TickResult};
+
+// Lines per minute over the time, or 0 if no time has passed.
+fn lines_per_minute(lines: usize, elapsed: Duration) -> f64 {
+    if elapsed.is_zero() {
+        0.0
+    } else {
+        lines as f64 * 60.0 / elapsed.as_secs_f64()
+    }
+}
+
+/// A mode to remove a number of lines as fast as possible, 40 lines by
+/// default.
+pub struct Sprint<S = Box<dyn PieceSelector>> {
+    game: Game<S>,
+    target_lines: usize,
+    elapsed: Duration,
+}
+
+impl<S: PieceSelector> Sprint<S> {
+    /// Start a 40-line sprint with the game.
+    pub fn new(game: Game<S>) -> Self {
+        Self::with_target(game, 40)
+    }
+
+    /// Start a sprint to remove `target_lines` lines.
+    pub fn with_target(game: Game<S>, target_lines: usize) -> Self {
+        Self {
+            game,
+            target_lines,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Get the game.
+    pub fn game(&self) -> &Game<S> {
+        &self.game
+    }
+
+    /// Get the game mutably.
+    pub fn game_mut(&mut self) -> &mut Game<S> {
+        &mut self.game
+    }
+
+    /// Get the number of lines to remove.
+    pub fn target_lines(&self) -> usize {
+        self.target_lines
+    }
+
+    /// Get the number of lines left to remove.
+    pub fn lines_left(&self) -> usize {
+        self.target_lines.saturating_sub(self.game.removed_lines())
+    }
+
+    /// Get the time passed since the start, which stops at the end.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Get the speed in lines per minute.
+    pub fn lines_per_minute(&self) -> f64 {
+        lines_per_minute(self.game.removed_lines(), self.elapsed)
+    }
+
+    /// Returns true if the target lines have been removed.
+    pub fn is_complete(&self) -> bool {
+        self.lines_left() == 0
+    }
+
+    /// Returns true if the sprint has been completed or the game has ended.
+    pub fn is_finished(&self) -> bool {
+        self.is_complete() || self.game.is_end()
+    }
+
+    /// Advance the time and call `Game::update`. Does nothing after finishing.
+    pub fn update(&mut self, delta: Duration) -> TickResult {
+        if self.is_finished() {
+            return TickResult::default();
+        }
+        self.elapsed += delta;
+        self.game.update(delta)
+    }
+
+    /// Call `Game::save`. Does nothing after finishing, and returns an empty
+    /// result.
+    pub fn save(&mut self) -> LockResult {
+        if self.is_finished() {
+            return LockResult::default();
+        }
+        self.game.save()
+    }
+}
+
+/// A mode where the level goes up every 10 lines and the tetrimino falls
+/// faster, until level 15 is cleared.
+pub struct Marathon<S = Box<dyn PieceSelector>> {
+    game: Game<S>,
+    lines_per_level: usize,
+    max_level: u64,
+    elapsed: Duration,
+}
+
+impl<S: PieceSelector> Marathon<S> {
+    /// Start a marathon of 15 levels with 10 lines per level.
+    pub fn new(game: Game<S>) -> Self {
+        Self::with_levels(game, 10, 15)
+    }
+
+    /// Start a marathon with the number of lines per level and the last
+    /// level. The level table of the game is replaced by the Guideline one
+    /// with the number of lines per level.
+    pub fn with_levels(game: Game<S>, lines_per_level: usize, max_level: u64) -> Self {
+        let mut game = game;
+        game.set_level_table(Some(LevelTable {
+            lines_per_level,
+            ..LevelTable::guideline()
+        }));
+        Self {
+            game,
+            lines_per_level,
+            max_level,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Get the game.
+    pub fn game(&self) -> &Game<S> {
+        &self.game
+    }
+
+    /// Get the game mutably.
+    pub fn game_mut(&mut self) -> &mut Game<S> {
+        &mut self.game
+    }
+
+    /// Get the current level, starting from 1.
+    pub fn level(&self) -> u64 {
+        self.game.level().min(self.max_level)
+    }
+
+    /// Get the time passed since the start, which stops at the end.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Get the speed in lines per minute.
+    pub fn lines_per_minute(&self) -> f64 {
+        lines_per_minute(self.game.removed_lines(), self.elapsed)
+    }
+
+    /// Returns true if the last level has been cleared.
+    pub fn is_complete(&self) -> bool {
+        self.game.removed_lines() >= self.lines_per_level * self.max_level as usize
+    }
+
+    /// Returns true if the marathon has been completed or the game has ended.
+    pub fn is_finished(&self) -> bool {
+        self.is_complete() || self.game.is_end()
+    }
+
+    /// Advance the time and call `Game::update`. Does nothing after finishing.
+    pub fn update(&mut self, delta: Duration) -> TickResult {
+        if self.is_finished() {
+            return TickResult::default();
+        }
+        self.elapsed += delta;
+        self.game.update(delta)
+    }
+
+    /// Call `Game::save`. Does nothing after finishing, and returns an empty
+    /// result.
+    pub fn save(&mut self) -> LockResult {
+        if self.is_finished() {
+            return LockResult::default();
+        }
+        self.game.save()
+    }
+}
+
+/// A mode to get as many points as possible in a time limit, 2 minutes by
+/// default.
+pub struct Ultra<S = Box<dyn PieceSelector>> {
+    game: Game<S>,
+    time_limit: Duration,
+    elapsed: Duration,
+}
+
+impl<S: PieceSelector> Ultra<S> {
+    /// Start a 2-minute ultra with the game.
+    pub fn new(game: Game<S>) -> Self {
+        Self::with_time_limit(game, Duration::from_secs(120))
+    }
+
+    /// Start an ultra with the time limit.
+    pub fn with_time_limit(game: Game<S>, time_limit: Duration) -> Self {
+        Self {
+            game,
+            time_limit,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Get the game.
+    pub fn game(&self) -> &Game<S> {
+        &self.game
+    }
+
+    /// Get the game mutably.
+    pub fn game_mut(&mut self) -> &mut Game<S> {
+        &mut self.game
+    }
+
+    /// Get the time passed since the start.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Get the time left.
+    pub fn time_left(&self) -> Duration {
+        self.time_limit.saturating_sub(self.elapsed)
+    }
+
+    /// Get the speed in lines per minute.
+    pub fn lines_per_minute(&self) -> f64 {
+        lines_per_minute(self.game.removed_lines(), self.elapsed)
+    }
+
+    /// Returns true if the time is up.
+    pub fn is_complete(&self) -> bool {
+        self.time_left().is_zero()
+    }
+
+    /// Returns true if the time is up or the game has ended.
+    pub fn is_finished(&self) -> bool {
+        self.is_complete() || self.game.is_end()
+    }
+
+    /// Advance the time and call `Game::update`, but not beyond the time
+    /// limit. Does nothing after finishing.
+    pub fn update(&mut self, delta: Duration) -> TickResult {
+        if self.is_finished() {
+            return TickResult::default();
+        }
+        let delta = delta.min(self.time_left());
+        self.elapsed += delta;
+        self.game.update(delta)
+    }
+
+    /// Call `Game::save`. Does nothing after finishing, and returns an empty
+    /// result.
+    pub fn save(&mut self) -> LockResult {
+        if self.is_finished() {
+            return LockResult::default();
+        }
+        self.game.save()
+    }
+}
@@ -0,0 +1,157 @@
+//! Puzzles with a starting field, a fixed sequence of tetriminos and a goal.
+
+use alloc::{boxed::Box, vec::Vec};
+use core::time::Duration;
+
+use crate::{BlockKind, ClearKind, Field, Game, LockResult, TickResult};
+
+/// The goal of a puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Goal {
+    /// Remove all the blocks except solid ones.
+    ClearAllBlocks,
+    /// Remove two lines by a full T-spin.
+    PerformTSpinDouble,
+    /// Save the number of tetriminos without ending the game.
+    SurviveNPieces(usize),
+    /// Get the number of points.
+    ReachScore(u64),
+}
+
+/// A puzzle, which is played by `PuzzleGame`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Puzzle {
+    /// The field at the start.
+    pub field: Field,
+    /// The tetriminos in the order they come, the current one first.
+    pub pieces: Vec<BlockKind>,
+    /// The goal.
+    pub goal: Goal,
+}
+
+/// The progress of a puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PuzzleStatus {
+    /// The goal hasn't been achieved yet.
+    Playing,
+    /// The goal has been achieved.
+    Solved,
+    /// The game has ended, or all the tetriminos have been saved without
+    /// achieving the goal.
+    Failed,
+}
+
+/// A game playing a puzzle, which reports whether the goal is achieved.
+///
+/// Operate the tetrimino through `game_mut`, but save it and advance the time
+/// through this, so that the progress can be checked.
+///
+/// Only as many tetriminos as the sequence of the puzzle can be saved. The
+/// tetriminos after the sequence, which can appear by holding, repeat the
+/// last one.
+pub struct PuzzleGame {
+    game: Game,
+    puzzle: Puzzle,
+    locks: usize,
+    status: PuzzleStatus,
+}
+
+impl PuzzleGame {
+    /// Start the puzzle with `queue_size` next tetriminos.
+    ///
+    /// Panics if the puzzle has no tetriminos, or the field is too small like
+    /// `Game::new`.
+    pub fn new(puzzle: Puzzle, queue_size: usize) -> Self {
+        let last = *puzzle.pieces.last().expect("a puzzle needs tetriminos");
+        let mut pieces = puzzle.pieces.clone().into_iter();
+        let selector = move || pieces.next().unwrap_or(last);
+        let game: Game = Game::with_field(puzzle.field.clone(), queue_size, Box::new(selector));
+        let mut puzzle_game = Self {
+            game,
+            puzzle,
+            locks: 0,
+            status: PuzzleStatus::Playing,
+        };
+        puzzle_game.check(None);
+        puzzle_game
+    }
+
+    /// Get the puzzle.
+    pub fn puzzle(&self) -> &Puzzle {
+        &self.puzzle
+    }
+
+    /// Get the game.
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Get the game mutably.
+    pub fn game_mut(&mut self) -> &mut Game {
+        &mut self.game
+    }
+
+    /// Get the progress.
+    pub fn status(&self) -> PuzzleStatus {
+        self.status
+    }
+
+    /// Get the number of tetriminos which can still be saved.
+    pub fn pieces_left(&self) -> usize {
+        self.puzzle.pieces.len() - self.locks
+    }
+
+    /// Returns true if the puzzle has been solved or failed.
+    pub fn is_finished(&self) -> bool {
+        self.status != PuzzleStatus::Playing
+    }
+
+    /// Advance the time and call `Game::update`. Does nothing after finishing.
+    pub fn update(&mut self, delta: Duration) -> TickResult {
+        if self.is_finished() {
+            return TickResult::default();
+        }
+        let result = self.game.update(delta);
+        if let Some(locked) = &result.locked {
+            self.locks += 1;
+            self.check(Some(locked));
+        } else {
+            self.check(None);
+        }
+        result
+    }
+
+    /// Call `Game::save`. Does nothing after finishing or while the game
+    /// can't save, such as during ARE, and returns an empty result.
+    pub fn save(&mut self) -> LockResult {
+        if self.is_finished() || !self.game.is_active() {
+            return LockResult::default();
+        }
+        let result = self.game.save();
+        self.locks += 1;
+        self.check(Some(&result));
+        result
+    }
+
+    // Update the status after a lock or a tick.
+    fn check(&mut self, locked: Option<&LockResult>) {
+        let solved = match self.puzzle.goal {
+            Goal::ClearAllBlocks => locked.is_some_and(|result| result.is_perfect_clear),
+            Goal::PerformTSpinDouble => {
+                locked.is_some_and(|result| result.clear_kind == ClearKind::TSpinDouble)
+            }
+            Goal::SurviveNPieces(pieces) => self.locks >= pieces && !self.game.is_end(),
+            Goal::ReachScore(points) => self.game.score().points() >= points,
+        };
+        self.status = if solved {
+            PuzzleStatus::Solved
+        } else if self.game.is_end() || self.pieces_left() == 0 {
+            PuzzleStatus::Failed
+        } else {
+            PuzzleStatus::Playing
+        };
+    }
+}
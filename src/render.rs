@@ -0,0 +1,139 @@
+use alloc::{string::String, vec::Vec};
+
+use crate::{BlockKind, Cell, Game, GhostMode, PieceSelector};
+
+/// Characters used by `Game::render`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glyphs {
+    /// The characters of blocks, in the order of `BlockKind::all_as_array`.
+    pub blocks: [char; 7],
+    /// The character of garbage blocks.
+    pub garbage: char,
+    /// The character of solid blocks.
+    pub solid: char,
+    /// The character of lines being cleared.
+    pub clearing: char,
+    /// The character of empty cells.
+    pub empty: char,
+    /// The character of the ghost.
+    pub ghost: char,
+    /// The character of the left and right walls.
+    pub wall: char,
+}
+
+impl Glyphs {
+    /// Get the character of the block kind.
+    pub fn block(&self, kind: BlockKind) -> char {
+        let index = BlockKind::all_as_array()
+            .iter()
+            .position(|k| *k == kind)
+            .unwrap();
+        self.blocks[index]
+    }
+
+    /// Get the character of the cell. `Cell::Outside` is rendered as a space.
+    pub fn cell(&self, cell: Cell) -> char {
+        match cell {
+            Cell::Block(kind) => self.block(kind),
+            Cell::Garbage => self.garbage,
+            Cell::Solid => self.solid,
+            Cell::Clearing => self.clearing,
+            Cell::Empty => self.empty,
+            Cell::Outside => ' ',
+        }
+    }
+}
+
+impl Default for Glyphs {
+    /// The same characters as the diagrams parsed by `Field::from_str`.
+    fn default() -> Self {
+        Self {
+            blocks: BlockKind::all_as_array().map(BlockKind::to_char),
+            garbage: '#',
+            solid: '=',
+            clearing: '-',
+            empty: '.',
+            ghost: '_',
+            wall: '|',
+        }
+    }
+}
+
+impl<S: PieceSelector> Game<S> {
+    /// Render the visible area of the field with the current tetrimino and
+    /// its ghost, one line per row. The blocks hidden by the visibility are
+    /// rendered as empty cells, and big mode is rendered enlarged.
+    pub fn render(&self, glyphs: &Glyphs) -> String {
+        let field = self.field();
+        let mask = self.visibility_mask();
+        let mut rows: Vec<Vec<char>> = field
+            .as_vec()
+            .iter()
+            .zip(mask)
+            .map(|(row, mask)| {
+                row.iter()
+                    .zip(mask)
+                    .map(|(cell, shown)| {
+                        if shown {
+                            glyphs.cell(*cell)
+                        } else {
+                            glyphs.empty
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut draw = |blocks: [(isize, isize); 4], c: char| {
+            for (x, y) in blocks {
+                if y >= 0 && (y as usize) < rows.len() {
+                    rows[y as usize][x as usize] = c;
+                }
+            }
+        };
+        if self.has_tetrimino() {
+            if let Some(ghost) = self
+                .try_ghost()
+                .filter(|_| self.ghost_mode() != GhostMode::Disabled)
+            {
+                draw(ghost.blocks(), glyphs.ghost);
+            }
+            draw(
+                self.tetrimino().blocks(),
+                glyphs.block(self.tetrimino().kind()),
+            );
+        }
+
+        if self.is_big() {
+            rows = rows
+                .into_iter()
+                .flat_map(|row| {
+                    let row: Vec<_> = row.into_iter().flat_map(|c| [c, c]).collect();
+                    [row.clone(), row]
+                })
+                .collect();
+        }
+
+        rows.iter()
+            .map(|row| {
+                let mut line = String::new();
+                line.push(glyphs.wall);
+                line.extend(row);
+                line.push(glyphs.wall);
+                line.push('\n');
+                line
+            })
+            .collect()
+    }
+
+    /// Render the game with the default glyphs, like `Game::render`.
+    pub fn render_to_string(&self) -> String {
+        self.render(&Glyphs::default())
+    }
+}
+
+impl<S: PieceSelector> core::fmt::Display for Game<S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.render_to_string())
+    }
+}
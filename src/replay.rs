@@ -0,0 +1,207 @@
+//! Recording games and playing them back.
+
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::input::Input;
+use crate::selectors::Bag;
+use crate::{ActionResult, BuildError, Game, GameBuilder, TickResult, MAX_WIDTH};
+
+/// A step of a replay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Step {
+    /// An input from the player.
+    Input(Input),
+    /// Time passed, which was given to `Game::update`.
+    Wait(Duration),
+}
+
+/// A record of a game, which is enough to reconstruct it.
+///
+/// The game uses the 7-bag randomizer with the seed, so the same tetriminos
+/// come in the same order when playing back.
+///
+/// The rules aren't recorded. `play_back` uses the default rules, like
+/// `Game::with_seed`. A replay recorded by `Recorder::with_builder` must be
+/// played back by `play_back_with` with the same options, or the game goes
+/// differently.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Replay {
+    /// The width of the field.
+    pub width: usize,
+    /// The height of the field.
+    pub height: usize,
+    /// The number of next tetriminos.
+    pub queue_size: usize,
+    /// The seed of the randomizer.
+    pub seed: u64,
+    /// The steps in the order they happened.
+    pub steps: Vec<Step>,
+}
+
+const WAIT_TAG: u8 = 0xff;
+// The width, the height, the queue size and the seed.
+const HEADER_LEN: usize = 14;
+
+impl Replay {
+    /// Create an empty replay.
+    pub fn new(width: usize, height: usize, queue_size: usize, seed: u64) -> Self {
+        Self {
+            width,
+            height,
+            queue_size,
+            seed,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Reconstruct the game with the default rules by applying all the
+    /// steps.
+    ///
+    /// Panics if the field is too small, like `Game::new`.
+    pub fn play_back(&self) -> Game<Bag> {
+        let game = Game::with_seed(self.width, self.height, self.queue_size, self.seed);
+        self.apply_steps(game)
+    }
+
+    /// Same as `play_back`, but build the game with the rules of `builder`,
+    /// which must have the options the replay was recorded with. The size
+    /// and the queue size of the replay override the ones of `builder`.
+    pub fn play_back_with(&self, builder: GameBuilder) -> Result<Game<Bag>, BuildError> {
+        let game = builder
+            .size(self.width, self.height)
+            .queue_size(self.queue_size)
+            .build(Bag::seven(self.seed))?;
+        Ok(self.apply_steps(game))
+    }
+
+    fn apply_steps(&self, mut game: Game<Bag>) -> Game<Bag> {
+        for step in &self.steps {
+            match step {
+                Step::Input(input) => {
+                    game.apply(*input);
+                }
+                Step::Wait(delta) => {
+                    game.update(*delta);
+                }
+            }
+        }
+        game
+    }
+
+    /// Encode this replay into compact bytes, which keep every step exactly.
+    ///
+    /// Each input takes one byte, and each wait takes nine bytes. Returns
+    /// `None` if the replay doesn't fit in the format: the width, the height
+    /// and the queue size can be up to 65535, and each wait up to `u64::MAX`
+    /// nanoseconds, about 584 years.
+    pub fn encode(&self) -> Option<Vec<u8>> {
+        let mut bytes = Vec::new();
+        for size in [self.width, self.height, self.queue_size] {
+            bytes.extend(u16::try_from(size).ok()?.to_le_bytes());
+        }
+        bytes.extend(self.seed.to_le_bytes());
+        for step in &self.steps {
+            match step {
+                Step::Input(input) => {
+                    bytes.push(Input::ALL.iter().position(|i| i == input).unwrap() as u8)
+                }
+                Step::Wait(delta) => {
+                    bytes.push(WAIT_TAG);
+                    bytes.extend(u64::try_from(delta.as_nanos()).ok()?.to_le_bytes());
+                }
+            }
+        }
+        Some(bytes)
+    }
+
+    /// Decode bytes made by `encode`. Returns `None` if the bytes are broken,
+    /// or if the field is too small or too wide to play back: the width must
+    /// be from 4 to `MAX_WIDTH`, and the height at least 1.
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let header = bytes.get(..HEADER_LEN)?;
+        let width = u16::from_le_bytes(header[0..2].try_into().ok()?) as usize;
+        let height = u16::from_le_bytes(header[2..4].try_into().ok()?) as usize;
+        if !(4..=MAX_WIDTH).contains(&width) || height == 0 {
+            return None;
+        }
+        let mut replay = Self::new(
+            width,
+            height,
+            u16::from_le_bytes(header[4..6].try_into().ok()?) as usize,
+            u64::from_le_bytes(header[6..14].try_into().ok()?),
+        );
+
+        let mut rest = &bytes[HEADER_LEN..];
+        while let Some((tag, tail)) = rest.split_first() {
+            if *tag == WAIT_TAG {
+                let nanos = u64::from_le_bytes(tail.get(..8)?.try_into().ok()?);
+                replay.steps.push(Step::Wait(Duration::from_nanos(nanos)));
+                rest = &tail[8..];
+            } else {
+                replay
+                    .steps
+                    .push(Step::Input(*Input::ALL.get(*tag as usize)?));
+                rest = tail;
+            }
+        }
+        Some(replay)
+    }
+}
+
+/// A game recording its inputs into a replay.
+pub struct Recorder {
+    game: Game<Bag>,
+    replay: Replay,
+}
+
+impl Recorder {
+    /// Start a game with the default rules and the 7-bag randomizer using
+    /// the seed.
+    pub fn new(width: usize, height: usize, queue_size: usize, seed: u64) -> Self {
+        Self {
+            game: Game::with_seed(width, height, queue_size, seed),
+            replay: Replay::new(width, height, queue_size, seed),
+        }
+    }
+
+    /// Start a game built by `builder` with the 7-bag randomizer using the
+    /// seed. The replay doesn't record the rules, so play it back by
+    /// `Replay::play_back_with` with the same options.
+    pub fn with_builder(builder: GameBuilder, seed: u64) -> Result<Self, BuildError> {
+        let replay = Replay::new(builder.width, builder.height, builder.queue_size, seed);
+        Ok(Self {
+            game: builder.build(Bag::seven(seed))?,
+            replay,
+        })
+    }
+
+    /// Get the game.
+    pub fn game(&self) -> &Game<Bag> {
+        &self.game
+    }
+
+    /// Get the replay recorded so far.
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Finish recording and get the replay.
+    pub fn finish(self) -> Replay {
+        self.replay
+    }
+
+    /// Call `Game::apply` and record it.
+    pub fn apply(&mut self, input: Input) -> ActionResult {
+        self.replay.steps.push(Step::Input(input));
+        self.game.apply(input)
+    }
+
+    /// Call `Game::update` and record it.
+    pub fn update(&mut self, delta: Duration) -> TickResult {
+        self.replay.steps.push(Step::Wait(delta));
+        self.game.update(delta)
+    }
+}
@@ -0,0 +1,146 @@
+use alloc::{vec, vec::Vec};
+
+use crate::{BlockKind, SpinKind};
+
+/// A rule deciding where a tetrimino may go when it is rotated.
+///
+/// When rotating, the game tries the offsets returned by `kicks` in order and
+/// moves the rotated tetrimino by the first one at which it doesn't overlap.
+/// The offsets are `(x, y)` pairs where x grows rightward and y grows downward,
+/// the same as the positions in the field.
+///
+/// Rotation systems must be `Send` and `Sync`, as explained in
+/// [`SendGame`](crate::SendGame).
+pub trait RotationSystem: Send + Sync {
+    /// Returns the offsets to try when rotating a tetrimino of `kind` from the
+    /// rotation state `from` to `to`.
+    fn kicks(&self, kind: BlockKind, from: usize, to: usize) -> Vec<(isize, isize)>;
+}
+
+/// What happened when rotating the tetrimino, returned by `Game::rotate`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RotationResult {
+    /// Whether the tetrimino rotated.
+    pub rotated: bool,
+    /// The offset the tetrimino was moved by, or `None` if it rotated in
+    /// place or didn't rotate.
+    pub kick: Option<(isize, isize)>,
+    /// The spin the rotation makes if the tetrimino is saved without moving.
+    pub spin: Option<SpinKind>,
+}
+
+/// The Super Rotation System used by the Tetris Guideline.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Srs;
+
+// Kicks for clockwise rotations from each state, written in the usual SRS
+// notation where y grows upward.
+const SRS_JLSTZ: [[(isize, isize); 5]; 4] = [
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],
+];
+
+const SRS_I: [[(isize, isize); 5]; 4] = [
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],
+];
+
+// Kicks for 180-degree rotations from each state, which aren't defined by the
+// Guideline. These are the ones widely used by modern games.
+const SRS_180: [[(isize, isize); 6]; 4] = [
+    [(0, 0), (0, 1), (1, 1), (-1, 1), (1, 0), (-1, 0)],
+    [(0, 0), (1, 0), (1, 2), (1, 1), (0, 2), (0, 1)],
+    [(0, 0), (0, -1), (-1, -1), (1, -1), (-1, 0), (1, 0)],
+    [(0, 0), (-1, 0), (-1, 2), (-1, 1), (0, 2), (0, 1)],
+];
+
+impl RotationSystem for Srs {
+    fn kicks(&self, kind: BlockKind, from: usize, to: usize) -> Vec<(isize, isize)> {
+        let table = match kind {
+            BlockKind::O => return vec![(0, 0)],
+            BlockKind::I => &SRS_I,
+            _ => &SRS_JLSTZ,
+        };
+        if to == (from + 1) % 4 {
+            // Flip y so that it grows downward.
+            table[from].iter().map(|(x, y)| (*x, -y)).collect()
+        } else if from == (to + 1) % 4 {
+            // A counter-clockwise kick is the reverse of the clockwise one.
+            table[to].iter().map(|(x, y)| (-x, *y)).collect()
+        } else if to == (from + 2) % 4 {
+            SRS_180[from].iter().map(|(x, y)| (*x, -y)).collect()
+        } else {
+            vec![(0, 0)]
+        }
+    }
+}
+
+/// A rotation system without any wall kicks, like the classic games.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Classic;
+
+impl RotationSystem for Classic {
+    fn kicks(&self, _kind: BlockKind, _from: usize, _to: usize) -> Vec<(isize, isize)> {
+        vec![(0, 0)]
+    }
+}
+
+/// A rotation system which searches the nearest place the rotated tetrimino
+/// doesn't overlap, within the distance of 2 blocks.
+///
+/// This is the default rotation system. Use `KickSearch` to change the
+/// distance or the order.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NearSearch;
+
+impl RotationSystem for NearSearch {
+    fn kicks(&self, kind: BlockKind, from: usize, to: usize) -> Vec<(isize, isize)> {
+        KickSearch::default().kicks(kind, from, to)
+    }
+}
+
+/// A rotation system like `NearSearch`, with the configurable distance and
+/// order of the search.
+///
+/// The places are tried from the nearest one. Among the places at the same
+/// distance, upper ones and then left ones are tried first by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KickSearch {
+    /// How far the tetrimino can be moved along each axis. 0 disables
+    /// kicks.
+    pub radius: usize,
+    /// Whether to try lower places before upper ones.
+    pub prefer_down: bool,
+    /// Whether to try right places before left ones.
+    pub prefer_right: bool,
+}
+
+impl Default for KickSearch {
+    /// The same search as `NearSearch`.
+    fn default() -> Self {
+        Self {
+            radius: 2,
+            prefer_down: false,
+            prefer_right: false,
+        }
+    }
+}
+
+impl RotationSystem for KickSearch {
+    fn kicks(&self, _kind: BlockKind, _from: usize, _to: usize) -> Vec<(isize, isize)> {
+        let r = self.radius as isize;
+        let mut points: Vec<(isize, isize)> = (-r..=r)
+            .flat_map(|x| (-r..=r).map(move |y| (x, y)))
+            .collect();
+        let y_sign = if self.prefer_down { -1 } else { 1 };
+        let x_sign = if self.prefer_right { -1 } else { 1 };
+        points.sort_by_key(|(x, y)| (x.pow(2) + y.pow(2), y * y_sign, x * x_sign));
+        points
+    }
+}
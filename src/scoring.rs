@@ -0,0 +1,312 @@
+//! Scores awarded for locking tetriminos and dropping them.
+
+use alloc::{boxed::Box, sync::Arc};
+
+use crate::field::Field;
+use crate::lock::ClearKind;
+use crate::tetrimino::{BlockKind, Tetrimino};
+
+/// The kind of a spin, such as a T-spin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SpinKind {
+    /// A mini spin, which is awarded less than a full spin.
+    Mini,
+    /// A full spin.
+    Full,
+}
+
+/// A table deciding how many points each action is worth.
+///
+/// The points for clears, combos and perfect clears are multiplied by the
+/// level, while the points for drops are not.
+///
+/// Tables must be `Send` and `Sync`, as explained in
+/// [`SendGame`](crate::SendGame).
+pub trait ScoringTable: Send + Sync {
+    /// Returns the points for removing `lines` lines, with a spin if `spin` is
+    /// not `None`. This is also called for locks without removing lines.
+    fn clear(&self, lines: usize, spin: Option<SpinKind>) -> u64;
+
+    /// Returns true if the clear continues a back-to-back chain.
+    fn is_difficult(&self, lines: usize, spin: Option<SpinKind>) -> bool {
+        ClearKind::new(lines, spin).is_difficult()
+    }
+
+    /// Returns the points for a difficult clear following another one, given
+    /// the points for the clear itself.
+    fn back_to_back(&self, points: u64) -> u64 {
+        points
+    }
+
+    /// Returns the points for the `combo`-th consecutive clear. The first
+    /// clear is the 0th one.
+    fn combo(&self, _combo: usize) -> u64 {
+        0
+    }
+
+    /// Returns the bonus for a perfect clear removing `lines` lines.
+    fn perfect_clear(&self, _lines: usize) -> u64 {
+        0
+    }
+
+    /// Returns the points for soft-dropping by `cells` cells.
+    fn soft_drop(&self, _cells: usize) -> u64 {
+        0
+    }
+
+    /// Returns the points for hard-dropping by `cells` cells.
+    fn hard_drop(&self, _cells: usize) -> u64 {
+        0
+    }
+}
+
+/// The scoring of the Tetris Guideline.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Guideline;
+
+impl ScoringTable for Guideline {
+    fn clear(&self, lines: usize, spin: Option<SpinKind>) -> u64 {
+        match (spin, lines) {
+            (None, 0) => 0,
+            (None, 1) => 100,
+            (None, 2) => 300,
+            (None, 3) => 500,
+            (None, _) => 800,
+            (Some(SpinKind::Mini), 0) => 100,
+            (Some(SpinKind::Mini), 1) => 200,
+            (Some(SpinKind::Mini), _) => 400,
+            (Some(SpinKind::Full), 0) => 400,
+            (Some(SpinKind::Full), 1) => 800,
+            (Some(SpinKind::Full), 2) => 1200,
+            (Some(SpinKind::Full), _) => 1600,
+        }
+    }
+
+    fn back_to_back(&self, points: u64) -> u64 {
+        points * 3 / 2
+    }
+
+    fn combo(&self, combo: usize) -> u64 {
+        50 * combo as u64
+    }
+
+    fn perfect_clear(&self, lines: usize) -> u64 {
+        match lines {
+            0 => 0,
+            1 => 800,
+            2 => 1200,
+            3 => 1800,
+            _ => 2000,
+        }
+    }
+
+    fn soft_drop(&self, cells: usize) -> u64 {
+        cells as u64
+    }
+
+    fn hard_drop(&self, cells: usize) -> u64 {
+        2 * cells as u64
+    }
+}
+
+/// The scoring of the classic NES game, which has no spins, combos or
+/// back-to-back bonuses.
+///
+/// The levels of the NES game start from 0, so NES level 0 is level 1 here.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Nes;
+
+impl ScoringTable for Nes {
+    fn clear(&self, lines: usize, _spin: Option<SpinKind>) -> u64 {
+        match lines {
+            0 => 0,
+            1 => 40,
+            2 => 100,
+            3 => 300,
+            _ => 1200,
+        }
+    }
+
+    fn is_difficult(&self, _lines: usize, _spin: Option<SpinKind>) -> bool {
+        false
+    }
+
+    fn soft_drop(&self, cells: usize) -> u64 {
+        cells as u64
+    }
+}
+
+/// A score tracker.
+#[derive(Clone)]
+pub struct Score {
+    table: Arc<dyn ScoringTable>,
+    points: u64,
+    level: u64,
+    combo: Option<usize>,
+    back_to_back: bool,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ScoreCounters {
+    points: u64,
+    level: u64,
+    combo: Option<usize>,
+    back_to_back: bool,
+}
+
+impl Score {
+    /// Create a score from zero points at level 1.
+    pub fn new(table: Box<dyn ScoringTable>) -> Self {
+        Self {
+            table: Arc::from(table),
+            points: 0,
+            level: 1,
+            combo: None,
+            back_to_back: false,
+        }
+    }
+
+    /// Get the total points.
+    pub fn points(&self) -> u64 {
+        self.points
+    }
+
+    /// Get the level.
+    pub fn level(&self) -> u64 {
+        self.level
+    }
+
+    /// Set the level, which multiplies the points for clears.
+    pub fn set_level(&mut self, level: u64) {
+        self.level = level;
+    }
+
+    /// Get the number of consecutive clears before the current one, or `None`
+    /// if the last lock didn't remove any lines.
+    pub fn combo(&self) -> Option<usize> {
+        self.combo
+    }
+
+    /// Returns true if the last clear was a difficult one, so that the next
+    /// difficult clear gets the back-to-back bonus.
+    pub fn back_to_back(&self) -> bool {
+        self.back_to_back
+    }
+
+    // Go back to zero points at level 1, keeping the table.
+    pub(crate) fn reset(&mut self) {
+        self.points = 0;
+        self.level = 1;
+        self.combo = None;
+        self.back_to_back = false;
+    }
+
+    pub(crate) fn counters(&self) -> ScoreCounters {
+        ScoreCounters {
+            points: self.points,
+            level: self.level,
+            combo: self.combo,
+            back_to_back: self.back_to_back,
+        }
+    }
+
+    pub(crate) fn set_counters(&mut self, counters: ScoreCounters) {
+        self.points = counters.points;
+        self.level = counters.level;
+        self.combo = counters.combo;
+        self.back_to_back = counters.back_to_back;
+    }
+
+    /// Add the points for a lock and returns them.
+    pub fn lock(&mut self, lines: usize, spin: Option<SpinKind>, perfect_clear: bool) -> u64 {
+        let mut points = self.table.clear(lines, spin);
+        if lines > 0 {
+            let difficult = self.table.is_difficult(lines, spin);
+            if difficult && self.back_to_back {
+                points = self.table.back_to_back(points);
+            }
+            self.back_to_back = difficult;
+
+            let combo = self.combo.map_or(0, |c| c + 1);
+            points += self.table.combo(combo);
+            self.combo = Some(combo);
+        } else {
+            self.combo = None;
+        }
+        if perfect_clear {
+            points += self.table.perfect_clear(lines);
+        }
+
+        let points = points * self.level;
+        self.points += points;
+        points
+    }
+
+    /// Add the points for a soft drop and returns them.
+    pub fn soft_drop(&mut self, cells: usize) -> u64 {
+        let points = self.table.soft_drop(cells);
+        self.points += points;
+        points
+    }
+
+    /// Add the points for a hard drop and returns them.
+    pub fn hard_drop(&mut self, cells: usize) -> u64 {
+        let points = self.table.hard_drop(cells);
+        self.points += points;
+        points
+    }
+}
+
+impl Default for Score {
+    /// A score using the Guideline table.
+    fn default() -> Self {
+        Self::new(Box::new(Guideline))
+    }
+}
+
+impl core::fmt::Debug for Score {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Score")
+            .field("points", &self.points)
+            .field("level", &self.level)
+            .field("combo", &self.combo)
+            .field("back_to_back", &self.back_to_back)
+            .finish()
+    }
+}
+
+// The corners around the center of a T-tetrimino, the front two first for
+// each rotation.
+const T_CORNERS: [[(isize, isize); 4]; 4] = [
+    [(0, 0), (2, 0), (0, 2), (2, 2)],
+    [(2, 0), (2, 2), (0, 0), (0, 2)],
+    [(0, 2), (2, 2), (0, 0), (2, 0)],
+    [(0, 0), (0, 2), (2, 0), (2, 2)],
+];
+
+/// Detect a T-spin by the three-corner rule, given the kick used by the last
+/// rotation.
+pub(crate) fn detect_t_spin(
+    field: &Field,
+    tetrimino: &Tetrimino,
+    kick: (isize, isize),
+) -> Option<SpinKind> {
+    if tetrimino.kind() != BlockKind::T {
+        return None;
+    }
+
+    let (x, y) = tetrimino.position();
+    let filled =
+        T_CORNERS[tetrimino.rotation()].map(|(dx, dy)| field.is_occupied((x + dx, y + dy)));
+    let front = filled[..2].iter().filter(|f| **f).count();
+    let back = filled[2..].iter().filter(|f| **f).count();
+    if front + back < 3 {
+        None
+    } else if front == 2 || (kick.0.abs() == 1 && kick.1.abs() == 2) {
+        // The last kick of SRS always makes a full T-spin.
+        Some(SpinKind::Full)
+    } else {
+        Some(SpinKind::Mini)
+    }
+}
@@ -0,0 +1,391 @@
+//! Selectors deciding the kind of each new tetrimino.
+
+use alloc::{boxed::Box, collections::VecDeque, vec, vec::Vec};
+
+use crate::random::Rng;
+use crate::{BlockKind, Field, Tetrimino};
+
+/// A source of the block kinds of new tetriminos.
+///
+/// Any `FnMut() -> BlockKind` closure is a selector, so you can pass your own
+/// function as well as the built-in randomizers in this module. Wrap a
+/// `FnMut(&GameView) -> BlockKind` closure with `WithView` to see the game.
+pub trait PieceSelector {
+    /// Returns the kind of the next tetrimino.
+    fn select(&mut self) -> BlockKind;
+
+    /// Returns the kind of the next tetrimino, looking at the game. The game
+    /// always calls this instead of `select`.
+    ///
+    /// The default implementation ignores the game and calls `select`.
+    fn select_with_view(&mut self, _view: &GameView) -> BlockKind {
+        self.select()
+    }
+}
+
+/// The state of a game seen by selectors.
+#[derive(Debug, Clone, Copy)]
+pub struct GameView<'game> {
+    pub(crate) field: &'game Field,
+    pub(crate) queue: &'game VecDeque<Tetrimino>,
+    pub(crate) held: Option<&'game Tetrimino>,
+}
+
+impl<'game> GameView<'game> {
+    /// Get the field.
+    pub fn field(&self) -> &'game Field {
+        self.field
+    }
+
+    /// Get the queue of next tetriminos, which doesn't include the tetrimino
+    /// being selected yet.
+    pub fn queue(&self) -> &'game VecDeque<Tetrimino> {
+        self.queue
+    }
+
+    /// Get the held tetrimino.
+    pub fn held(&self) -> Option<&'game Tetrimino> {
+        self.held
+    }
+}
+
+/// A selector made from a closure looking at the game, for example to give
+/// the worst tetrimino for the player or the one they need.
+///
+/// ```
+/// use tetrice::selectors::WithView;
+/// use tetrice::{BlockKind, Game};
+///
+/// // Give an I-tetrimino whenever the field has a deep well.
+/// let selector = WithView(|view: &tetrice::selectors::GameView| {
+///     if view.field().wells().iter().any(|depth| *depth >= 4) {
+///         BlockKind::I
+///     } else {
+///         BlockKind::O
+///     }
+/// });
+/// let game = Game::new(10, 20, 3, selector);
+/// ```
+#[derive(Debug, Clone)]
+pub struct WithView<F>(pub F);
+
+impl<F: FnMut(&GameView) -> BlockKind> PieceSelector for WithView<F> {
+    /// Calls the closure with an empty 10x20 field, since there is no game.
+    fn select(&mut self) -> BlockKind {
+        let field = Field::new(10, 20, crate::field::DEFAULT_HIDDEN_HEIGHT);
+        self.select_with_view(&GameView {
+            field: &field,
+            queue: &VecDeque::new(),
+            held: None,
+        })
+    }
+
+    fn select_with_view(&mut self, view: &GameView) -> BlockKind {
+        (self.0)(view)
+    }
+}
+
+impl<F: FnMut() -> BlockKind> PieceSelector for F {
+    fn select(&mut self) -> BlockKind {
+        self()
+    }
+}
+
+impl PieceSelector for Box<dyn PieceSelector> {
+    fn select(&mut self) -> BlockKind {
+        (**self).select()
+    }
+
+    fn select_with_view(&mut self, view: &GameView) -> BlockKind {
+        (**self).select_with_view(view)
+    }
+}
+
+impl PieceSelector for Box<dyn PieceSelector + Send> {
+    fn select(&mut self) -> BlockKind {
+        (**self).select()
+    }
+
+    fn select_with_view(&mut self, view: &GameView) -> BlockKind {
+        (**self).select_with_view(view)
+    }
+}
+
+/// A bag randomizer, which shuffles a bag including every kind the same times
+/// and deals tetriminos from it.
+#[derive(Debug, Clone)]
+pub struct Bag {
+    copies: usize,
+    bag: Vec<BlockKind>,
+    rng: Rng,
+}
+
+impl Bag {
+    /// Create a bag including every kind `copies` times.
+    pub fn new(copies: usize, seed: u64) -> Self {
+        if copies < 1 {
+            panic!("a bag must include at least one copy")
+        }
+
+        Self {
+            copies,
+            bag: Vec::new(),
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// Create the standard 7-bag.
+    pub fn seven(seed: u64) -> Self {
+        Self::new(1, seed)
+    }
+
+    /// Create a 14-bag, which includes every kind twice.
+    pub fn fourteen(seed: u64) -> Self {
+        Self::new(2, seed)
+    }
+
+    /// Create a bag including every kind `copies` times, seeded by a random
+    /// number generator of the `rand` crate.
+    #[cfg(feature = "rand")]
+    pub fn from_rng(copies: usize, rng: &mut impl rand::RngCore) -> Self {
+        Self::new(copies, rng.next_u64())
+    }
+
+    /// Get the number of kinds in a full bag.
+    pub fn size(&self) -> usize {
+        7 * self.copies
+    }
+
+    /// Get the kinds left in the bag, in the reverse order of dealing. The
+    /// bag is refilled when it's empty and another kind is needed.
+    pub fn remaining(&self) -> &[BlockKind] {
+        &self.bag
+    }
+
+    fn refill(&mut self) {
+        self.bag = BlockKind::all_as_array().repeat(self.copies);
+        // Fisher-Yates shuffle
+        for i in (1..self.bag.len()).rev() {
+            let j = self.rng.below(i + 1);
+            self.bag.swap(i, j);
+        }
+    }
+}
+
+impl PieceSelector for Bag {
+    fn select(&mut self) -> BlockKind {
+        if self.bag.is_empty() {
+            self.refill();
+        }
+        self.bag.pop().unwrap()
+    }
+}
+
+/// A randomizer selecting every kind independently with the same probability.
+#[derive(Debug, Clone)]
+pub struct Random {
+    rng: Rng,
+}
+
+impl Random {
+    /// Create a randomizer from the seed.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: Rng::new(seed),
+        }
+    }
+}
+
+impl PieceSelector for Random {
+    fn select(&mut self) -> BlockKind {
+        BlockKind::all_as_array()[self.rng.below(7)]
+    }
+}
+
+/// A history randomizer like the TGM series.
+///
+/// It remembers the recently selected kinds and rerolls a few times when the
+/// selected kind is in the history.
+#[derive(Debug, Clone)]
+pub struct History {
+    history: Vec<BlockKind>,
+    rolls: usize,
+    is_first: bool,
+    rng: Rng,
+}
+
+impl History {
+    /// Create a history randomizer with the initial history and the number of
+    /// rolls.
+    pub fn new(history: Vec<BlockKind>, rolls: usize, seed: u64) -> Self {
+        Self {
+            history,
+            rolls,
+            is_first: true,
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// Create the randomizer of TGM, which has 4 rolls.
+    pub fn tgm(seed: u64) -> Self {
+        Self::new(vec![BlockKind::Z; 4], 4, seed)
+    }
+
+    /// Create the randomizer of TGM2, which has 6 rolls.
+    pub fn tgm2(seed: u64) -> Self {
+        Self::new(
+            vec![BlockKind::Z, BlockKind::S, BlockKind::S, BlockKind::Z],
+            6,
+            seed,
+        )
+    }
+
+    fn roll(&mut self) -> BlockKind {
+        BlockKind::all_as_array()[self.rng.below(7)]
+    }
+}
+
+impl PieceSelector for History {
+    fn select(&mut self) -> BlockKind {
+        let kind = if self.is_first {
+            // The first tetrimino is never S, Z or O.
+            self.is_first = false;
+            loop {
+                let kind = self.roll();
+                if ![BlockKind::S, BlockKind::Z, BlockKind::O].contains(&kind) {
+                    break kind;
+                }
+            }
+        } else {
+            let mut kind = self.roll();
+            for _ in 1..self.rolls {
+                if !self.history.contains(&kind) {
+                    break;
+                }
+                kind = self.roll();
+            }
+            kind
+        };
+
+        if !self.history.is_empty() {
+            self.history.remove(0);
+            self.history.push(kind);
+        }
+        kind
+    }
+}
+
+/// A randomizer selecting every kind independently with the probability in
+/// proportion to its weight, for example to give more T-tetriminos for
+/// practicing T-spins.
+///
+/// ```
+/// use tetrice::selectors::Weighted;
+/// use tetrice::{BlockKind, Game};
+///
+/// // Half of the tetriminos are T, and the others are I or O.
+/// let selector = Weighted::new(
+///     [(BlockKind::T, 2.0), (BlockKind::I, 1.0), (BlockKind::O, 1.0)],
+///     0,
+/// );
+/// let game = Game::new(10, 20, 3, selector);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Weighted {
+    weights: [f64; 7],
+    rng: Rng,
+}
+
+impl Weighted {
+    /// Create a randomizer from the weights of the kinds, such as a
+    /// `HashMap<BlockKind, f64>`. The kinds not included are never selected.
+    ///
+    /// Panics if a weight is negative or not finite, or all of them are zero.
+    pub fn new(weights: impl IntoIterator<Item = (BlockKind, f64)>, seed: u64) -> Self {
+        let mut table = [0.0; 7];
+        for (kind, weight) in weights {
+            if !(weight.is_finite() && weight >= 0.0) {
+                panic!("weights must be finite and non-negative")
+            }
+            table[kind as usize] += weight;
+        }
+        if table.iter().sum::<f64>() <= 0.0 {
+            panic!("at least one weight must be positive")
+        }
+
+        Self {
+            weights: table,
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// Get the weight of the kind.
+    pub fn weight(&self, kind: BlockKind) -> f64 {
+        self.weights[kind as usize]
+    }
+}
+
+impl PieceSelector for Weighted {
+    fn select(&mut self) -> BlockKind {
+        let mut rest = self.rng.unit() * self.weights.iter().sum::<f64>();
+        let kinds = BlockKind::all_as_array();
+        for (kind, weight) in kinds.iter().zip(self.weights) {
+            if weight > 0.0 && rest < weight {
+                return *kind;
+            }
+            rest -= weight;
+        }
+        // Rounding errors can leave a little; take the last possible kind.
+        let last = self.weights.iter().rposition(|w| *w > 0.0).unwrap();
+        kinds[last]
+    }
+}
+
+/// A selector dealing the kinds in the script first, and then using another
+/// selector, for example to practice a fixed opening.
+///
+/// ```
+/// use tetrice::selectors::{Bag, Scripted};
+/// use tetrice::{BlockKind, Game};
+///
+/// let script = vec![BlockKind::I, BlockKind::O, BlockKind::L];
+/// let game = Game::new(10, 20, 3, Scripted::new(script, Bag::seven(0)));
+/// assert_eq!(game.tetrimino().kind(), BlockKind::I);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Scripted<S> {
+    script: VecDeque<BlockKind>,
+    fallback: S,
+}
+
+impl<S: PieceSelector> Scripted<S> {
+    /// Create a selector dealing the script, followed by the kinds from
+    /// `fallback`.
+    pub fn new(script: Vec<BlockKind>, fallback: S) -> Self {
+        Self {
+            script: script.into(),
+            fallback,
+        }
+    }
+
+    /// Get the kinds in the script not dealt yet.
+    pub fn remaining(&self) -> &VecDeque<BlockKind> {
+        &self.script
+    }
+}
+
+impl<S: PieceSelector> PieceSelector for Scripted<S> {
+    fn select(&mut self) -> BlockKind {
+        match self.script.pop_front() {
+            Some(kind) => kind,
+            None => self.fallback.select(),
+        }
+    }
+
+    fn select_with_view(&mut self, view: &GameView) -> BlockKind {
+        match self.script.pop_front() {
+            Some(kind) => kind,
+            None => self.fallback.select_with_view(view),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{BlockKind, Field, Tetrimino};
+
+/// Actions applied to the next tetrimino as soon as it appears, known as the
+/// initial rotation (IRS) and the initial hold (IHS).
+///
+/// Set them by `Game::set_initial_actions` while the player holds the keys
+/// before the tetrimino appears. They are used once, by the next tetrimino
+/// appearing after saving.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InitialActions {
+    /// How many times to rotate the tetrimino clockwise: 1 for clockwise, 2
+    /// for 180 degrees and 3 for counter-clockwise. 0 doesn't rotate it.
+    pub rotation: usize,
+    /// Whether to hold the tetrimino. The rotation is applied to the one
+    /// coming out by holding.
+    pub hold: bool,
+}
+
+/// A rule deciding where and how each tetrimino appears.
+///
+/// The tetrimino is centered horizontally, rounding to the left, then moved
+/// by the offset of its kind. If it overlaps blocks there, it is moved up one
+/// row at a time, up to `max_lift` rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpawnRule {
+    /// The rotation state each kind appears in, in the order of O, I, Z, S,
+    /// L, T and J.
+    pub rotations: [usize; 7],
+    /// The horizontal offset from the center for each kind, in the same
+    /// order as `rotations`.
+    pub offsets: [isize; 7],
+    /// The y coordinate of the lowest row of the tetrimino, where -1 is the
+    /// row just above the visible area. The tetrimino never goes above the
+    /// non-visible area.
+    pub bottom_row: isize,
+    /// How many rows the tetrimino can be moved up when it overlaps blocks.
+    pub max_lift: usize,
+}
+
+impl SpawnRule {
+    /// The rule of the Guideline. Every tetrimino appears flat side down in
+    /// the two rows above the visible area, the 3-wide ones left of the
+    /// center, and is moved up at most one row.
+    pub fn guideline() -> Self {
+        Self {
+            max_lift: 1,
+            ..Self::default()
+        }
+    }
+
+    // Returns the tetrimino of the kind at where it appears, before moving it
+    // up.
+    pub(crate) fn place(&self, kind: BlockKind, field: &Field) -> Tetrimino {
+        let t = Tetrimino::new(kind).rotate(self.rotations[kind as usize]);
+        let left = (field.width() - t.width()) as isize / 2 + self.offsets[kind as usize];
+        let top =
+            (self.bottom_row + 1 - t.height() as isize).max(-(field.hidden_height() as isize));
+        t.move_to((left, top))
+    }
+}
+
+impl Default for SpawnRule {
+    /// The same as `guideline`, but the tetrimino can be moved up 4 rows.
+    fn default() -> Self {
+        Self {
+            rotations: [0; 7],
+            offsets: [0; 7],
+            bottom_row: -1,
+            max_lift: 4,
+        }
+    }
+}
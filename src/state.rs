@@ -0,0 +1,327 @@
+use alloc::{collections::VecDeque, vec, vec::Vec};
+use core::time::Duration;
+
+use crate::random::Rng;
+use crate::scoring::ScoreCounters;
+use crate::timing::Delay;
+use crate::visibility::LockTimes;
+use crate::{BlockKind, Cell, Checker, EndReason, Field, Input, Stats, Tetrimino};
+
+/// A snapshot of a game, made by `Game::to_state`.
+///
+/// This contains everything except the selector and the rules, so a game can
+/// be restored from it with `Game::from_state`. With the `serde` feature, it
+/// can also be serialized to save games or send them over the network.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameState {
+    /// The field.
+    pub field: Field,
+    /// The current tetrimino.
+    pub tetrimino: Tetrimino,
+    /// The queue of next tetriminos.
+    pub queue: VecDeque<Tetrimino>,
+    /// The held tetriminos, the one held first at the front.
+    pub held: VecDeque<Tetrimino>,
+    /// Whether the current tetrimino can be held.
+    pub can_hold: bool,
+    /// Why the game has ended, or `None` if it hasn't ended.
+    pub end_reason: Option<EndReason>,
+    /// The number of lines removed in the game.
+    pub removed_lines: usize,
+}
+
+/// The first part where two states differ, found by `GameState::diff`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StateDiff {
+    /// The fields have different sizes.
+    FieldSize,
+    /// The row of the field at the y coordinate differs.
+    FieldRow(isize),
+    /// The current tetriminos differ.
+    Tetrimino,
+    /// The tetriminos in the queue differ at the index, or one of the queues
+    /// ends there.
+    Queue(usize),
+    /// The held tetriminos differ at the index, or one of them ends there.
+    Held(usize),
+    /// Whether the tetrimino can be held differs.
+    CanHold,
+    /// The reasons of the end differ.
+    EndReason,
+    /// The numbers of removed lines differ.
+    RemovedLines,
+}
+
+/// An error returned by `Game::from_state` for a state which can't be
+/// played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StateError {
+    /// The current tetrimino has blocks outside the field.
+    TetriminoOutside,
+    /// The current tetrimino overlaps blocks, although the game hasn't
+    /// ended.
+    TetriminoOverlaps,
+    /// The held tetrimino at the index has blocks outside the field.
+    HeldOutside(usize),
+}
+
+impl core::fmt::Display for StateError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            StateError::TetriminoOutside => write!(f, "the tetrimino is outside the field"),
+            StateError::TetriminoOverlaps => write!(f, "the tetrimino overlaps blocks"),
+            StateError::HeldOutside(index) => {
+                write!(f, "held tetrimino {} is outside the field", index)
+            }
+        }
+    }
+}
+
+impl core::error::Error for StateError {}
+
+impl GameState {
+    /// Check that the tetriminos are placed where the game can go on: every
+    /// block of the current and the held tetriminos lies in the field, and
+    /// the current one doesn't overlap blocks unless the game has ended.
+    pub fn validate(&self) -> Result<(), StateError> {
+        let inside = |t: &Tetrimino| {
+            t.blocks()
+                .iter()
+                .all(|pos| self.field.get_cell(*pos) != Cell::Outside)
+        };
+        if !inside(&self.tetrimino) {
+            return Err(StateError::TetriminoOutside);
+        }
+        if self.end_reason.is_none() && Checker(&self.field, &self.tetrimino).overlap() {
+            return Err(StateError::TetriminoOverlaps);
+        }
+        if let Some(index) = self.held.iter().position(|t| !inside(t)) {
+            return Err(StateError::HeldOutside(index));
+        }
+        Ok(())
+    }
+
+    /// Find the first part where this state differs from `other`, in the
+    /// order of the variants of `StateDiff`. Returns `None` if they are the
+    /// same, which is useful for finding desyncs.
+    pub fn diff(&self, other: &GameState) -> Option<StateDiff> {
+        let (a, b) = (&self.field, &other.field);
+        if a.width() != b.width()
+            || a.height() != b.height()
+            || a.hidden_height() != b.hidden_height()
+        {
+            return Some(StateDiff::FieldSize);
+        }
+        if let Some(((y, _), _)) = a.rows().zip(b.rows()).find(|(a, b)| a.1 != b.1) {
+            return Some(StateDiff::FieldRow(y));
+        }
+        if self.tetrimino != other.tetrimino {
+            return Some(StateDiff::Tetrimino);
+        }
+        if let Some(index) = first_difference(&self.queue, &other.queue) {
+            return Some(StateDiff::Queue(index));
+        }
+        if let Some(index) = first_difference(&self.held, &other.held) {
+            return Some(StateDiff::Held(index));
+        }
+        if self.can_hold != other.can_hold {
+            Some(StateDiff::CanHold)
+        } else if self.end_reason != other.end_reason {
+            Some(StateDiff::EndReason)
+        } else if self.removed_lines != other.removed_lines {
+            Some(StateDiff::RemovedLines)
+        } else {
+            None
+        }
+    }
+
+    /// Encode this state into compact bytes, which is smaller and faster
+    /// than serializing it. The layout is fixed by the size of the field and
+    /// the number of the tetriminos, so this is suitable for sending every
+    /// frame.
+    ///
+    /// Each cell takes 4 bits, and each tetrimino takes 5 bytes. Returns
+    /// `None` if the state doesn't fit in the format: the field can have up
+    /// to 65535 visible rows and 255 hidden rows, the queue and the held
+    /// tetriminos up to 65535 each, the positions must fit in `i16`, and the
+    /// removed lines in `u32`.
+    pub fn encode(&self) -> Option<Vec<u8>> {
+        let field = &self.field;
+        let mut bytes = vec![u8::try_from(field.width()).ok()?];
+        bytes.extend(u16::try_from(field.height()).ok()?.to_le_bytes());
+        bytes.push(u8::try_from(field.hidden_height()).ok()?);
+        let cells: Vec<_> = field
+            .as_vec_full()
+            .iter()
+            .flatten()
+            .map(|cell| cell.to_code())
+            .collect();
+        bytes.extend(
+            cells
+                .chunks(2)
+                .map(|pair| pair[0] | pair.get(1).map_or(0, |c| c << 4)),
+        );
+
+        encode_tetrimino(&self.tetrimino, &mut bytes)?;
+        for tetriminos in [&self.queue, &self.held] {
+            bytes.extend(u16::try_from(tetriminos.len()).ok()?.to_le_bytes());
+            for t in tetriminos {
+                encode_tetrimino(t, &mut bytes)?;
+            }
+        }
+        bytes.push(self.can_hold as u8);
+        bytes.push(match self.end_reason {
+            None => 0,
+            Some(EndReason::LockOut) => 1,
+            Some(EndReason::BlockOut) => 2,
+            Some(EndReason::GarbageOut) => 3,
+        });
+        bytes.extend(u32::try_from(self.removed_lines).ok()?.to_le_bytes());
+        Some(bytes)
+    }
+
+    /// Decode bytes made by `encode`. Returns `None` if the bytes are broken
+    /// or the state isn't valid (see `validate`).
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let mut reader = Reader(bytes);
+        let width = reader.u8()? as usize;
+        let height = u16::from_le_bytes(reader.take()?) as usize;
+        let hidden_height = reader.u8()? as usize;
+        if !(1..=crate::MAX_WIDTH).contains(&width) || height == 0 {
+            return None;
+        }
+        let count = width * (height + hidden_height);
+        let cells = reader
+            .bytes(count.div_ceil(2))?
+            .iter()
+            .flat_map(|byte| [byte & 0xf, byte >> 4])
+            .take(count)
+            .map(Cell::from_code)
+            .collect::<Option<Vec<_>>>()?;
+        let field = Field::from_rows(
+            cells.chunks(width).map(<[Cell]>::to_vec).collect(),
+            hidden_height,
+        );
+
+        let tetrimino = reader.tetrimino()?;
+        let mut lists = [VecDeque::new(), VecDeque::new()];
+        for list in &mut lists {
+            for _ in 0..u16::from_le_bytes(reader.take()?) {
+                list.push_back(reader.tetrimino()?);
+            }
+        }
+        let [queue, held] = lists;
+        let can_hold = reader.u8()? != 0;
+        let end_reason = match reader.u8()? {
+            0 => None,
+            1 => Some(EndReason::LockOut),
+            2 => Some(EndReason::BlockOut),
+            3 => Some(EndReason::GarbageOut),
+            _ => return None,
+        };
+        let removed_lines = u32::from_le_bytes(reader.take()?) as usize;
+        if !reader.0.is_empty() {
+            return None;
+        }
+        let state = Self {
+            field,
+            tetrimino,
+            queue,
+            held,
+            can_hold,
+            end_reason,
+            removed_lines,
+        };
+        state.validate().ok()?;
+        Some(state)
+    }
+}
+
+// The index of the first different tetriminos, or the shorter length.
+fn first_difference(a: &VecDeque<Tetrimino>, b: &VecDeque<Tetrimino>) -> Option<usize> {
+    (0..a.len().max(b.len())).find(|i| a.get(*i) != b.get(*i))
+}
+
+fn encode_tetrimino(t: &Tetrimino, bytes: &mut Vec<u8>) -> Option<()> {
+    let (x, y) = t.position();
+    bytes.push((t.kind() as u8) << 2 | t.rotation() as u8);
+    bytes.extend(i16::try_from(x).ok()?.to_le_bytes());
+    bytes.extend(i16::try_from(y).ok()?.to_le_bytes());
+    Some(())
+}
+
+// A cursor over the bytes to decode.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let (head, tail) = self.0.split_at_checked(len)?;
+        self.0 = tail;
+        Some(head)
+    }
+
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        self.bytes(N)?.try_into().ok()
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.take::<1>()?[0])
+    }
+
+    fn tetrimino(&mut self) -> Option<Tetrimino> {
+        let tag = self.u8()?;
+        let kind = *BlockKind::all_as_array().get(tag as usize >> 2)?;
+        let x = i16::from_le_bytes(self.take()?) as isize;
+        let y = i16::from_le_bytes(self.take()?) as isize;
+        Some(
+            Tetrimino::new(kind)
+                .rotate(tag as usize & 3)
+                .move_right(x)
+                .move_down(y),
+        )
+    }
+}
+
+/// A snapshot of a game made by `Game::snapshot`, which also remembers the
+/// score and the position of the selector.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub(crate) state: GameState,
+    pub(crate) score: ScoreCounters,
+    pub(crate) dealt_pos: usize,
+}
+
+/// A snapshot of a game made by `Game::save_snapshot` for rollback, which is
+/// faster to make and restore than `Snapshot`.
+///
+/// This also remembers the timers and the statistics, so that the game goes
+/// on exactly the same way after restoring. The events and the history are
+/// not restored.
+#[derive(Debug, Clone)]
+pub struct RollbackSnapshot {
+    pub(crate) cells: Vec<Cell>,
+    pub(crate) masks: Vec<u64>,
+    pub(crate) hidden_height: usize,
+    pub(crate) tetrimino: Tetrimino,
+    pub(crate) queue: VecDeque<Tetrimino>,
+    pub(crate) held: VecDeque<Tetrimino>,
+    pub(crate) can_hold: bool,
+    pub(crate) end_reason: Option<EndReason>,
+    pub(crate) removed_lines: usize,
+    pub(crate) score: ScoreCounters,
+    pub(crate) dealt_pos: usize,
+    pub(crate) fall_progress: f64,
+    pub(crate) lock_elapsed: Option<Duration>,
+    pub(crate) lock_resets: usize,
+    pub(crate) last_kick: Option<(isize, isize)>,
+    pub(crate) floor_elapsed: Duration,
+    pub(crate) floor_rng: Rng,
+    pub(crate) piece_inputs: Vec<(Input, bool)>,
+    pub(crate) finesse_faults: usize,
+    pub(crate) stats: Stats,
+    pub(crate) delay: Option<(Delay, Duration)>,
+    pub(crate) clearing_field: Option<Field>,
+    pub(crate) buffered_inputs: Vec<Input>,
+    pub(crate) lock_times: LockTimes,
+}
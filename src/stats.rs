@@ -0,0 +1,83 @@
+use core::time::Duration;
+
+use crate::attack::AttackTable;
+use crate::{BlockKind, LockResult, SpinKind};
+
+/// Statistics of a game, accumulated by `Game` and taken by `Game::stats`.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stats {
+    /// The number of tetriminos saved for each kind, indexed by
+    /// `BlockKind as usize`.
+    pub pieces: [usize; 7],
+    /// The number of clears removing one line.
+    pub singles: usize,
+    /// The number of clears removing two lines.
+    pub doubles: usize,
+    /// The number of clears removing three lines.
+    pub triples: usize,
+    /// The number of clears removing four lines.
+    pub tetrises: usize,
+    /// The number of full T-spins, indexed by the number of removed lines.
+    pub t_spins: [usize; 4],
+    /// The number of mini T-spins, indexed by the number of removed lines.
+    pub mini_t_spins: [usize; 3],
+    /// The number of perfect clears.
+    pub perfect_clears: usize,
+    /// The longest combo, which is the highest `LockResult::combo`.
+    pub max_combo: usize,
+    /// The total garbage lines the clears would send with the default
+    /// attack table.
+    pub attack: u64,
+    /// The time passed in `Game::update`.
+    pub elapsed: Duration,
+    // The table for `attack`, kept to avoid making it for every lock.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    attack_table: AttackTable,
+}
+
+impl Stats {
+    /// Get the number of tetriminos saved of the kind.
+    pub fn pieces_of(&self, kind: BlockKind) -> usize {
+        self.pieces[kind as usize]
+    }
+
+    /// Get the number of tetriminos saved.
+    pub fn total_pieces(&self) -> usize {
+        self.pieces.iter().sum()
+    }
+
+    /// Get the number of tetriminos saved per second, or `None` if no time
+    /// has passed in `Game::update`.
+    pub fn pieces_per_second(&self) -> Option<f64> {
+        if self.elapsed.is_zero() {
+            None
+        } else {
+            Some(self.total_pieces() as f64 / self.elapsed.as_secs_f64())
+        }
+    }
+
+    pub(crate) fn record(&mut self, kind: BlockKind, result: &LockResult) {
+        self.pieces[kind as usize] += 1;
+        let kind = result.clear_kind;
+        match kind.spin() {
+            Some(SpinKind::Full) => self.t_spins[kind.lines()] += 1,
+            Some(SpinKind::Mini) => self.mini_t_spins[kind.lines()] += 1,
+            None => {}
+        }
+        match kind.lines() {
+            1 => self.singles += 1,
+            2 => self.doubles += 1,
+            3 => self.triples += 1,
+            4 => self.tetrises += 1,
+            _ => {}
+        }
+        if result.is_perfect_clear {
+            self.perfect_clears += 1;
+        }
+        if let Some(combo) = result.combo {
+            self.max_combo = self.max_combo.max(combo);
+        }
+        self.attack += self.attack_table.lines(result) as u64;
+    }
+}
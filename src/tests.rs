@@ -1,12 +1,29 @@
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
 
 use crate::{
+    attack::AttackTable,
+    battle::{self, Battle, BattleRules, Player},
+    checker::{Checker, Move, RouteSearch},
+    cheese::{CheeseRace, CheeseRules},
+    eval::{Features, Weights},
     field::{Cell, Field},
+    finesse::{self, FinesseFault, FinesseTable},
+    input::{AutoRepeat, Handling},
+    modes::{Marathon, Sprint, Ultra},
+    puzzle::{Goal, Puzzle, PuzzleGame, PuzzleStatus},
+    replay::{Recorder, Replay},
+    rotation::{Classic, KickSearch, NearSearch, RotationResult, RotationSystem, Srs},
+    scoring::{Nes, Score},
+    selectors::{Bag, GameView, History, PieceSelector, Random, WithView},
     tetrimino::{BlockKind, Tetrimino},
-    Game,
+    ActionResult, BuildError, ClearKind, EndReason, Game, GameEvent, GameListener, GameState,
+    GhostMode, Glyphs, Gravity, HistoryEntry, HoldRule, InitialActions, Input, LevelTable,
+    LockDelay, LockResult, ParseFieldError, Phase, RisingFloor, SendGame, SpawnRule, SpinKind,
+    StateDiff, StateError, Stats, TickResult, Visibility, MAX_WIDTH,
 };
 
-fn make_selector() -> Box<dyn FnMut() -> BlockKind> {
+fn make_selector() -> Box<dyn PieceSelector> {
     let mut count = 0;
     Box::new(move || {
         count += 1;
@@ -87,10 +104,13 @@ fn create_a_tetrimino() {
 #[test]
 fn locate_the_tetrimino_higher_when_it_overlaps() {
     let mut game = make_game();
+    // The row isn't filled, so it stays after saving.
+    let mut row = vec![Cell::Block(BlockKind::O); 10];
+    row[9] = Cell::Empty;
     game.field = Field::from_vec(
         [
             vec![vec![Cell::Empty; 10]; 6],
-            vec![vec![Cell::Block(BlockKind::O); 10]],
+            vec![row],
             vec![vec![Cell::Empty; 10]; 20],
         ]
         .concat(),
@@ -133,7 +153,7 @@ fn move_tetrimino_to_left() {
         game.tetrimino(),
         &Tetrimino::new(BlockKind::T).move_to((3 - 1, -2)),
     );
-    assert_eq!(result, true);
+    assert!(result);
 }
 
 #[test]
@@ -145,7 +165,7 @@ fn move_tetrimino_to_right() {
         game.tetrimino(),
         &Tetrimino::new(BlockKind::T).move_to((3 + 1, -2))
     );
-    assert_eq!(result, true);
+    assert!(result);
 }
 
 #[test]
@@ -157,7 +177,41 @@ fn soft_drop() {
         game.tetrimino(),
         &Tetrimino::new(BlockKind::T).move_to((3, -2 + 1))
     );
-    assert_eq!(result, true);
+    assert_eq!(result, 1);
+}
+
+#[test]
+fn move_tetrimino_to_wall() {
+    let mut game = make_game();
+    assert_eq!(game.move_left_to_wall(), 3);
+    assert_eq!(
+        game.tetrimino(),
+        &Tetrimino::new(BlockKind::T).move_to((0, -2))
+    );
+    assert_eq!(game.move_left_to_wall(), 0);
+
+    let mut row = vec![Cell::Empty; 10];
+    row[8] = Cell::Garbage;
+    game.field = Field::from_vec(vec![row; 27]);
+    assert_eq!(game.move_right_to_wall(), 5);
+    assert_eq!(
+        game.tetrimino(),
+        &Tetrimino::new(BlockKind::T).move_to((5, -2))
+    );
+}
+
+#[test]
+fn soft_drop_by_rows() {
+    let mut game = make_game();
+    assert_eq!(game.soft_drop_by(5), 5);
+    assert_eq!(game.score().points(), 5);
+    assert_eq!(game.soft_drop_by(30), 15);
+    assert_eq!(
+        game.tetrimino(),
+        &Tetrimino::new(BlockKind::T).move_to((3, 18))
+    );
+    assert_eq!(game.soft_drop(), 0);
+    assert_eq!(game.score().points(), 20);
 }
 
 #[test]
@@ -169,7 +223,7 @@ fn do_not_go_through_border() {
 
     let result = game.move_left();
     assert_eq!(game.tetrimino(), &original);
-    assert_eq!(result, false);
+    assert!(!result);
 }
 
 #[test]
@@ -190,7 +244,7 @@ fn do_not_go_through_other_blocks() {
 
     let result = game.move_left();
     assert_eq!(game.tetrimino(), &original);
-    assert_eq!(result, false);
+    assert!(!result);
 }
 
 #[test]
@@ -201,7 +255,8 @@ fn rotate_tetrimino() {
         game.tetrimino(),
         &Tetrimino::new(BlockKind::T).move_to((3, -2)).rotate(1)
     );
-    assert_eq!(result, true);
+    assert!(result.rotated);
+    assert_eq!(result.kick, None);
 }
 
 #[test]
@@ -218,6 +273,120 @@ fn move_tetrimino_not_to_overlap_after_rotation() {
     );
 }
 
+#[test]
+fn rotate_tetrimino_counter_clockwise() {
+    let mut game = make_game();
+    let result = game.rotate_ccw();
+    assert_eq!(
+        game.tetrimino(),
+        &Tetrimino::new(BlockKind::T).move_to((3, -2)).rotate(3)
+    );
+    assert!(result.rotated);
+}
+
+#[test]
+fn rotate_tetrimino_by_180_degrees() {
+    let mut game = make_game();
+    let result = game.rotate_180();
+    assert_eq!(
+        game.tetrimino(),
+        &Tetrimino::new(BlockKind::T).move_to((3, -2)).rotate(2)
+    );
+    assert!(result.rotated);
+}
+
+#[test]
+fn srs_provides_kick_tables() {
+    assert_eq!(
+        Srs.kicks(BlockKind::T, 0, 1),
+        vec![(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)]
+    );
+    assert_eq!(
+        Srs.kicks(BlockKind::T, 1, 0),
+        vec![(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)]
+    );
+    assert_eq!(
+        Srs.kicks(BlockKind::I, 0, 1),
+        vec![(0, 0), (-2, 0), (1, 0), (-2, 1), (1, -2)]
+    );
+    assert_eq!(
+        Srs.kicks(BlockKind::T, 0, 2),
+        vec![(0, 0), (0, -1), (1, -1), (-1, -1), (1, 0), (-1, 0)]
+    );
+    assert_eq!(Srs.kicks(BlockKind::O, 0, 0), vec![(0, 0)]);
+}
+
+#[test]
+fn kick_i_tetrimino_from_wall_with_srs() {
+    let mut game = make_game();
+    game.set_rotation_system(Box::new(Srs));
+    game.tetrimino = Tetrimino::new(BlockKind::I)
+        .rotate(1)
+        .move_right(7)
+        .move_down(10);
+
+    let result = game.rotate();
+    assert!(result.rotated);
+    assert_eq!(result.kick, Some((-1, 0)));
+    assert_eq!(
+        game.tetrimino(),
+        &Tetrimino::new(BlockKind::I)
+            .rotate(2)
+            .move_right(6)
+            .move_down(10)
+    );
+}
+
+#[test]
+fn search_kicks_by_radius_and_order() {
+    let search = KickSearch {
+        radius: 1,
+        prefer_down: true,
+        prefer_right: true,
+    };
+    assert_eq!(
+        search.kicks(BlockKind::T, 0, 1),
+        vec![
+            (0, 0),
+            (0, 1),
+            (1, 0),
+            (-1, 0),
+            (0, -1),
+            (1, 1),
+            (-1, 1),
+            (1, -1),
+            (-1, -1)
+        ]
+    );
+    assert_eq!(
+        KickSearch {
+            radius: 0,
+            ..KickSearch::default()
+        }
+        .kicks(BlockKind::T, 0, 1),
+        vec![(0, 0)]
+    );
+    assert_eq!(
+        KickSearch::default().kicks(BlockKind::I, 0, 1),
+        NearSearch.kicks(BlockKind::I, 0, 1)
+    );
+}
+
+#[test]
+fn do_not_kick_with_classic_rotation() {
+    let original = Tetrimino::new(BlockKind::I)
+        .rotate(1)
+        .move_right(7)
+        .move_down(10);
+
+    let mut game = make_game();
+    game.set_rotation_system(Box::new(Classic));
+    game.tetrimino = original.clone();
+
+    assert_eq!(game.rotate(), RotationResult::default());
+    assert_eq!(game.tetrimino(), &original);
+}
+
 #[test]
 fn create_ghost() {
     let game = make_game();
@@ -251,13 +420,245 @@ fn ghost_may_jump_over_blocks() {
 }
 
 #[test]
-fn hard_drop_tetrimino() {
+fn choose_ghost_mode() {
+    let mut game = make_game();
+    game.field = "
+        |          |
+        |          |
+        |          |
+        |          |
+        |   OOO    |
+        |          |
+        |          |
+    "
+    .parse()
+    .unwrap();
+    let straight = Tetrimino::new(BlockKind::T).move_to((3, 2));
+    assert_ne!(game.ghost(), straight);
+
+    game.set_ghost_mode(GhostMode::StraightDown);
+    assert_eq!(game.ghost(), straight);
+    let mut disabled = game.fork();
+    disabled.set_ghost_mode(GhostMode::Disabled);
+    assert_eq!(disabled.ghost(), straight);
+    assert!(game.render(&Glyphs::default()).contains('_'));
+    assert!(!disabled.render(&Glyphs::default()).contains('_'));
+
+    game.hard_drop();
+    assert_eq!(game.tetrimino(), &straight);
+}
+
+#[test]
+fn no_ghost_for_overlapping_tetrimino() {
+    let mut game = make_game();
+    game.field = Field::from_vec(vec![vec![Cell::Garbage; 10]; 27]);
+    assert_eq!(game.try_ghost(), None);
+    assert_eq!(&game.ghost(), game.tetrimino());
+    assert_eq!(game.drop_distance(), 0);
+    assert_eq!(game.hard_drop(), 0);
+    game.render(&Glyphs::default());
+}
+
+#[test]
+fn check_tetrimino_from_game() {
+    let mut game = make_game();
+    assert!(!game.touches_left() && !game.touches_right() && !game.touches_down());
+    assert!(!game.overlaps());
+
+    game.move_left_to_wall();
+    game.hard_drop();
+    assert!(game.touches_left() && game.touches_down());
+    assert!(!game.touches_right());
+
+    let tetrimino = game.tetrimino().move_down(1);
+    assert!(Checker::new(game.field(), &tetrimino).overlap());
+}
+
+#[test]
+fn detect_immobile_tetrimino() {
+    // An S-tetrimino in the slot can't move anywhere.
+    let mut game = make_game();
+    let mut row_18 = vec![Cell::Garbage; 10];
+    row_18[0..2].fill(Cell::Empty);
+    let mut row_19 = vec![Cell::Garbage; 10];
+    row_19[1..3].fill(Cell::Empty);
+    game.field = Field::from_vec(
+        [
+            vec![vec![Cell::Empty; 10]; 24],
+            vec![vec![Cell::Garbage; 10], row_18, row_19],
+        ]
+        .concat(),
+    );
+
+    let slot = Tetrimino::new(BlockKind::S).move_to((0, 18));
+    assert!(!Checker(game.field(), &slot).overlap());
+    assert!(Checker(game.field(), &slot).immobile());
+    assert!(!game.check().immobile());
+}
+
+#[test]
+fn count_contact_cells() {
+    let game = make_game();
+    assert_eq!(game.check().contact_cells(), 0);
+
+    // Three faces on the floor and one on the left border
+    let corner = Tetrimino::new(BlockKind::T).move_to((0, 18));
+    assert_eq!(Checker::new(game.field(), &corner).contact_cells(), 4);
+}
+
+#[test]
+fn get_landing_state() {
     let mut game = make_game();
+    assert!(!game.is_grounded());
+    assert_eq!(game.drop_distance(), 20);
+    assert_eq!(game.landing_row(), 19);
+
+    game.soft_drop_by(15);
+    assert_eq!(game.drop_distance(), 5);
     game.hard_drop();
+    assert!(game.is_grounded());
+    assert_eq!(game.drop_distance(), 0);
+    assert_eq!(game.landing_row(), 19);
+}
+
+#[test]
+fn hard_drop_tetrimino() {
+    let mut game = make_game();
+    assert_eq!(game.hard_drop(), 20);
     assert_eq!(
         game.tetrimino(),
         &Tetrimino::new(BlockKind::T).move_to((3, 18)),
-    )
+    );
+    assert_eq!(game.hard_drop(), 0);
+}
+
+#[test]
+fn sonic_drop_tetrimino() {
+    let mut game = make_game();
+    let mut roof = vec![Cell::Empty; 10];
+    roof[3..8].fill(Cell::Garbage);
+    let mut wall = vec![Cell::Empty; 10];
+    wall[7] = Cell::Garbage;
+    game.field = Field::from_vec(
+        [
+            vec![vec![Cell::Empty; 10]; 24],
+            vec![roof, wall.clone(), wall],
+        ]
+        .concat(),
+    );
+
+    assert_eq!(game.sonic_drop(), 17);
+    assert_eq!(
+        game.tetrimino(),
+        &Tetrimino::new(BlockKind::T).move_to((3, 15))
+    );
+    // Hard drop would go under the roof.
+    assert_eq!(game.ghost(), Tetrimino::new(BlockKind::T).move_to((3, 18)));
+    assert_eq!(game.score().points(), 0);
+    assert_eq!(game.field().get_cell((4, 16)), Cell::Empty);
+}
+
+#[test]
+fn apply_inputs() {
+    let mut game = make_game();
+    assert!(game.apply(Input::Left).applied);
+    for _ in 0..3 {
+        game.apply(Input::Left);
+    }
+    assert_eq!(game.apply(Input::Left), ActionResult::default());
+
+    let result = game.apply(Input::SoftDrop);
+    assert_eq!((result.applied, result.dropped), (true, 1));
+    assert_eq!(game.apply(Input::HardDrop).dropped, 19);
+    let result = game.apply(Input::Lock);
+    assert_eq!(result.locked.map(|r| r.lines()), Some(0));
+    assert!(game.apply(Input::Hold).applied);
+    assert!(!game.apply(Input::Hold).applied);
+}
+
+#[test]
+fn repeat_moves_after_das() {
+    let ms = Duration::from_millis;
+    let gravity = Gravity::default();
+    let mut auto_repeat = AutoRepeat::new(Handling {
+        das: ms(100),
+        arr: ms(20),
+        sdf: 20.0,
+    });
+
+    auto_repeat.key_down(Input::Left, ms(0));
+    assert_eq!(auto_repeat.tick(ms(99), gravity), vec![Input::Left]);
+    assert_eq!(auto_repeat.tick(ms(100), gravity), vec![Input::Left]);
+    assert_eq!(auto_repeat.tick(ms(140), gravity), vec![Input::Left; 2]);
+
+    // The last pressed direction wins, and the other one charges again
+    // after it is released.
+    auto_repeat.key_down(Input::Right, ms(150));
+    auto_repeat.key_up(Input::Right, ms(200));
+    auto_repeat.key_down(Input::RotateCw, ms(210));
+    assert_eq!(
+        auto_repeat.tick(ms(300), gravity),
+        vec![Input::Right, Input::RotateCw, Input::Left]
+    );
+
+    auto_repeat.key_up(Input::Left, ms(310));
+    assert!(auto_repeat.tick(ms(1000), gravity).is_empty());
+}
+
+#[test]
+fn repeat_soft_drops_by_sdf() {
+    let ms = Duration::from_millis;
+    let mut auto_repeat = AutoRepeat::new(Handling {
+        das: ms(100),
+        arr: Duration::ZERO,
+        sdf: 20.0,
+    });
+
+    auto_repeat.key_down(Input::SoftDrop, ms(0));
+    auto_repeat.key_up(Input::SoftDrop, ms(120));
+    assert_eq!(
+        auto_repeat.tick(ms(200), Gravity::default()),
+        vec![Input::SoftDrop; 3]
+    );
+
+    auto_repeat.key_down(Input::Right, ms(200));
+    assert_eq!(
+        auto_repeat.tick(ms(300), Gravity::default()),
+        vec![Input::Right, Input::RightToWall]
+    );
+    // Holding the key moves every new tetrimino to the wall once a tick.
+    auto_repeat.key_down(Input::RotateCw, ms(310));
+    assert_eq!(
+        auto_repeat.tick(ms(320), Gravity::default()),
+        vec![Input::RightToWall, Input::RotateCw, Input::RightToWall]
+    );
+    assert_eq!(
+        auto_repeat.tick(ms(330), Gravity::default()),
+        vec![Input::RightToWall]
+    );
+
+    let mut game = make_game();
+    game.apply(Input::Right);
+    assert!(game.apply(Input::RightToWall).applied);
+    assert!(!game.apply(Input::RightToWall).applied);
+    assert_eq!(
+        finesse::count_presses(&[
+            (Input::Right, true),
+            (Input::RightToWall, true),
+            (Input::RightToWall, false),
+        ]),
+        1
+    );
+    assert_eq!(finesse::count_presses(&[(Input::LeftToWall, true)]), 1);
+}
+
+#[test]
+fn hard_drop_and_lock_tetrimino() {
+    let mut game = make_game();
+    let result = game.hard_drop_and_lock();
+    assert_eq!(result.lines(), 0);
+    assert_eq!(game.field().get_cell((4, 19)), Cell::Block(BlockKind::T));
+    assert_eq!(game.tetrimino().kind(), BlockKind::L);
 }
 
 #[test]
@@ -344,8 +745,9 @@ fn remove_filled_lines_when_saving() {
             ],
         ]
     );
-    // return how many lines are removed
-    assert_eq!(result, 1);
+    // return which lines are removed
+    assert_eq!(result.cleared_rows, vec![19]);
+    assert_eq!(result.clear_kind, ClearKind::Single);
 }
 
 #[test]
@@ -355,12 +757,100 @@ fn end_when_saved_tetrimino_is_out_of_visible_area() {
 
     game.save();
     assert!(game.is_end());
+    assert_eq!(game.end_reason(), Some(EndReason::LockOut));
+}
+
+// Make a field where the middle columns are filled up to the top.
+fn block_middle_columns(game: &mut Game) {
+    let mut row = vec![Cell::Empty; 10];
+    row[3..7].fill(Cell::Garbage);
+    game.field = Field::from_vec(vec![row; 27]);
+}
+
+#[test]
+fn end_when_spawn_is_blocked() {
+    let mut game = make_game();
+    block_middle_columns(&mut game);
+    game.tetrimino = Tetrimino::new(BlockKind::I)
+        .rotate(1)
+        .move_left(2)
+        .move_down(16);
+
+    let result = game.save();
+    assert!(!result.topped_out);
+    assert_eq!(game.end_reason(), Some(EndReason::BlockOut));
+    assert_eq!(game.tetrimino().kind(), BlockKind::L);
+}
+
+#[test]
+fn end_when_held_tetrimino_is_blocked() {
+    let mut game = make_game();
+    block_middle_columns(&mut game);
+    assert!(game.hold());
+    assert_eq!(game.end_reason(), Some(EndReason::BlockOut));
+}
+
+#[test]
+fn spawn_by_rule() {
+    let rule = SpawnRule {
+        rotations: [0, 1, 0, 0, 0, 2, 0],
+        offsets: [0, 0, 0, 0, 0, 1, 0],
+        bottom_row: 0,
+        max_lift: 0,
+    };
+    let mut game = Game::builder()
+        .spawn_rule(rule)
+        .build(make_selector())
+        .unwrap();
+    assert_eq!(
+        game.tetrimino(),
+        &Tetrimino::new(BlockKind::T).rotate(2).move_to((4, -1))
+    );
+
+    game.hard_drop_and_lock();
+    game.hard_drop_and_lock();
+    assert_eq!(
+        game.tetrimino(),
+        &Tetrimino::new(BlockKind::I).rotate(1).move_to((4, -3))
+    );
+
+    // The Guideline rule moves the tetrimino up only one row.
+    game.set_spawn_rule(SpawnRule::guideline());
+    let mut row = vec![Cell::Garbage; 10];
+    row[9] = Cell::Empty;
+    game.field = Field::from_vec([vec![row; 7], vec![vec![Cell::Empty; 10]; 20]].concat());
+    game.tetrimino = Tetrimino::new(BlockKind::I).move_to((0, 19));
+    game.save();
+    assert_eq!(game.end_reason(), Some(EndReason::BlockOut));
+}
+
+#[test]
+fn apply_initial_rotation_and_hold() {
+    let mut game = make_game();
+    game.set_initial_actions(InitialActions {
+        rotation: 3,
+        hold: false,
+    });
+    game.hard_drop_and_lock();
+    assert_eq!(game.tetrimino().kind(), BlockKind::L);
+    assert_eq!(game.tetrimino().rotation(), 3);
+    assert_eq!(game.initial_actions(), InitialActions::default());
+
+    game.set_initial_actions(InitialActions {
+        rotation: 1,
+        hold: true,
+    });
+    game.hard_drop_and_lock();
+    assert_eq!(game.held().map(|t| t.kind()), Some(BlockKind::I));
+    assert_eq!(game.tetrimino().kind(), BlockKind::J);
+    assert_eq!(game.tetrimino().rotation(), 1);
+    assert!(!game.can_hold());
 }
 
 #[test]
 fn stop_updating_after_end() {
     let mut game = make_game();
-    game.is_end = true;
+    game.end_reason = Some(EndReason::LockOut);
 
     let expected = Tetrimino::new(BlockKind::T).move_to((3, -2));
     game.move_left();
@@ -408,8 +898,10 @@ fn reset_rotation_when_holding() {
 #[test]
 fn do_not_hold_twice_without_saving() {
     let mut game = make_game();
-    game.hold();
-    game.hold(); // actually doesn't hold
+    assert!(game.can_hold());
+    assert!(game.hold());
+    assert!(!game.can_hold());
+    assert!(!game.hold()); // actually doesn't hold
     assert_eq!(
         game.held().unwrap(),
         Tetrimino::new(BlockKind::T).move_to((0, 0))
@@ -426,7 +918,8 @@ fn can_hold_again_after_save() {
     game.hold(); // held: T, current: L
     game.hard_drop();
     game.save(); // held: T, current: I
-    game.hold(); // held: I, current: T
+    assert!(game.can_hold());
+    assert!(game.hold()); // held: I, current: T
     assert_eq!(
         game.held().unwrap(),
         Tetrimino::new(BlockKind::I).move_to((0, 0))
@@ -491,7 +984,2371 @@ fn implement_debug() {
     );
     assert_eq!(format!("{:?}", Cell::Block(BlockKind::T)), "Block(T)");
     assert_eq!(
-        format!("{:?}", Field::new(10, 20)),
+        format!("{:?}", Field::new(10, 20, 7)),
         ["|__________|\n"; 20].join("")
     );
 }
+
+#[test]
+fn accept_closures_as_selectors() {
+    let mut game = Game::new(10, 20, 1, || BlockKind::O);
+    assert_eq!(game.tetrimino().kind(), BlockKind::O);
+    game.hard_drop();
+    game.save();
+    assert_eq!(game.tetrimino().kind(), BlockKind::O);
+}
+
+#[test]
+fn deal_every_kind_once_from_seven_bag() {
+    let mut bag = Bag::seven(1);
+    for _ in 0..3 {
+        let kinds: HashSet<_> = (0..7).map(|_| bag.select()).collect();
+        assert_eq!(kinds.len(), 7);
+    }
+}
+
+#[test]
+fn deal_every_kind_twice_from_fourteen_bag() {
+    let mut bag = Bag::fourteen(1);
+    let mut kinds: Vec<_> = (0..14).map(|_| bag.select()).collect();
+    kinds.sort_by_key(|k| *k as usize);
+    let expected: Vec<_> = BlockKind::all_as_array()
+        .iter()
+        .flat_map(|k| [*k, *k])
+        .collect();
+    assert_eq!(kinds, expected);
+}
+
+#[test]
+fn selectors_are_deterministic_for_seed() {
+    let sequence = |mut selector: Box<dyn PieceSelector>| -> Vec<_> {
+        (0..20).map(|_| selector.select()).collect()
+    };
+    assert_eq!(
+        sequence(Box::new(Random::new(42))),
+        sequence(Box::new(Random::new(42)))
+    );
+    assert_eq!(
+        sequence(Box::new(History::tgm(42))),
+        sequence(Box::new(History::tgm(42)))
+    );
+    assert_ne!(
+        sequence(Box::new(Bag::seven(1))),
+        sequence(Box::new(Bag::seven(2)))
+    );
+}
+
+#[test]
+fn history_randomizer_never_starts_with_s_z_o() {
+    for seed in 0..50 {
+        let first = History::tgm(seed).select();
+        assert!(![BlockKind::S, BlockKind::Z, BlockKind::O].contains(&first));
+    }
+}
+
+#[test]
+fn restore_game_from_state() {
+    let mut game = make_game();
+    game.hard_drop();
+    game.save();
+    game.hold();
+
+    let restored = Game::from_state(game.to_state(), make_selector()).unwrap();
+    assert_eq!(restored.tetrimino(), game.tetrimino());
+    assert_eq!(restored.queue(), game.queue());
+    assert_eq!(restored.held(), game.held());
+    assert_eq!(restored.field().as_vec(), game.field().as_vec());
+    assert!(!restored.can_hold);
+
+    let mut state = game.to_state();
+    state.tetrimino = Tetrimino::new(BlockKind::I).move_to((8, 0));
+    assert_eq!(
+        Game::from_state(state.clone(), make_selector()).err(),
+        Some(StateError::TetriminoOutside)
+    );
+    state.tetrimino = Tetrimino::new(BlockKind::I).move_to((3, 18));
+    assert_eq!(
+        Game::from_state(state.clone(), make_selector()).err(),
+        Some(StateError::TetriminoOverlaps)
+    );
+    state.end_reason = Some(EndReason::BlockOut);
+    assert!(Game::from_state(state.clone(), make_selector()).is_ok());
+    state.held[0] = Tetrimino::new(BlockKind::O).move_to((0, 40));
+    assert_eq!(
+        Game::from_state(state.clone(), make_selector()).err(),
+        Some(StateError::HeldOutside(0))
+    );
+    state.held[0] = Tetrimino::new(BlockKind::O).move_to((0, 0));
+    let bytes = state.encode().unwrap();
+    assert!(GameState::decode(&bytes).is_some());
+    state.tetrimino = state.tetrimino.move_down(5);
+    assert!(GameState::decode(&state.encode().unwrap()).is_none());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serialize_game_state() {
+    let mut game = make_game();
+    game.hard_drop();
+    game.save();
+
+    let json = serde_json::to_string(&game.to_state()).unwrap();
+    let state: crate::GameState = serde_json::from_str(&json).unwrap();
+    assert_eq!(serde_json::to_string(&state).unwrap(), json);
+    assert_eq!(&state.tetrimino, game.tetrimino());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn reject_broken_serialized_field() {
+    let field: Field = serde_json::from_str(r#"{"state":[["Empty"]],"hidden_height":0}"#).unwrap();
+    assert_eq!(field.width(), 1);
+
+    let too_wide = format!(
+        r#"{{"state":[[{}]],"hidden_height":0}}"#,
+        vec![r#""Empty""#; MAX_WIDTH + 1].join(",")
+    );
+    for json in [
+        too_wide.as_str(),
+        r#"{"state":[],"hidden_height":0}"#,
+        r#"{"state":[["Empty"],[]],"hidden_height":0}"#,
+        r#"{"state":[["Empty"]],"hidden_height":2}"#,
+    ] {
+        assert!(serde_json::from_str::<Field>(json).is_err());
+    }
+}
+
+#[test]
+fn fall_by_gravity() {
+    let mut game = make_game();
+    game.set_gravity(Gravity::cells_per_second(2.0));
+
+    assert_eq!(game.update(Duration::from_millis(250)).dropped, 0);
+    assert_eq!(game.update(Duration::from_millis(250)).dropped, 1);
+    assert_eq!(game.update(Duration::from_secs(1)).dropped, 2);
+    assert_eq!(
+        game.tetrimino(),
+        &Tetrimino::new(BlockKind::T).move_to((3, -2 + 3))
+    );
+}
+
+#[test]
+fn reach_bottom_instantly_with_20g() {
+    let mut game = make_game();
+    game.set_gravity(Gravity::from_g(100.0));
+    assert_eq!(game.gravity(), Gravity::MAX);
+
+    game.update(Duration::from_secs_f64(1.0 / 60.0));
+    assert_eq!(game.tetrimino(), &game.ghost());
+}
+
+#[test]
+fn lock_after_lock_delay() {
+    let mut game = make_game();
+    game.hard_drop();
+    assert_eq!(game.lock_timer(), None);
+
+    assert_eq!(game.update(Duration::from_millis(100)).locked, None);
+    assert_eq!(game.lock_timer(), Some(Duration::from_millis(500)));
+    assert_eq!(game.update(Duration::from_millis(300)).locked, None);
+    assert_eq!(game.lock_timer(), Some(Duration::from_millis(200)));
+    assert_eq!(
+        game.update(Duration::from_millis(200))
+            .locked
+            .map(|r| r.lines()),
+        Some(0)
+    );
+    assert_eq!(game.lock_timer(), None);
+    assert_eq!(game.field().get_cell((4, 19)), Cell::Block(BlockKind::T));
+}
+
+#[test]
+fn reset_lock_delay_by_moving_up_to_limit() {
+    let mut game = make_game();
+    game.set_lock_delay(LockDelay {
+        delay: Duration::from_millis(500),
+        max_resets: 1,
+    });
+    game.hard_drop();
+    game.update(Duration::ZERO);
+    game.update(Duration::from_millis(400));
+
+    game.move_left();
+    assert_eq!(game.lock_timer(), Some(Duration::from_millis(500)));
+    game.update(Duration::from_millis(400));
+
+    // No more resets
+    game.move_left();
+    assert_eq!(game.lock_timer(), Some(Duration::from_millis(100)));
+    assert_eq!(
+        game.update(Duration::from_millis(100))
+            .locked
+            .map(|r| r.lines()),
+        Some(0)
+    );
+}
+
+#[test]
+fn score_with_back_to_back_and_combo() {
+    let mut score = Score::default();
+    assert_eq!(score.lock(4, None, false), 800);
+    assert_eq!(score.lock(4, None, false), 1200 + 50);
+    assert_eq!(score.combo(), Some(1));
+    assert!(score.back_to_back());
+    assert_eq!(score.lock(0, None, false), 0);
+    assert_eq!(score.combo(), None);
+
+    score.set_level(2);
+    assert_eq!(score.lock(1, None, true), (100 + 800) * 2);
+    assert!(!score.back_to_back());
+    assert_eq!(score.points(), 800 + 1250 + 1800);
+}
+
+#[test]
+fn score_with_nes_table() {
+    let mut score = Score::new(Box::new(Nes));
+    score.lock(4, None, false);
+    score.lock(4, None, false);
+    score.hard_drop(10);
+    assert_eq!(score.points(), 2400);
+}
+
+#[test]
+fn score_drops() {
+    let mut game = make_game();
+    game.soft_drop();
+    game.hard_drop();
+    assert_eq!(game.score().points(), 1 + 2 * 19);
+}
+
+#[test]
+fn score_t_spin_double() {
+    let mut row_18 = vec![Cell::Block(BlockKind::O); 10];
+    row_18[3..6].fill(Cell::Empty);
+    let mut row_19 = vec![Cell::Block(BlockKind::O); 10];
+    row_19[4] = Cell::Empty;
+    let mut row_17 = vec![Cell::Empty; 10];
+    row_17[3] = Cell::Block(BlockKind::O);
+
+    let mut game = make_game();
+    game.field = Field::from_vec(
+        [
+            vec![vec![Cell::Empty; 10]; 24],
+            vec![row_17, row_18, row_19],
+        ]
+        .concat(),
+    );
+    game.tetrimino = Tetrimino::new(BlockKind::T)
+        .rotate(2)
+        .move_right(3)
+        .move_down(17);
+    game.last_kick = Some((0, 0));
+
+    let result = game.save();
+    assert_eq!(result.cleared_rows, vec![18, 19]);
+    assert_eq!(result.spin, Some(SpinKind::Full));
+    assert_eq!(result.clear_kind, ClearKind::TSpinDouble);
+    assert_eq!(result.cleared_visible_rows(), [18, 19]);
+    assert!(!result.is_perfect_clear);
+    assert_eq!(game.score().points(), 1200);
+}
+
+#[test]
+fn report_spin_on_rotation() {
+    let mut row_18 = vec![Cell::Block(BlockKind::O); 10];
+    row_18[3..6].fill(Cell::Empty);
+    let mut row_19 = vec![Cell::Block(BlockKind::O); 10];
+    row_19[4] = Cell::Empty;
+    let mut row_17 = vec![Cell::Empty; 10];
+    row_17[3] = Cell::Block(BlockKind::O);
+
+    let mut game = make_game();
+    game.field = Field::from_vec(
+        [
+            vec![vec![Cell::Empty; 10]; 24],
+            vec![row_17, row_18, row_19],
+        ]
+        .concat(),
+    );
+    game.tetrimino = Tetrimino::new(BlockKind::T)
+        .rotate(1)
+        .move_right(3)
+        .move_down(17);
+
+    let result = game.rotate();
+    assert!(result.rotated);
+    assert_eq!(result.kick, None);
+    assert_eq!(result.spin, Some(SpinKind::Full));
+}
+
+#[test]
+fn do_not_score_t_spin_without_rotation() {
+    let mut row_18 = vec![Cell::Block(BlockKind::O); 10];
+    row_18[3..6].fill(Cell::Empty);
+    let mut row_19 = vec![Cell::Block(BlockKind::O); 10];
+    row_19[4] = Cell::Empty;
+    let mut row_17 = vec![Cell::Empty; 10];
+    row_17[3] = Cell::Block(BlockKind::O);
+
+    let mut game = make_game();
+    game.field = Field::from_vec(
+        [
+            vec![vec![Cell::Empty; 10]; 24],
+            vec![row_17, row_18, row_19],
+        ]
+        .concat(),
+    );
+    game.tetrimino = Tetrimino::new(BlockKind::T)
+        .rotate(2)
+        .move_right(3)
+        .move_down(17);
+
+    assert_eq!(game.save().lines(), 2);
+    assert_eq!(game.score().points(), 300);
+}
+
+#[test]
+fn report_perfect_clear_and_top_out() {
+    let mut row = vec![Cell::Block(BlockKind::O); 10];
+    row[3..7].fill(Cell::Empty);
+    let mut game = make_game();
+    game.field = Field::from_vec([vec![vec![Cell::Empty; 10]; 26], vec![row]].concat());
+    game.tetrimino = Tetrimino::new(BlockKind::I).move_right(3).move_down(18);
+
+    let result = game.save();
+    assert!(result.is_perfect_clear);
+    assert!(!result.topped_out);
+
+    game.tetrimino = Tetrimino::new(BlockKind::T).move_to((3, -3));
+    assert!(game.save().topped_out);
+}
+
+#[test]
+fn add_garbage_lines() {
+    let mut game = make_game();
+    game.hard_drop();
+    game.save();
+    game.add_garbage(2, 1);
+
+    let mut garbage = [Cell::Garbage; 10];
+    garbage[1] = Cell::Empty;
+    assert_eq!(game.field().as_vec()[18..], [garbage, garbage]);
+    assert_eq!(game.field().get_cell((4, 17)), Cell::Block(BlockKind::T));
+}
+
+#[test]
+fn raise_floor_by_timer() {
+    let mut game = make_game();
+    game.set_gravity(Gravity::cells_per_second(0.0));
+    game.set_rising_floor(Some(RisingFloor {
+        interval: Duration::from_secs(1),
+        seed: 0,
+    }));
+
+    assert_eq!(game.update(Duration::from_millis(2500)).raised, 2);
+    for y in [18, 19] {
+        assert_eq!(game.field().row_mask(y).count_ones(), 9);
+    }
+    assert_eq!(game.update(Duration::from_millis(400)).raised, 0);
+    assert_eq!(game.update(Duration::from_millis(100)).raised, 1);
+
+    while !game.is_end() {
+        game.raise_floor();
+    }
+    assert_eq!(game.end_reason(), Some(EndReason::GarbageOut));
+    assert_eq!(game.update(Duration::from_secs(5)), Default::default());
+}
+
+#[test]
+fn push_up_tetrimino_overlapping_garbage() {
+    let mut game = make_game();
+    game.hard_drop();
+    game.add_garbage_rows(vec![vec![true; 10]]);
+    assert_eq!(
+        game.tetrimino(),
+        &Tetrimino::new(BlockKind::T).move_to((3, 17))
+    );
+}
+
+#[test]
+fn end_when_garbage_pushes_blocks_out() {
+    let mut game = make_game();
+    game.field = Field::from_vec(
+        [
+            vec![vec![Cell::Block(BlockKind::O); 10]],
+            vec![vec![Cell::Empty; 10]; 26],
+        ]
+        .concat(),
+    );
+    game.add_garbage(1, 0);
+    assert_eq!(game.end_reason(), Some(EndReason::GarbageOut));
+}
+
+#[test]
+fn track_combo_and_back_to_back() {
+    let mut game = make_game();
+    prepare_tetris(&mut game);
+    let result = game.save();
+    assert_eq!(result.combo, Some(0));
+    assert!(!result.back_to_back);
+    assert!(game.back_to_back());
+
+    prepare_tetris(&mut game);
+    let result = game.save();
+    assert_eq!(result.combo, Some(1));
+    assert!(result.back_to_back);
+    assert_eq!(game.combo(), Some(1));
+
+    game.hard_drop();
+    let result = game.save();
+    assert_eq!(result.combo, None);
+    assert!(!result.back_to_back);
+    assert_eq!(game.combo(), None);
+    assert!(game.back_to_back());
+}
+
+#[test]
+fn calculate_attack() {
+    let lock = |lines: usize, spin, combo: Option<usize>, back_to_back| LockResult {
+        cleared_rows: (0..lines as isize).collect(),
+        clear_kind: ClearKind::new(lines, spin),
+        is_perfect_clear: false,
+        topped_out: false,
+        spin,
+        combo,
+        back_to_back,
+        chain: 0,
+    };
+
+    let table = AttackTable::default();
+    assert_eq!(table.lines(&lock(4, None, Some(0), false)), 4);
+    assert_eq!(
+        table.lines(&lock(2, Some(SpinKind::Full), Some(1), true)),
+        4 + 1
+    );
+    // Combo bonus only
+    assert_eq!(table.lines(&lock(1, None, Some(2), false)), 1);
+    assert_eq!(table.lines(&lock(4, None, Some(3), false)), 4 + 1);
+    assert_eq!(table.lines(&lock(0, None, None, false)), 0);
+    assert_eq!(table.lines(&lock(2, None, Some(0), false)), 1);
+}
+
+// Make a field where the current I-tetrimino clears four lines.
+fn prepare_tetris(game: &mut Game) {
+    let mut row = vec![Cell::Block(BlockKind::O); 10];
+    row[9] = Cell::Empty;
+    game.field = Field::from_vec(
+        [
+            vec![vec![Cell::Empty; 10]; 22],
+            vec![[vec![Cell::Block(BlockKind::O)], vec![Cell::Empty; 9]].concat()],
+            vec![row; 4],
+        ]
+        .concat(),
+    );
+    game.tetrimino = Tetrimino::new(BlockKind::I)
+        .rotate(1)
+        .move_right(7)
+        .move_down(16);
+}
+
+#[test]
+fn send_garbage_in_battle() {
+    let mut first = make_game();
+    prepare_tetris(&mut first);
+    let mut battle = Battle::new(first, make_game(), BattleRules::default());
+
+    assert_eq!(battle.save(Player::First).lines(), 4);
+    assert_eq!(battle.pending_garbage(Player::Second), 4);
+
+    battle.game_mut(Player::Second).hard_drop();
+    battle.save(Player::Second);
+    assert_eq!(battle.pending_garbage(Player::Second), 0);
+    let field = battle.game(Player::Second).field();
+    for row in &field.as_vec()[16..] {
+        assert_eq!(row.iter().filter(|c| **c == Cell::Garbage).count(), 9);
+    }
+    assert_eq!(battle.winner(), None);
+}
+
+#[test]
+fn ignore_saves_during_delays_in_battle() {
+    let mut battle = Battle::new(
+        make_game(),
+        make_game(),
+        BattleRules {
+            garbage_delay: 2,
+            ..BattleRules::default()
+        },
+    );
+    battle.pending[0].push_back(battle::PendingGarbage { lines: 4, delay: 2 });
+    battle
+        .game_mut(Player::First)
+        .set_are(Duration::from_secs(1));
+
+    battle.game_mut(Player::First).hard_drop();
+    battle.save(Player::First);
+    assert_eq!(battle.save(Player::First), LockResult::default());
+    assert_eq!(battle.pending_garbage(Player::First), 4);
+    assert_eq!(battle.game(Player::First).stats().total_pieces(), 1);
+}
+
+#[test]
+fn cancel_garbage_by_attack() {
+    let mut battle = Battle::new(make_game(), make_game(), BattleRules::default());
+    battle.pending[0].push_back(battle::PendingGarbage { lines: 3, delay: 1 });
+
+    prepare_tetris(battle.game_mut(Player::First));
+    battle.save(Player::First);
+
+    assert_eq!(battle.pending_garbage(Player::First), 0);
+    assert_eq!(battle.pending_garbage(Player::Second), 1);
+}
+
+#[test]
+fn record_events() {
+    let mut game = make_game();
+    game.move_left();
+    assert!(game.take_events().is_empty());
+
+    game.set_events_enabled(true);
+    game.move_right();
+    game.rotate();
+    game.hold();
+    game.hard_drop();
+    game.save();
+    assert_eq!(
+        game.take_events(),
+        vec![
+            GameEvent::PieceMoved { dx: 1, dy: 0 },
+            GameEvent::PieceRotated { kick: (0, 0) },
+            GameEvent::Held(BlockKind::T),
+            GameEvent::PieceSpawned(BlockKind::L),
+            GameEvent::HardDropped { distance: 20 },
+            GameEvent::Locked(LockResult {
+                cleared_rows: vec![],
+                clear_kind: ClearKind::None,
+                is_perfect_clear: false,
+                topped_out: false,
+                spin: None,
+                combo: None,
+                back_to_back: false,
+                chain: 0,
+            }),
+            GameEvent::PieceSpawned(BlockKind::I),
+        ]
+    );
+    assert!(game.take_events().is_empty());
+}
+
+#[test]
+fn record_game_over_event() {
+    let mut game = make_game();
+    game.set_events_enabled(true);
+    game.field = Field::from_vec(vec![vec![Cell::Empty; 10]; 7]);
+    game.save();
+    let events = game.take_events();
+    assert_eq!(
+        events.last(),
+        Some(&GameEvent::GameOver {
+            reason: EndReason::LockOut
+        })
+    );
+    // The next tetrimino doesn't appear after locking out.
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, GameEvent::PieceSpawned(_))));
+}
+
+#[test]
+fn play_back_replay() {
+    let mut recorder = Recorder::new(10, 20, 3, 7);
+    for input in [Input::Left, Input::RotateCw, Input::HardDrop, Input::Lock] {
+        recorder.apply(input);
+    }
+    recorder.update(Duration::from_millis(1500));
+    for input in [Input::Hold, Input::Right, Input::Right, Input::HardDrop] {
+        recorder.apply(input);
+    }
+    recorder.update(Duration::from_millis(600));
+
+    let replay = Replay::decode(&recorder.replay().encode().unwrap()).unwrap();
+    assert_eq!(&replay, recorder.replay());
+    let game = replay.play_back();
+    assert_eq!(game.field().as_vec(), recorder.game().field().as_vec());
+    assert_eq!(game.tetrimino(), recorder.game().tetrimino());
+    assert_eq!(game.queue(), recorder.game().queue());
+    assert_eq!(game.held(), recorder.game().held());
+}
+
+#[test]
+fn reject_broken_replay() {
+    let mut bytes = Replay::new(10, 20, 3, 7).encode().unwrap();
+    assert!(Replay::decode(&bytes[..5]).is_none());
+    bytes.push(0xff);
+    assert!(Replay::decode(&bytes).is_none());
+    assert!(Replay::new(10, 20, 70_000, 7).encode().is_none());
+    for (width, height) in [(3, 20), (MAX_WIDTH + 1, 20), (10, 0)] {
+        let bytes = Replay::new(width, height, 3, 7).encode().unwrap();
+        assert!(Replay::decode(&bytes).is_none());
+    }
+}
+
+#[test]
+fn play_back_replay_with_builder() {
+    let builder = || {
+        Game::builder()
+            .size(6, 12)
+            .hold_enabled(false)
+            .mirrored(true)
+    };
+    let mut recorder = Recorder::with_builder(builder(), 7).unwrap();
+    for input in [Input::Left, Input::Hold, Input::HardDrop, Input::Lock] {
+        recorder.apply(input);
+    }
+    recorder.update(Duration::from_millis(1500));
+
+    let replay = Replay::decode(&recorder.replay().encode().unwrap()).unwrap();
+    assert_eq!((replay.width, replay.height), (6, 12));
+    let game = replay.play_back_with(builder()).unwrap();
+    assert_eq!(game.to_state().diff(&recorder.game().to_state()), None);
+}
+
+#[test]
+fn replay_exact_frames() {
+    let mut recorder = Recorder::new(10, 20, 300, 7);
+    for i in 0..600 {
+        recorder.update(Duration::from_nanos(16_666_667));
+        if i % 45 == 0 {
+            recorder.apply(Input::Left);
+        }
+    }
+    recorder.update(Duration::from_secs(5_000));
+
+    let replay = Replay::decode(&recorder.replay().encode().unwrap()).unwrap();
+    assert_eq!(&replay, recorder.replay());
+    let game = replay.play_back();
+    assert_eq!(game.to_state().diff(&recorder.game().to_state()), None);
+    assert_eq!(game.stats().elapsed, recorder.game().stats().elapsed);
+}
+
+#[test]
+fn undo_and_redo_lock() {
+    let mut game = make_game();
+    assert!(!game.undo_last_lock());
+
+    game.set_undo_limit(2);
+    game.hard_drop();
+    let before = game.to_state();
+    game.save();
+    let after = game.to_state();
+
+    assert!(game.undo_last_lock());
+    assert_eq!(game.field().as_vec(), before.field.as_vec());
+    assert_eq!(game.tetrimino(), &before.tetrimino);
+    assert_eq!(game.queue(), &before.queue);
+    assert_eq!(game.score().points(), 40);
+
+    assert!(game.redo_lock());
+    assert_eq!(game.field().as_vec(), after.field.as_vec());
+    assert_eq!(game.queue(), &after.queue);
+    assert!(!game.redo_lock());
+}
+
+#[test]
+fn deal_same_tetriminos_after_restore() {
+    fn drop_ten(game: &mut Game<Bag>) -> Vec<BlockKind> {
+        (0..10)
+            .map(|_| {
+                game.hard_drop();
+                game.save();
+                game.tetrimino().kind()
+            })
+            .collect()
+    }
+
+    let mut game = Game::new(10, 20, 1, Bag::seven(3));
+    let snapshot = game.snapshot();
+    let first = drop_ten(&mut game);
+    game.restore(&snapshot);
+    assert_eq!(drop_ten(&mut game), first);
+}
+
+#[test]
+fn build_game_with_options() {
+    let mut game = Game::builder()
+        .size(12, 30)
+        .queue_size(5)
+        .hold_enabled(false)
+        .rotation_system(Classic)
+        .scoring_table(Nes)
+        .gravity(Gravity::MAX)
+        .build(make_selector())
+        .unwrap();
+
+    assert_eq!(game.field().width(), 12);
+    assert_eq!(game.field().height(), 30);
+    assert_eq!(game.queue().len(), 5);
+    assert_eq!(game.gravity(), Gravity::MAX);
+
+    game.hold();
+    assert!(game.held().is_none());
+    game.hard_drop();
+    game.save();
+    assert_eq!(game.score().points(), 0);
+}
+
+#[test]
+fn configure_hidden_height() {
+    let game = Game::builder()
+        .hidden_height(20)
+        .build(make_selector())
+        .unwrap();
+    assert_eq!(game.field().hidden_height(), 20);
+    assert_eq!(game.field().get_cell((0, -20)), Cell::Empty);
+    assert_eq!(game.field().get_cell((0, -21)), Cell::Outside);
+
+    let mut game = Game::builder()
+        .hidden_height(0)
+        .build(make_selector())
+        .unwrap();
+    assert_eq!(
+        game.tetrimino(),
+        &Tetrimino::new(BlockKind::T).move_to((3, 0))
+    );
+    game.hard_drop();
+    game.save();
+    assert!(!game.is_end());
+}
+
+#[test]
+fn remove_lines_in_narrow_field() {
+    let mut game = Game::new(4, 6, 3, make_selector());
+    game.tetrimino = Tetrimino::new(BlockKind::I);
+    game.hard_drop();
+    assert_eq!(game.save().lines(), 1);
+    assert_eq!(game.field().as_vec(), vec![vec![Cell::Empty; 4]; 6]);
+    assert_eq!(game.field().width(), 4);
+}
+
+#[test]
+fn remove_lines_in_wide_field() {
+    let mut game = Game::new(16, 20, 3, make_selector());
+    for x in 0..4 {
+        game.tetrimino = Tetrimino::new(BlockKind::I).move_right(x * 4);
+        game.hard_drop();
+        game.save();
+    }
+    assert_eq!(game.removed_lines(), 1);
+    assert_eq!(game.field().as_vec(), vec![vec![Cell::Empty; 16]; 20]);
+    assert_eq!(
+        game.tetrimino(),
+        &Tetrimino::new(BlockKind::J).move_to((6, -2))
+    );
+}
+
+#[test]
+fn do_not_remove_lines_with_solid_blocks() {
+    let mut row = vec![Cell::Garbage; 10];
+    row[0] = Cell::Solid;
+    row[4] = Cell::Empty;
+    let mut game = make_game();
+    game.field = Field::from_vec([vec![vec![Cell::Empty; 10]; 26], vec![row]].concat());
+    game.tetrimino = Tetrimino::new(BlockKind::I).rotate(1).move_right(2);
+    game.hard_drop();
+
+    let result = game.save();
+    assert_eq!(result.lines(), 0);
+    assert_eq!(game.field().get_cell((0, 19)), Cell::Solid);
+    assert_eq!(
+        format!("{:?}", game.field()).lines().last(),
+        Some("|=###I#####|")
+    );
+}
+
+#[test]
+fn parse_field() {
+    let field: Field = "
+        |    |
+        |T.x_|
+        |=#IO|
+    "
+    .parse()
+    .unwrap();
+    assert_eq!(field.width(), 4);
+    assert_eq!(field.height(), 3);
+    assert_eq!(field.hidden_height(), 7);
+    assert_eq!(field.get_cell((0, 0)), Cell::Empty);
+    assert_eq!(field.get_cell((0, 1)), Cell::Block(BlockKind::T));
+    assert_eq!(field.get_cell((2, 1)), Cell::Garbage);
+    assert_eq!(field.get_cell((0, 2)), Cell::Solid);
+    assert_eq!(field.get_cell((3, 2)), Cell::Block(BlockKind::O));
+
+    let reparsed: Field = format!("{:?}", field).parse().unwrap();
+    assert_eq!(reparsed.as_vec(), field.as_vec());
+}
+
+#[test]
+fn fail_to_parse_broken_field() {
+    assert_eq!("".parse::<Field>().err(), Some(ParseFieldError::Empty));
+    assert_eq!(
+        "|  |\n  |".parse::<Field>().err(),
+        Some(ParseFieldError::MissingBorder { line: 1 })
+    );
+    assert_eq!(
+        "|  |\n| |".parse::<Field>().err(),
+        Some(ParseFieldError::UnevenRows { line: 1 })
+    );
+    assert_eq!(
+        "|a |".parse::<Field>().err(),
+        Some(ParseFieldError::UnknownChar { line: 0, char: 'a' })
+    );
+}
+
+#[test]
+fn render_game_with_ghost() {
+    let mut game = Game::new(5, 5, 1, || BlockKind::T);
+    game.field = "
+        |     |
+        |     |
+        |     |
+        |     |
+        |=#  Z|
+    "
+    .parse()
+    .unwrap();
+    game.soft_drop();
+    game.soft_drop();
+    assert_eq!(
+        game.to_string(),
+        "|..T..|\n|.TTT.|\n|.._..|\n|.___.|\n|=#..Z|\n"
+    );
+
+    let glyphs = Glyphs {
+        empty: ' ',
+        ghost: '*',
+        ..Glyphs::default()
+    };
+    assert_eq!(
+        game.render(&glyphs),
+        "|  T  |\n| TTT |\n|  *  |\n| *** |\n|=#  Z|\n"
+    );
+}
+
+#[test]
+fn iterate_over_field_including_hidden_area() {
+    let mut field = Field::new(3, 2, 2);
+    field.set((0, -2), BlockKind::T);
+    field.set((2, 1), BlockKind::I);
+
+    let rows: Vec<_> = field.rows().map(|(y, row)| (y, row.len())).collect();
+    assert_eq!(rows, vec![(-2, 3), (-1, 3), (0, 3), (1, 3)]);
+
+    let cells: Vec<_> = field.iter_cells().collect();
+    assert_eq!(cells.len(), 12);
+    assert_eq!(cells[0], ((0, -2), Cell::Block(BlockKind::T)));
+    assert_eq!(cells[11], ((2, 1), Cell::Block(BlockKind::I)));
+
+    let column: Vec<_> = field.column(2).collect();
+    assert_eq!(
+        column,
+        vec![
+            (-2, Cell::Empty),
+            (-1, Cell::Empty),
+            (0, Cell::Empty),
+            (1, Cell::Block(BlockKind::I))
+        ]
+    );
+}
+
+#[test]
+fn get_hidden_and_visible_areas() {
+    let mut field = Field::new(3, 2, 2);
+    field.set((1, -1), BlockKind::T);
+    assert_eq!(field.as_vec_full().len(), 4);
+    assert_eq!(field.visible(), field.as_vec());
+    assert_eq!(field.visible().len(), 2);
+    assert_eq!(field.hidden().len(), 2);
+    assert_eq!(field.hidden()[1][1], Cell::Block(BlockKind::T));
+    assert_eq!(field.as_vec_full()[1][1], Cell::Block(BlockKind::T));
+}
+
+#[test]
+fn analyze_field_surface() {
+    let field: Field = "
+        |     |
+        |T    |
+        |T  I |
+        |. LI |
+        |TTLI |
+    "
+    .parse()
+    .unwrap();
+    assert_eq!(field.column_heights(), vec![4, 1, 2, 3, 0]);
+    assert_eq!(field.aggregate_height(), 10);
+    assert_eq!(field.holes(), 1);
+    assert_eq!(field.bumpiness(), 3 + 1 + 1 + 3);
+    assert_eq!(field.wells(), vec![0, 1, 0, 0, 3]);
+}
+
+#[test]
+fn list_legal_placements() {
+    let game = Game::new(4, 4, 1, || BlockKind::O);
+    let placements = game.legal_placements(false);
+    assert_eq!(placements.len(), 3);
+    assert!(placements
+        .iter()
+        .all(|t| t.blocks().iter().all(|(_, y)| *y >= 2)));
+
+    let mut game = make_game();
+    let without_hold = game.legal_placements(false);
+    assert!(without_hold.iter().all(|t| t.kind() == BlockKind::T));
+    assert!(without_hold.contains(&game.ghost()));
+    // The T-tetrimino has 8 + 9 + 8 + 9 places on the empty field.
+    assert_eq!(without_hold.len(), 34);
+
+    let with_hold = game.legal_placements(true);
+    assert!(with_hold.iter().any(|t| t.kind() == BlockKind::L));
+    assert_eq!(with_hold.len(), 34 + 34);
+
+    game.hold();
+    assert!(game
+        .legal_placements(true)
+        .iter()
+        .all(|t| t.kind() == BlockKind::L));
+}
+
+#[test]
+fn follow_route_path() {
+    let mut game = make_game();
+    game.field = Field::from_vec(
+        [
+            vec![vec![Cell::Empty; 10]; 24],
+            vec![
+                [vec![Cell::Empty; 3], vec![Cell::Garbage; 7]].concat(),
+                [vec![Cell::Empty; 9], vec![Cell::Garbage; 1]].concat(),
+                [vec![Cell::Empty; 9], vec![Cell::Garbage; 1]].concat(),
+            ],
+        ]
+        .concat(),
+    );
+    let goal = game.ghost();
+    let path = Checker(game.field(), &goal)
+        .route_path(game.tetrimino())
+        .unwrap();
+    assert!(path.contains(&Move::Left) && path.contains(&Move::Right));
+    for m in path {
+        match m {
+            Move::Left => game.move_left(),
+            Move::Right => game.move_right(),
+            Move::Down => game.soft_drop() > 0,
+            Move::RotateCw => game.rotate().rotated,
+            Move::Rotate180 => game.rotate_180().rotated,
+            Move::RotateCcw => game.rotate_ccw().rotated,
+        };
+    }
+    assert_eq!(*game.tetrimino(), goal);
+
+    let blocked = game.tetrimino().move_down(5);
+    assert_eq!(
+        Checker(game.field(), &blocked).route_path(game.tetrimino()),
+        None
+    );
+}
+
+#[test]
+fn search_route_with_limit() {
+    let game = make_game();
+    let goal = game.ghost();
+    let checker = Checker::new(game.field(), &goal);
+
+    let search = checker.search_route(game.tetrimino(), 1000);
+    assert_eq!(search.path.as_ref().map(|p| p.len()), Some(20));
+    assert!(!search.limited);
+    // The search goes straight down to the goal.
+    assert_eq!(search.visited, 20);
+
+    let search = checker.search_route(game.tetrimino(), 5);
+    assert_eq!(
+        search,
+        RouteSearch {
+            path: None,
+            visited: 5,
+            limited: true,
+        }
+    );
+}
+
+#[test]
+fn remove_separate_filled_lines() {
+    let mut field: Field = "
+        |OO  |
+        |IIII|
+        |J   |
+        |TTTT|
+        |==S=|
+    "
+    .parse()
+    .unwrap();
+    let expected: Field = "
+        |    |
+        |    |
+        |OO  |
+        |J   |
+        |==S=|
+    "
+    .parse()
+    .unwrap();
+
+    assert_eq!(field.remove_filled_lines(), vec![1, 3]);
+    assert_eq!(field.as_vec_full(), expected.as_vec_full());
+    assert_eq!(field.row_mask(2), 0b0011);
+    assert_eq!(field.row_mask(0), 0);
+}
+
+#[test]
+fn drop_floating_groups_in_cascade() {
+    let row = |s: &str| {
+        s.chars()
+            .map(|c| match c {
+                'O' => Cell::Block(BlockKind::O),
+                'x' => Cell::Garbage,
+                _ => Cell::Empty,
+            })
+            .collect::<Vec<_>>()
+    };
+    let mut game = make_game();
+    game.set_cascade(true);
+    game.field = Field::from_vec(
+        [
+            vec![vec![Cell::Empty; 10]; 23],
+            vec![
+                row("O         "),
+                row("OOOOOOOOO "),
+                row(" xxxxxxxx "),
+                row(" xxxxxxxx "),
+            ],
+        ]
+        .concat(),
+    );
+    game.tetrimino = Tetrimino::new(BlockKind::I)
+        .rotate(1)
+        .move_right(7)
+        .move_down(16);
+
+    let result = game.save();
+    assert_eq!(result.cleared_rows, vec![17]);
+    assert_eq!(result.chain, 1);
+    assert_eq!(result.combo, Some(1));
+    assert_eq!(game.removed_lines(), 2);
+    let field = game.field();
+    assert_eq!(field.row_mask(19), 0b11_1111_1110);
+    assert_eq!(field.row_mask(18), 0b10_0000_0000);
+    assert_eq!(field.row_mask(17), 0);
+    assert_eq!(field.connected_groups().len(), 1);
+}
+
+#[test]
+fn compute_features_of_placement() {
+    let field: Field = "
+        |    |
+        |    |
+        |    |
+        |LL  |
+    "
+    .parse()
+    .unwrap();
+    let mut game = Game::new(4, 4, 1, || BlockKind::O);
+    game.field = field.clone();
+
+    let filling = game
+        .legal_placements(false)
+        .into_iter()
+        .find(|t| t.blocks().contains(&(2, 3)))
+        .unwrap();
+    let features = Features::new(&field, &filling);
+    // Removes the bottom line with two blocks of the tetrimino.
+    assert_eq!(features.landing_height, 0.5);
+    assert_eq!(features.eroded_cells, 2.0);
+    assert_eq!(features.holes, 0.0);
+
+    let weights = Weights::default();
+    let best = game
+        .legal_placements(false)
+        .into_iter()
+        .max_by(|a, b| {
+            weights
+                .evaluate(&field, a)
+                .total_cmp(&weights.evaluate(&field, b))
+        })
+        .unwrap();
+    assert_eq!(best, filling);
+}
+
+#[cfg(feature = "bot")]
+#[test]
+fn play_with_greedy_bot() {
+    use crate::bot::GreedyBot;
+
+    let bot = GreedyBot {
+        use_hold: true,
+        ..GreedyBot::default()
+    };
+    let mut game = Game::new(10, 20, 3, Bag::seven(1));
+    for _ in 0..100 {
+        let placement = bot.best_move(&game).unwrap();
+        assert!(Checker(game.field(), &placement.tetrimino).touch_down());
+        bot.play(&mut game).unwrap();
+    }
+    assert!(!game.is_end());
+    assert!(game.removed_lines() >= 30);
+}
+
+#[cfg(feature = "bot")]
+#[test]
+fn solve_perfect_clear() {
+    use crate::bot::PcSolver;
+
+    let mut game = make_game();
+    let mut row = vec![Cell::Garbage; 10];
+    row[6..].fill(Cell::Empty);
+    game.field = Field::from_vec([vec![vec![Cell::Empty; 10]; 25], vec![row; 2]].concat());
+    game.set_queue(vec![BlockKind::O, BlockKind::O, BlockKind::Z]);
+
+    let without_hold = PcSolver {
+        use_hold: false,
+        ..PcSolver::default()
+    };
+    assert_eq!(without_hold.solve(&game), None);
+
+    let placements = PcSolver::default().solve(&game).unwrap();
+    assert_eq!(placements.len(), 2);
+    assert!(placements[0].hold);
+    assert!(!placements[0].play(&mut game).is_perfect_clear);
+    assert!(placements[1].play(&mut game).is_perfect_clear);
+}
+
+#[test]
+fn get_row_masks() {
+    let field: Field = "
+        |    |
+        |T  I|
+        |TTxI|
+    "
+    .parse()
+    .unwrap();
+    assert_eq!(field.row_mask(0), 0b0000);
+    assert_eq!(field.row_mask(1), 0b1001);
+    assert_eq!(field.row_mask(2), 0b1111);
+    assert_eq!(field.row_mask(-7), 0);
+    assert_eq!(field.row_mask(3), u64::MAX);
+    assert_eq!(field.row_mask(-8), u64::MAX);
+
+    let too_wide = format!("|{}|", " ".repeat(65));
+    assert_eq!(
+        too_wide.parse::<Field>().err(),
+        Some(ParseFieldError::TooWide)
+    );
+}
+
+#[test]
+#[should_panic]
+fn cannot_create_too_wide_field() {
+    Game::new(65, 20, 3, make_selector());
+}
+
+#[test]
+fn create_ghost_in_tall_field() {
+    let game = Game::new(10, 400, 1, || BlockKind::T);
+    assert_eq!(game.ghost().blocks()[0].1, 398);
+
+    let mut state = game.to_state();
+    state.field = "
+        |          |
+        |   #######|
+        |          |
+        |          |
+    "
+    .parse()
+    .unwrap();
+    state.tetrimino = Tetrimino::new(BlockKind::T).move_to((4, -2));
+    let game = Game::from_state(state, || BlockKind::T).unwrap();
+    assert_eq!(game.ghost().blocks()[0].1, 2);
+}
+
+#[test]
+fn track_dirty_rows() {
+    let mut game = make_game();
+    let start = game.field().revision();
+    assert!(game.field().dirty_rows_since(start).is_empty());
+
+    game.hard_drop();
+    game.save();
+    let after_lock = game.field().revision();
+    assert!(after_lock > start);
+    assert_eq!(game.field().dirty_rows_since(start), vec![18, 19]);
+    assert!(game.field().dirty_rows_since(after_lock).is_empty());
+
+    game.add_garbage(1, 0);
+    assert_eq!(game.field().dirty_rows_since(after_lock).len(), 27);
+}
+
+#[test]
+fn mark_moved_rows_dirty_when_removing_lines() {
+    let mut game = make_game();
+    prepare_tetris(&mut game);
+    let start = game.field().revision();
+    game.hard_drop();
+    game.save();
+    assert_eq!(
+        game.field().dirty_rows_since(start),
+        (-7..20).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn hash_positions() {
+    let mut game = make_game();
+    let empty = game.field().zobrist_hash();
+    assert_eq!(empty, 0);
+    let start = game.position_hash();
+
+    game.move_left();
+    assert_ne!(game.position_hash(), start);
+    game.move_right();
+    assert_eq!(game.position_hash(), start);
+
+    game.hard_drop();
+    game.save();
+    let field_hash = game.field().zobrist_hash();
+    assert_ne!(field_hash, empty);
+    let other: Field = format!("{:?}", game.field()).parse().unwrap();
+    assert_eq!(other.zobrist_hash(), field_hash);
+
+    let mut same = make_game();
+    same.hard_drop();
+    same.save();
+    assert_eq!(same.position_hash(), game.position_hash());
+    same.hold();
+    assert_ne!(same.position_hash(), game.position_hash());
+
+    // An O-tetrimino in the second slot is apart from whether it can hold.
+    let mut state = make_game().to_state();
+    state.held = VecDeque::from([Tetrimino::new(BlockKind::T)]);
+    state.can_hold = true;
+    let one_held = Game::from_state(state.clone(), make_selector()).unwrap();
+    state.held.push_back(Tetrimino::new(BlockKind::O));
+    state.can_hold = false;
+    let two_held = Game::from_state(state, make_selector()).unwrap();
+    assert_ne!(one_held.position_hash(), two_held.position_hash());
+}
+
+#[test]
+fn fork_game_for_looking_ahead() {
+    let mut game = make_game();
+    game.set_rotation_system(Box::new(Srs));
+    game.move_left();
+    let hash = game.position_hash();
+
+    let mut fork = game.fork();
+    assert_eq!(fork.position_hash(), hash);
+    for _ in 0..5 {
+        fork.hard_drop();
+        fork.save();
+    }
+    assert!(fork.score().points() > 0);
+    assert_eq!(game.position_hash(), hash);
+    assert_eq!(game.score().points(), 0);
+
+    // The queue was L, I, J, and it repeats after that.
+    let kinds: Vec<_> = fork.queue().iter().map(|t| t.kind()).collect();
+    assert_eq!(kinds, vec![BlockKind::J, BlockKind::L, BlockKind::I]);
+
+    let mut fork = game.fork_with(|| BlockKind::O);
+    for _ in 0..4 {
+        fork.hard_drop();
+        fork.save();
+    }
+    assert_eq!(fork.tetrimino().kind(), BlockKind::O);
+    game.hard_drop();
+    game.save();
+    assert_eq!(game.tetrimino().kind(), BlockKind::L);
+}
+
+#[test]
+fn fork_without_old_kinds() {
+    let mut game = Game::with_seed(10, 20, 3, 5);
+    game.set_undo_limit(2);
+    for _ in 0..100 {
+        game.hard_drop();
+        game.save();
+        game.field.clear();
+    }
+    let fork = game.fork();
+    assert_eq!(fork.dealt.len(), 2);
+
+    // The fork can still undo into the same queue.
+    let mut fork = game.fork();
+    game.undo_last_lock();
+    game.undo_last_lock();
+    fork.undo_last_lock();
+    fork.undo_last_lock();
+    assert_eq!(fork.to_state().diff(&game.to_state()), None);
+    fork.hard_drop();
+    fork.save();
+    game.hard_drop();
+    game.save();
+    assert_eq!(fork.to_state().diff(&game.to_state()), None);
+}
+
+#[test]
+fn clone_game_with_concrete_selector() {
+    let mut game: Game<Bag> = Game::new(10, 20, 3, Bag::seven(5));
+    let mut clone = game.clone();
+    for _ in 0..10 {
+        game.hard_drop();
+        game.save();
+        clone.hard_drop();
+        clone.save();
+    }
+    assert_eq!(game.position_hash(), clone.position_hash());
+
+    let mut boxed: Game = game.into_boxed();
+    boxed.hard_drop();
+    clone.hard_drop();
+    assert_eq!(boxed.save(), clone.save());
+    assert_eq!(boxed.tetrimino(), clone.tetrimino());
+}
+
+#[test]
+fn select_looking_at_game() {
+    let mut game = Game::new(
+        10,
+        20,
+        1,
+        WithView(|view: &GameView| {
+            if view.queue().is_empty() {
+                BlockKind::T
+            } else if view.field().is_empty() {
+                BlockKind::O
+            } else {
+                BlockKind::I
+            }
+        }),
+    );
+    assert_eq!(game.tetrimino().kind(), BlockKind::T);
+    assert_eq!(game.queue()[0].kind(), BlockKind::T);
+
+    game.hard_drop();
+    game.save();
+    assert_eq!(game.queue()[0].kind(), BlockKind::I);
+}
+
+#[test]
+fn send_games_to_other_threads() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Game<Bag>>();
+    assert_send_sync::<Battle<Bag>>();
+
+    let selector: Box<dyn PieceSelector + Send> = Box::new(History::tgm(0));
+    let mut game: SendGame = Game::new(10, 20, 3, selector);
+    let handle = std::thread::spawn(move || {
+        game.hard_drop();
+        game.save();
+        game
+    });
+    let game = handle.join().unwrap();
+    assert!(!game.field().is_empty());
+}
+
+#[test]
+fn hold_unlimited_times() {
+    let mut game = make_game();
+    game.set_hold_rule(HoldRule::unlimited());
+    assert!(game.hold()); // held: T, current: L
+    assert!(game.hold()); // held: L, current: T
+    assert!(game.hold()); // held: T, current: L
+    assert_eq!(game.tetrimino().kind(), BlockKind::L);
+    assert_eq!(game.held().unwrap().kind(), BlockKind::T);
+}
+
+#[test]
+fn hold_in_multiple_slots() {
+    let mut game = Game::builder()
+        .hold_rule(HoldRule::with_slots(2))
+        .build(make_selector())
+        .unwrap();
+    game.hold(); // held: T, current: L
+    game.hard_drop();
+    game.save(); // current: I
+    game.hold(); // held: T, I, current: J
+    assert_eq!(game.tetrimino().kind(), BlockKind::J);
+    let held: Vec<_> = game.held_all().iter().map(|t| t.kind()).collect();
+    assert_eq!(held, vec![BlockKind::T, BlockKind::I]);
+
+    game.hard_drop();
+    game.save();
+    game.hold(); // held: I, J, current: T
+    assert_eq!(game.tetrimino().kind(), BlockKind::T);
+    assert_eq!(game.held().unwrap().kind(), BlockKind::I);
+}
+
+#[test]
+fn disable_holding() {
+    let mut game = make_game();
+    game.set_hold_rule(HoldRule::disabled());
+    assert!(!game.can_hold());
+    assert!(!game.hold());
+    assert!(game
+        .legal_placements(true)
+        .iter()
+        .all(|t| t.kind() == BlockKind::T));
+}
+
+#[test]
+fn peek_queue_and_bag() {
+    let mut game = Game::new(10, 20, 3, Bag::seven(9));
+    let mut appeared: Vec<_> = [game.tetrimino().kind()]
+        .into_iter()
+        .chain(game.queue_shapes())
+        .collect();
+    assert_eq!(game.queue_shapes().count(), 3);
+
+    for _ in 0..10 {
+        let mut seen = appeared[appeared.len() / 7 * 7..].to_vec();
+        seen.extend(game.remaining_in_bag());
+        seen.sort_by_key(|kind| BlockKind::all_as_array().iter().position(|k| k == kind));
+        if appeared.len() % 7 != 0 {
+            assert_eq!(seen, BlockKind::all_as_array().to_vec());
+        } else {
+            assert!(game.remaining_in_bag().is_empty());
+        }
+
+        game.hard_drop();
+        game.save();
+        appeared.push(game.queue_shapes().last().unwrap());
+    }
+
+    // Going back makes the kinds in the queue remain in the bag again.
+    game.set_undo_limit(5);
+    game.hard_drop();
+    game.save();
+    let before = game.remaining_in_bag();
+    game.hard_drop();
+    game.save();
+    game.undo_last_lock();
+    assert_eq!(game.remaining_in_bag(), before);
+}
+
+#[test]
+fn inject_tetriminos_into_queue() {
+    let mut game = make_game();
+    game.push_front_queue(BlockKind::O);
+    let kinds: Vec<_> = game.queue_shapes().collect();
+    assert_eq!(kinds, vec![BlockKind::O, BlockKind::L, BlockKind::I]);
+
+    game.hard_drop();
+    game.save();
+    assert_eq!(game.tetrimino().kind(), BlockKind::O);
+    // The pushed-out J comes back before the selector is called.
+    let kinds: Vec<_> = game.queue_shapes().collect();
+    assert_eq!(kinds, vec![BlockKind::L, BlockKind::I, BlockKind::J]);
+}
+
+#[test]
+fn script_queue() {
+    let mut game = make_game();
+    game.set_queue(vec![BlockKind::S, BlockKind::Z, BlockKind::S, BlockKind::Z]);
+    let mut kinds = Vec::new();
+    for _ in 0..6 {
+        game.hard_drop();
+        game.save();
+        kinds.push(game.tetrimino().kind());
+    }
+    assert_eq!(
+        kinds,
+        vec![
+            BlockKind::S,
+            BlockKind::Z,
+            BlockKind::S,
+            BlockKind::Z,
+            BlockKind::J,
+            BlockKind::J
+        ]
+    );
+}
+
+#[test]
+fn deal_same_tetriminos_with_same_seed() {
+    let mut first = Game::with_seed(10, 20, 5, 42);
+    let mut second = Game::with_seed(10, 20, 5, 42);
+    for _ in 0..20 {
+        assert_eq!(first.position_hash(), second.position_hash());
+        first.hard_drop();
+        first.save();
+        second.hard_drop();
+        second.save();
+    }
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn seed_game_from_rng() {
+    let mut rng = rand::rngs::mock::StepRng::new(1, 1);
+    let game = Game::from_rng(10, 20, 5, &mut rng);
+    let same = Game::with_seed(10, 20, 5, 1);
+    assert_eq!(game.position_hash(), same.position_hash());
+}
+
+#[test]
+fn complete_sprint() {
+    let mut sprint = Sprint::with_target(make_game(), 4);
+    assert_eq!(sprint.lines_left(), 4);
+    sprint.update(Duration::from_secs(30));
+    prepare_tetris(sprint.game_mut());
+    sprint.game_mut().hard_drop();
+    sprint.save();
+    assert!(sprint.is_complete());
+    assert!(sprint.is_finished());
+    assert_eq!(sprint.lines_per_minute(), 8.0);
+
+    // The time stops after finishing.
+    sprint.update(Duration::from_secs(30));
+    assert_eq!(sprint.elapsed(), Duration::from_secs(30));
+}
+
+#[test]
+fn level_up_in_marathon() {
+    let mut marathon = Marathon::with_levels(make_game(), 4, 2);
+    assert_eq!(marathon.level(), 1);
+    assert_eq!(marathon.game().gravity(), Gravity::cells_per_second(1.0));
+
+    prepare_tetris(marathon.game_mut());
+    marathon.game_mut().hard_drop();
+    marathon.save();
+    assert_eq!(marathon.level(), 2);
+    assert_eq!(marathon.game().score().level(), 2);
+    assert!(marathon.game().gravity().as_cells_per_second() > 1.0);
+    assert!(!marathon.is_complete());
+}
+
+#[test]
+fn follow_level_table() {
+    let mut game = make_game();
+    game.set_level_table(Some(LevelTable {
+        lines_per_level: 4,
+        ..LevelTable::nes()
+    }));
+    assert_eq!(game.level(), 1);
+    assert_eq!(game.gravity(), Gravity::from_g(1.0 / 48.0));
+    assert_eq!(game.lock_delay().delay, Duration::ZERO);
+
+    prepare_tetris(&mut game);
+    game.hard_drop();
+    game.save();
+    assert_eq!(game.level(), 2);
+    assert_eq!(game.score().level(), 2);
+    assert_eq!(game.gravity(), Gravity::from_g(1.0 / 43.0));
+
+    // The tetrimino is saved as soon as it touches down.
+    game.hard_drop();
+    assert!(game.update(Duration::from_millis(1)).locked.is_some());
+}
+
+#[test]
+fn use_last_entry_of_level_table() {
+    let table = LevelTable::guideline();
+    assert_eq!(table.level(0), 1);
+    assert_eq!(table.level(25), 3);
+    assert_eq!(table.gravity(1), Gravity::cells_per_second(1.0));
+    assert_eq!(table.gravity(40), table.gravity(20));
+    assert_eq!(LevelTable::nes().gravity(30), Gravity::from_g(1.0));
+}
+
+#[test]
+fn finish_ultra_on_time() {
+    let mut ultra = Ultra::with_time_limit(make_game(), Duration::from_secs(3));
+    ultra.update(Duration::from_secs(2));
+    assert_eq!(ultra.time_left(), Duration::from_secs(1));
+    ultra.update(Duration::from_secs(2));
+    assert!(ultra.is_complete());
+    assert_eq!(ultra.elapsed(), Duration::from_secs(3));
+    assert_eq!(ultra.save(), LockResult::default());
+}
+
+#[test]
+fn fill_field_with_cheese() {
+    let race = CheeseRace::new(make_game(), CheeseRules::default());
+    let field = race.game().field();
+    let holes: Vec<_> = (10..20)
+        .map(|y| field.row_mask(y).trailing_ones())
+        .collect();
+    assert!(holes.windows(2).all(|pair| pair[0] != pair[1]));
+    assert_eq!(field.row_mask(9), 0);
+    assert_eq!(race.lines_left(), 18);
+}
+
+#[test]
+fn refill_removed_cheese() {
+    let rules = CheeseRules {
+        lines: 12,
+        height: 5,
+        messiness: 0.0,
+        seed: 1,
+    };
+    let mut race = CheeseRace::new(make_game(), rules);
+    let hole = race.game().field().row_mask(19).trailing_ones() as isize;
+    race.game_mut().tetrimino = Tetrimino::new(BlockKind::I).rotate(1).move_to((hole, 16));
+
+    assert_eq!(race.save().lines(), 4);
+    assert_eq!(race.lines_left(), 8);
+    assert_eq!(race.game().field().row_mask(14), 0);
+    assert_ne!(race.game().field().row_mask(15), 0);
+    assert!(!race.is_complete());
+}
+
+#[test]
+fn solve_puzzle() {
+    let puzzle = Puzzle {
+        field: "
+            |          |
+            |          |
+            |xxxxxx    |
+        "
+        .parse()
+        .unwrap(),
+        pieces: vec![BlockKind::I, BlockKind::O],
+        goal: Goal::ClearAllBlocks,
+    };
+    let mut puzzle_game = PuzzleGame::new(puzzle, 1);
+    assert_eq!(puzzle_game.status(), PuzzleStatus::Playing);
+
+    puzzle_game.game_mut().move_right_to_wall();
+    puzzle_game.game_mut().hard_drop();
+    assert!(puzzle_game.save().is_perfect_clear);
+    assert_eq!(puzzle_game.status(), PuzzleStatus::Solved);
+    assert_eq!(puzzle_game.pieces_left(), 1);
+    assert_eq!(puzzle_game.save(), LockResult::default());
+}
+
+#[test]
+fn fail_puzzle_after_all_pieces() {
+    let puzzle = Puzzle {
+        field: "|          |\n".repeat(10).parse().unwrap(),
+        pieces: vec![BlockKind::O, BlockKind::O],
+        goal: Goal::SurviveNPieces(3),
+    };
+    let mut puzzle_game = PuzzleGame::new(puzzle, 1);
+    puzzle_game.game_mut().hard_drop();
+    puzzle_game.save();
+    assert_eq!(puzzle_game.status(), PuzzleStatus::Playing);
+    puzzle_game.game_mut().hard_drop();
+    puzzle_game.save();
+    assert_eq!(puzzle_game.status(), PuzzleStatus::Failed);
+    assert!(puzzle_game.is_finished());
+}
+
+#[test]
+fn compute_finesse_table() {
+    let table = FinesseTable::new(&make_game(), false);
+    assert_eq!(table.optimal_presses(BlockKind::T, 3, 0), Some(0));
+    assert_eq!(table.optimal_presses(BlockKind::T, 2, 0), Some(1));
+    assert_eq!(table.optimal_presses(BlockKind::T, 0, 0), Some(1));
+    assert_eq!(table.optimal_presses(BlockKind::T, 1, 0), Some(2));
+    assert_eq!(table.optimal_presses(BlockKind::T, 0, 1), Some(2));
+    assert_eq!(table.optimal_presses(BlockKind::T, 8, 0), None);
+    // The vertical I-tetrimino occupies the same cells in two states.
+    assert_eq!(
+        table.optimal_presses(BlockKind::I, 0, 1),
+        table.optimal_presses(BlockKind::I, 0, 3)
+    );
+}
+
+#[test]
+fn compute_finesse_table_during_delay() {
+    let mut game = make_game();
+    game.set_are(Duration::from_secs(1));
+    game.hard_drop();
+    game.save();
+    assert_eq!(game.phase(), Phase::Are);
+
+    let table = FinesseTable::new(&game, false);
+    assert_eq!(table, FinesseTable::new(&make_game(), false));
+    assert_eq!(table.optimal_presses(BlockKind::T, 0, 0), Some(1));
+}
+
+#[test]
+fn report_finesse_faults() {
+    let mut game = make_game();
+    game.set_finesse_table(Some(FinesseTable::new(&game, false)));
+
+    for _ in 0..3 {
+        game.apply(Input::Left);
+    }
+    game.apply(Input::HardDrop);
+    assert_eq!(
+        game.apply(Input::Lock).finesse_fault,
+        Some(FinesseFault {
+            optimal: 1,
+            presses: 3
+        })
+    );
+
+    // Holding the key until bumping into the wall is one press.
+    for _ in 0..MAX_WIDTH {
+        game.apply(Input::Left);
+    }
+    game.apply(Input::HardDrop);
+    assert_eq!(game.apply(Input::Lock).finesse_fault, None);
+    assert_eq!(game.finesse_faults(), 1);
+}
+
+#[test]
+fn accumulate_stats() {
+    let mut game = make_game();
+    assert_eq!(game.stats(), &Stats::default());
+
+    game.update(Duration::from_secs(1));
+    game.hard_drop_and_lock();
+    prepare_tetris(&mut game);
+    game.save();
+    let stats = game.stats();
+    assert_eq!(stats.pieces_of(BlockKind::T), 1);
+    assert_eq!(stats.pieces_of(BlockKind::I), 1);
+    assert_eq!(stats.total_pieces(), 2);
+    assert_eq!(stats.tetrises, 1);
+    assert_eq!(stats.max_combo, 0);
+    assert_eq!(stats.attack, 4);
+    assert_eq!(stats.pieces_per_second(), Some(2.0));
+}
+
+#[test]
+fn record_history_of_locks() {
+    let mut game = make_game();
+    game.hard_drop_and_lock();
+    assert!(game.history().is_empty());
+
+    game.set_history_limit(2);
+    game.update(Duration::from_millis(500));
+    let ghost = game.ghost();
+    let result = game.hard_drop_and_lock();
+    assert_eq!(
+        game.history().back(),
+        Some(&HistoryEntry {
+            tetrimino: ghost,
+            result,
+            time: Duration::from_millis(500),
+        })
+    );
+
+    game.hard_drop_and_lock();
+    game.hard_drop_and_lock();
+    assert_eq!(game.history().len(), 2);
+    game.set_history_limit(1);
+    assert_eq!(game.history()[0].tetrimino.kind(), BlockKind::J);
+}
+
+#[test]
+fn read_and_write_map_strings() {
+    let field = Field::from_map_string("____SS____ZZ_GSS_JJJ?TIOS", 10);
+    let expected: Field = "
+        |    SS    |
+        |ZZ xSS JJJ|
+    "
+    .parse()
+    .unwrap();
+    let field = field.unwrap();
+    assert_eq!(field.as_vec_full(), expected.as_vec_full());
+    assert_eq!(field.to_map_string(), "____SS____ZZ_#SS_JJJ");
+
+    assert_eq!(
+        Field::from_map_string("____", 3).err(),
+        Some(ParseFieldError::UnevenRows { line: 1 })
+    );
+    assert_eq!(
+        Field::from_map_string("___k", 2).err(),
+        Some(ParseFieldError::UnknownChar { line: 1, char: 'k' })
+    );
+    assert_eq!(
+        Field::from_map_string("?", 10).err(),
+        Some(ParseFieldError::Empty)
+    );
+}
+
+#[test]
+fn encode_and_decode_state() {
+    let mut game = make_game();
+    game.add_garbage(2, 3);
+    game.hold();
+    game.rotate();
+    game.move_left();
+    let state = game.to_state();
+
+    let bytes = state.encode().unwrap();
+    assert_eq!(bytes.len(), 4 + 135 + 5 + 2 + 15 + 2 + 5 + 6);
+    let decoded = GameState::decode(&bytes).unwrap();
+    assert_eq!(decoded.field.as_vec_full(), state.field.as_vec_full());
+    assert_eq!(decoded.tetrimino, state.tetrimino);
+    assert_eq!(decoded.queue, state.queue);
+    assert_eq!(decoded.held, state.held);
+    assert!(!decoded.can_hold);
+    assert_eq!(decoded.encode().unwrap(), bytes);
+
+    assert!(GameState::decode(&bytes[..bytes.len() - 1]).is_none());
+    assert!(GameState::decode(&[bytes.as_slice(), &[0]].concat()).is_none());
+
+    // Long queues are kept, and values out of the format are rejected.
+    let mut long = state.clone();
+    long.queue = vec![Tetrimino::new(BlockKind::T); 300].into();
+    let decoded = GameState::decode(&long.encode().unwrap()).unwrap();
+    assert_eq!(decoded.queue.len(), 300);
+    long.tetrimino = long.tetrimino.move_down(40_000);
+    assert!(long.encode().is_none());
+}
+
+#[test]
+fn roll_back_to_snapshot() {
+    let mut game = Game::with_seed(10, 20, 3, 0);
+    game.update(Duration::from_millis(1500));
+    let snapshot = game.save_snapshot();
+    let expected = game.fork();
+
+    game.hard_drop_and_lock();
+    game.add_garbage(2, 0);
+    game.hold();
+    game.update(Duration::from_millis(700));
+    game.restore_snapshot(&snapshot);
+    assert_eq!(
+        game.to_state().encode().unwrap(),
+        expected.to_state().encode().unwrap()
+    );
+    assert_eq!(game.stats(), expected.stats());
+    assert_eq!(game.lock_timer(), expected.lock_timer());
+    // Restoring again changes nothing.
+    let revision = game.field().revision();
+    game.restore_snapshot(&snapshot);
+    assert!(game.field().dirty_rows_since(revision).is_empty());
+
+    let mut reused = game.save_snapshot();
+    game.hard_drop_and_lock();
+    game.save_snapshot_into(&mut reused);
+    let after_lock = game.to_state().encode().unwrap();
+    game.restore_snapshot(&snapshot);
+    game.restore_snapshot(&reused);
+    assert_eq!(game.to_state().encode().unwrap(), after_lock);
+
+    // The same tetriminos come again after rolling back.
+    game.restore_snapshot(&snapshot);
+    game.hard_drop_and_lock();
+    assert_eq!(game.to_state().encode().unwrap(), after_lock);
+}
+
+#[test]
+fn detect_desync() {
+    let mut first = Game::with_seed(10, 20, 3, 0);
+    let mut second = Game::with_seed(10, 20, 3, 0);
+    assert_eq!(first.state_digest(), second.state_digest());
+    assert_eq!(first.to_state().diff(&second.to_state()), None);
+
+    first.move_left();
+    assert_ne!(first.state_digest(), second.state_digest());
+    assert_eq!(
+        first.to_state().diff(&second.to_state()),
+        Some(StateDiff::Tetrimino)
+    );
+
+    first.hard_drop_and_lock();
+    second.move_right();
+    second.hard_drop_and_lock();
+    assert_eq!(
+        first.to_state().diff(&second.to_state()),
+        Some(StateDiff::FieldRow(18))
+    );
+
+    let mut state = first.to_state();
+    state.queue.pop_back();
+    assert_eq!(first.to_state().diff(&state), Some(StateDiff::Queue(2)));
+}
+
+#[cfg(feature = "wasm")]
+#[test]
+fn play_through_js_game() {
+    use crate::wasm::JsGame;
+
+    let mut game = JsGame::new(10, 20, 3, 0);
+    assert_eq!(game.cells().len(), 200);
+    assert_eq!(game.queue().len(), 3);
+    assert!(game.apply("left"));
+    assert!(!game.apply("unknown"));
+    assert_eq!(game.blocks().len(), 8);
+    assert!(game.apply("hardDrop"));
+    assert_eq!(game.blocks(), game.ghost_blocks());
+    assert!(game.apply("lock"));
+    assert_eq!(game.cells().iter().filter(|c| **c != 0).count(), 4);
+    assert_eq!(game.held(), None);
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn play_through_ffi() {
+    use crate::ffi::*;
+
+    assert!(tetrice_game_new(2, 20, 3, 0).is_null());
+    let game = tetrice_game_new(10, 20, 3, 0);
+    unsafe {
+        assert_eq!(tetrice_game_field_cells(game, std::ptr::null_mut(), 0), 200);
+        let mut queue = [0; 3];
+        assert_eq!(tetrice_game_queue(game, queue.as_mut_ptr(), 3), 3);
+        assert!(queue.iter().all(|kind| (1..=7).contains(kind)));
+
+        assert!(tetrice_game_move_left(game));
+        assert!(tetrice_game_hard_drop(game));
+        let mut blocks = [0; 8];
+        let mut ghost = [0; 8];
+        tetrice_game_tetrimino_blocks(game, blocks.as_mut_ptr());
+        tetrice_game_ghost_blocks(game, ghost.as_mut_ptr());
+        assert_eq!(blocks, ghost);
+        assert!(tetrice_game_lock(game));
+
+        let mut cells = [0; 200];
+        tetrice_game_field_cells(game, cells.as_mut_ptr(), cells.len());
+        assert_eq!(cells.iter().filter(|c| **c != 0).count(), 4);
+        assert_eq!(tetrice_game_tetrimino_kind(game), queue[0]);
+        tetrice_game_free(game);
+    }
+}
+
+#[test]
+fn edit_field() {
+    let mut field = Field::new(4, 3, 1);
+    assert!(field.set_cell((1, 2), Cell::Garbage));
+    assert!(field.set_cell((0, -1), Cell::Block(BlockKind::T)));
+    assert!(!field.set_cell((4, 0), Cell::Garbage));
+    assert!(!field.set_cell((0, 3), Cell::Garbage));
+    assert!(field.fill_row(0, Cell::Solid));
+    assert!(!field.fill_row(-2, Cell::Solid));
+    assert!(field.clear_cell((2, 0)));
+    assert_eq!(field.get_cell((0, -1)), Cell::Block(BlockKind::T));
+    assert_eq!(field.to_map_string(), "==_=_____#__");
+    assert_eq!(field.row_mask(0), 0b1011);
+    assert_eq!(field.dirty_rows_since(0), [-1, 0, 2]);
+
+    field.clear();
+    assert_eq!(field, Field::new(4, 3, 1));
+}
+
+#[test]
+fn compare_and_hash_fields() {
+    let field: Field = "
+        |   T  |
+        |  TTT |
+    "
+    .parse()
+    .unwrap();
+    let mut changed = field.clone();
+    changed.push_up(vec![vec![Cell::Garbage; 6]]);
+    assert_ne!(changed, field);
+    changed.remove_filled_lines();
+    assert_ne!(changed.revision(), field.revision());
+    assert_eq!(changed, field);
+
+    let set = HashSet::from([field, changed]);
+    assert_eq!(set.len(), 1);
+}
+
+#[test]
+fn convert_block_kinds_to_chars() {
+    for kind in BlockKind::all_as_array() {
+        assert_eq!(BlockKind::from_char(kind.to_char()), Some(kind));
+    }
+    assert_eq!(BlockKind::T.to_char(), 'T');
+    assert_eq!(BlockKind::from_char('t'), None);
+    assert_eq!(BlockKind::I.guideline_color(), (0, 255, 255));
+}
+
+#[test]
+fn select_by_weights_and_script() {
+    use crate::selectors::{Scripted, Weighted};
+
+    let mut weighted = Weighted::new([(BlockKind::T, 3.0), (BlockKind::I, 1.0)], 0);
+    let kinds: Vec<_> = (0..1000).map(|_| weighted.select()).collect();
+    let ts = kinds.iter().filter(|k| **k == BlockKind::T).count();
+    assert!(kinds
+        .iter()
+        .all(|k| [BlockKind::T, BlockKind::I].contains(k)));
+    assert!((650..850).contains(&ts));
+
+    let fallback = Weighted::new([(BlockKind::O, 1.0)], 0);
+    let mut scripted = Scripted::new(vec![BlockKind::S, BlockKind::Z], fallback);
+    assert_eq!(scripted.select(), BlockKind::S);
+    assert_eq!(scripted.remaining().len(), 1);
+    assert_eq!(scripted.select(), BlockKind::Z);
+    assert_eq!(scripted.select(), BlockKind::O);
+}
+
+#[test]
+fn create_game_from_parts() {
+    let field: Field = "
+        |          |
+        |          |
+        |          |
+        |#### #####|
+    "
+    .parse()
+    .unwrap();
+    let queue = vec![BlockKind::L, BlockKind::J];
+    let mut game = Game::from_parts(
+        field.clone(),
+        BlockKind::I,
+        queue,
+        Some(BlockKind::O),
+        Random::new(0),
+    );
+    assert_eq!(game.field(), &field);
+    assert_eq!(game.tetrimino().kind(), BlockKind::I);
+    assert_eq!(
+        game.queue_shapes().collect::<Vec<_>>(),
+        [BlockKind::L, BlockKind::J]
+    );
+    assert_eq!(game.held().map(|t| t.kind()), Some(BlockKind::O));
+
+    game.apply(Input::HardDrop);
+    game.apply(Input::Lock);
+    assert_eq!(game.tetrimino().kind(), BlockKind::L);
+    assert_eq!(game.queue_shapes().count(), 2);
+    assert!(game.hold());
+    assert_eq!(game.tetrimino().kind(), BlockKind::O);
+}
+
+#[test]
+fn reset_game() {
+    let mut game = Game::with_seed(10, 20, 3, 0);
+    game.set_gravity(Gravity::cells_per_second(0.0));
+    let first: Vec<_> = (0..7)
+        .map(|_| {
+            let kind = game.tetrimino().kind();
+            game.hard_drop_and_lock();
+            kind
+        })
+        .collect();
+    game.hold();
+    game.reset();
+    assert_eq!(game.field(), &Field::new(10, 20, 7));
+    assert_eq!(game.score().points(), 0);
+    assert_eq!(game.stats(), &Stats::default());
+    assert_eq!(game.held(), None);
+    assert_eq!(game.queue_shapes().count(), 3);
+    assert!(!game.is_end());
+    assert_eq!(game.gravity(), Gravity::cells_per_second(0.0));
+
+    game.reset_with(Bag::seven(0));
+    let again: Vec<_> = (0..7)
+        .map(|_| {
+            let kind = game.tetrimino().kind();
+            game.hard_drop_and_lock();
+            kind
+        })
+        .collect();
+    assert_eq!(again, first);
+}
+
+#[test]
+fn pause_and_resume() {
+    let mut game = make_game();
+    game.set_gravity(Gravity::cells_per_second(1.0));
+    game.update(Duration::from_millis(500));
+    assert!(game.pause());
+    assert!(!game.pause());
+    assert_eq!(game.update(Duration::from_secs(10)), TickResult::default());
+    assert!(!game.apply(Input::Left).applied);
+    assert!(!game.move_left());
+    assert!(!game.rotate().rotated);
+    assert!(!game.hold());
+    assert_eq!(game.hard_drop(), 0);
+    assert_eq!(game.save(), LockResult::default());
+    assert_eq!(game.stats().total_pieces(), 0);
+    assert_eq!(game.paused_duration(), Duration::from_secs(10));
+    assert_eq!(game.stats().elapsed, Duration::from_millis(500));
+
+    assert!(game.resume());
+    assert!(!game.is_paused());
+    assert_eq!(game.update(Duration::from_millis(500)).dropped, 1);
+    assert!(game.apply(Input::Left).applied);
+}
+
+#[test]
+fn classify_clears() {
+    assert_eq!(ClearKind::new(4, None), ClearKind::Tetris);
+    assert_eq!(ClearKind::new(6, None).lines(), 4);
+    assert_eq!(ClearKind::new(0, Some(SpinKind::Full)), ClearKind::TSpin);
+    assert_eq!(
+        ClearKind::new(1, Some(SpinKind::Mini)),
+        ClearKind::TSpinMiniSingle
+    );
+    for kind in [ClearKind::TSpinTriple, ClearKind::TSpinMiniDouble] {
+        assert_eq!(ClearKind::new(kind.lines(), kind.spin()), kind);
+    }
+    assert!(ClearKind::TSpinSingle.is_difficult());
+    assert!(!ClearKind::TSpin.is_difficult());
+    assert!(!ClearKind::Triple.is_difficult());
+}
+
+#[test]
+fn report_visible_cleared_rows() {
+    let result = LockResult {
+        cleared_rows: vec![-1, 0, 3],
+        ..LockResult::default()
+    };
+    assert_eq!(result.cleared_visible_rows(), [0, 3]);
+}
+
+#[test]
+fn delay_line_clears() {
+    let mut game = make_game();
+    game.set_line_clear_delay(Duration::from_millis(300));
+    prepare_tetris(&mut game);
+    assert_eq!(game.save().lines(), 4);
+    assert!(game.is_clearing());
+    assert_eq!(game.field().get_cell((0, 19)), Cell::Clearing);
+    assert_eq!(game.field().get_cell((0, 15)), Cell::Block(BlockKind::O));
+    assert!(!game.apply(Input::HardDrop).buffered);
+    assert!(!game.hold());
+
+    game.update(Duration::from_millis(200));
+    assert!(game.is_clearing());
+    game.update(Duration::from_millis(100));
+    assert!(!game.is_clearing());
+    assert_eq!(game.field().get_cell((0, 19)), Cell::Block(BlockKind::O));
+    assert_eq!(game.tetrimino().kind(), BlockKind::L);
+    assert!(game.apply(Input::Left).applied);
+}
+
+#[test]
+fn buffer_inputs_during_are() {
+    let mut game = make_game();
+    game.set_are(Duration::from_millis(100));
+    game.set_line_clear_delay(Duration::from_millis(300));
+    prepare_tetris(&mut game);
+    game.save();
+    let result = game.apply(Input::RotateCw);
+    assert!(result.buffered && !result.applied);
+    assert!(!game.apply(Input::HardDrop).buffered);
+    game.apply(Input::Left);
+    assert_eq!(game.buffered_inputs(), [Input::RotateCw, Input::Left]);
+
+    game.update(Duration::from_millis(350));
+    assert!(!game.is_clearing());
+    assert_eq!(game.buffered_inputs().len(), 2);
+    game.update(Duration::from_millis(50));
+    assert_eq!(game.tetrimino().kind(), BlockKind::L);
+    assert_eq!(game.tetrimino().rotation(), 1);
+    assert!(game.buffered_inputs().is_empty());
+
+    // Without clears, only ARE runs.
+    game.hard_drop();
+    game.save();
+    assert!(!game.is_clearing());
+    assert!(!game.apply(Input::Left).applied);
+    game.update(Duration::from_millis(100));
+    assert_eq!(game.tetrimino().kind(), BlockKind::I);
+    assert!(game.apply(Input::Left).applied);
+}
+
+#[test]
+fn follow_phases() {
+    let mut game = make_game();
+    game.set_are(Duration::from_millis(100));
+    game.set_line_clear_delay(Duration::from_millis(100));
+    assert_eq!(game.phase(), Phase::Falling);
+    prepare_tetris(&mut game);
+    assert_eq!(game.phase(), Phase::LockDelay);
+    game.save();
+    assert_eq!(game.phase(), Phase::Clearing);
+    game.update(Duration::from_millis(100));
+    assert_eq!(game.phase(), Phase::Are);
+    game.update(Duration::from_millis(100));
+    assert_eq!(game.phase(), Phase::Falling);
+    game.end(EndReason::BlockOut);
+    assert_eq!(game.phase(), Phase::Over);
+}
+
+#[test]
+fn hide_saved_blocks() {
+    let mut game = make_game();
+    game.set_visibility(Visibility::HideAfter(Duration::from_secs(1)));
+    game.add_garbage(1, 0);
+    game.hard_drop();
+    game.save();
+    assert!(game.is_visible((4, 18)));
+    game.update(Duration::from_millis(999));
+    assert!(game.is_visible((4, 18)));
+    game.update(Duration::from_millis(1));
+    assert!(!game.is_visible((4, 18)));
+    assert!(game.is_visible((1, 19)));
+    assert!(!game.visibility_mask()[18][4]);
+    assert!(game
+        .render_to_string()
+        .lines()
+        .nth(18)
+        .unwrap()
+        .chars()
+        .all(|c| c != 'T'));
+
+    game.set_visibility(Visibility::Visible);
+    assert!(game.is_visible((4, 18)));
+}
+
+#[test]
+fn play_big_mode() {
+    let mut game = Game::builder()
+        .size(10, 20)
+        .big(true)
+        .build(Bag::seven(0))
+        .unwrap();
+    assert!(game.is_big());
+    assert_eq!((game.field().width(), game.field().height()), (5, 10));
+    assert_eq!(game.field().hidden_height(), 4);
+
+    game.field.fill_row(9, Cell::Garbage);
+    game.field.clear_cell((0, 9));
+    let scaled = game.field().scaled(2);
+    assert_eq!((scaled.width(), scaled.height()), (10, 20));
+    assert_eq!(scaled.get_cell((2, 19)), Cell::Garbage);
+    assert_eq!(scaled.get_cell((1, 18)), Cell::Empty);
+
+    let t = Tetrimino::new(BlockKind::O).move_to((1, 2));
+    let blocks = t.scaled_blocks(2);
+    assert_eq!(blocks.len(), 16);
+    assert!(blocks.contains(&(2, 4)) && blocks.contains(&(5, 7)));
+
+    let rendered = game.render_to_string();
+    assert_eq!(rendered.lines().count(), 20);
+    assert!(rendered.lines().all(|line| line.chars().count() == 12));
+
+    let field = Field::new(10, 20, 7);
+    let result = Game::builder()
+        .big(true)
+        .starting_field(field)
+        .build(Bag::seven(0));
+    assert_eq!(result.err(), Some(BuildError::BigWithStartingField));
+}
+
+#[test]
+fn mirror_game() {
+    for kind in BlockKind::all_as_array() {
+        for rotation in 0..4 {
+            let t = Tetrimino::new(kind).rotate(rotation).move_to((2, 5));
+            let mut expected = t.blocks().map(|(x, y)| (9 - x, y));
+            let mut blocks = t.mirrored(10).blocks();
+            expected.sort();
+            blocks.sort();
+            assert_eq!(blocks, expected);
+            assert_eq!(t.mirrored(10).mirrored(10).blocks(), t.blocks());
+        }
+    }
+
+    let mut game = make_game();
+    game.hard_drop();
+    game.save();
+    let before = game.field().clone();
+    game.set_mirrored(true);
+    assert!(game.is_mirrored());
+    assert_eq!(game.field().get_cell((5, 19)), Cell::Block(BlockKind::T));
+    assert_eq!(game.tetrimino().kind(), BlockKind::J);
+    assert_eq!(
+        game.queue_shapes().collect::<Vec<_>>(),
+        [BlockKind::I, BlockKind::L, BlockKind::L]
+    );
+    game.set_mirrored(false);
+    assert_eq!(game.field(), &before);
+    assert_eq!(game.tetrimino().kind(), BlockKind::L);
+
+    let game = Game::builder().mirrored(true).build(Bag::seven(0)).unwrap();
+    let plain = Game::with_seed(10, 20, 3, 0);
+    assert_eq!(game.tetrimino().kind(), plain.tetrimino().kind().mirrored());
+}
+
+#[test]
+fn build_with_handicaps() {
+    let game = Game::builder()
+        .starting_garbage(5, 0.0, 3)
+        .build(Bag::seven(0))
+        .unwrap();
+    let rows: Vec<_> = game
+        .field()
+        .rows()
+        .filter(|(_, row)| row.contains(&Cell::Garbage))
+        .collect();
+    assert_eq!(rows.len(), 5);
+    assert_eq!(rows[0].0, 15);
+    // Without messiness, the holes make a straight well.
+    let hole = rows[0].1.iter().position(|cell| *cell == Cell::Empty);
+    assert!(rows
+        .iter()
+        .all(|(_, row)| row.iter().position(|cell| *cell == Cell::Empty) == hole));
+
+    let field = Field::from_map_string(&format!("{}#########_", "_".repeat(70)), 10).unwrap();
+    let game = Game::builder()
+        .starting_field(field)
+        .starting_garbage(2, 1.0, 0)
+        .build(Bag::seven(0))
+        .unwrap();
+    assert_eq!(game.field().height(), 8);
+    assert_eq!(game.field().get_cell((0, 5)), Cell::Garbage);
+    assert_eq!(game.field().get_cell((9, 5)), Cell::Empty);
+    assert!(game
+        .field()
+        .rows()
+        .filter(|(y, _)| *y >= 6)
+        .all(|(_, row)| row.contains(&Cell::Empty)));
+
+    let field = Field::from_map_string("T_________", 10).unwrap();
+    let total = field.height() + field.hidden_height();
+    for (rows, field) in [(total + 1, Field::new(10, 1, 7)), (total, field)] {
+        let result = Game::builder()
+            .starting_field(field)
+            .starting_garbage(rows, 0.0, 0)
+            .build(Bag::seven(0));
+        assert_eq!(result.err(), Some(BuildError::GarbageTooTall));
+    }
+}
+
+#[test]
+fn notify_listener() {
+    #[derive(Default)]
+    struct Counter {
+        locks: usize,
+        cleared: Vec<isize>,
+        end: Option<EndReason>,
+    }
+    struct Listener(std::sync::Arc<std::sync::Mutex<Counter>>);
+    impl GameListener for Listener {
+        fn on_lock(&mut self, _result: &LockResult) {
+            self.0.lock().unwrap().locks += 1;
+        }
+        fn on_clear(&mut self, rows: &[isize]) {
+            self.0.lock().unwrap().cleared.extend(rows);
+        }
+        fn on_top_out(&mut self, reason: EndReason) {
+            self.0.lock().unwrap().end = Some(reason);
+        }
+    }
+
+    let counter = std::sync::Arc::default();
+    let mut game = make_game();
+    game.set_listener(Box::new(Listener(std::sync::Arc::clone(&counter))));
+    prepare_tetris(&mut game);
+    game.save();
+    assert!(game.take_events().is_empty());
+    {
+        let counter = counter.lock().unwrap();
+        assert_eq!(counter.locks, 1);
+        assert_eq!(counter.cleared, [16, 17, 18, 19]);
+    }
+
+    while !game.is_end() {
+        game.hard_drop();
+        game.save();
+    }
+    assert_eq!(counter.lock().unwrap().end, game.end_reason());
+    assert!(game.take_listener().is_some());
+}
@@ -1,5 +1,19 @@
+use alloc::vec::Vec;
+
 /// A tetrimino consisting of four dropping blocks.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+///
+/// The methods moving and rotating tetriminos return new ones and don't care
+/// about fields, which is useful for trying hypothetical placements. To move
+/// the current tetrimino of a game, use `Game::apply`.
+///
+/// ```
+/// use tetrice::{BlockKind, Tetrimino};
+///
+/// let t = Tetrimino::new(BlockKind::T).rotate(1).move_to((3, 10));
+/// assert_eq!(t.blocks(), [(4, 11), (3, 10), (3, 11), (3, 12)]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tetrimino {
     kind: BlockKind,
     rot: usize,
@@ -7,7 +21,9 @@ pub struct Tetrimino {
 }
 
 impl Tetrimino {
-    pub(crate) fn new(kind: BlockKind) -> Self {
+    /// Create a tetrimino of the kind in the initial rotation, whose
+    /// bounding box of the 4 rotations has its top left corner at (0, 0).
+    pub fn new(kind: BlockKind) -> Self {
         Self {
             kind,
             rot: 0,
@@ -27,16 +43,59 @@ impl Tetrimino {
             .map(|(x, y)| (x as isize + self.pos.0, y as isize + self.pos.1))
     }
 
+    /// Get the positions of the blocks enlarged to `factor` by `factor`
+    /// cells, in the coordinates of `Field::scaled` with the same factor.
+    pub fn scaled_blocks(&self, factor: usize) -> Vec<(isize, isize)> {
+        let factor = factor as isize;
+        self.blocks()
+            .into_iter()
+            .flat_map(|(x, y)| {
+                (0..factor * factor)
+                    .map(move |i| (x * factor + i % factor, y * factor + i / factor))
+            })
+            .collect()
+    }
+
     /// Get the width.
     pub fn width(&self) -> usize {
         let blocks = self.kind.blocks(self.rot).map(|(x, _)| x);
-        (blocks.iter().max().unwrap() - blocks.iter().min().unwrap() + 1) as usize
+        blocks.iter().max().unwrap() - blocks.iter().min().unwrap() + 1
     }
 
     /// Get the height.
     pub fn height(&self) -> usize {
         let blocks = self.kind.blocks(self.rot).map(|(_, y)| y);
-        (blocks.iter().max().unwrap() - blocks.iter().min().unwrap() + 1) as usize
+        blocks.iter().max().unwrap() - blocks.iter().min().unwrap() + 1
+    }
+
+    /// Get the number of clockwise rotations from the initial rotation, which
+    /// is less than 4.
+    pub fn rotation(&self) -> usize {
+        self.rot
+    }
+
+    /// Get the position of the top left corner of the box the tetrimino
+    /// rotates in, which is (0, 0) for `Tetrimino::new`.
+    pub fn position(&self) -> (isize, isize) {
+        self.pos
+    }
+
+    /// Get the shape in the current rotation as a grid of rows, moved to the
+    /// top left corner, which is handy for drawing previews.
+    ///
+    /// ```
+    /// use tetrice::{BlockKind, Tetrimino};
+    ///
+    /// let grid = Tetrimino::new(BlockKind::I).rotate(1).preview_grid();
+    /// assert!(grid.iter().all(|row| row == &[true, false, false, false]));
+    /// ```
+    pub fn preview_grid(&self) -> [[bool; 4]; 4] {
+        let blocks = self.move_to((0, 0)).blocks();
+        let mut grid = [[false; 4]; 4];
+        for (x, y) in blocks {
+            grid[y as usize][x as usize] = true;
+        }
+        grid
     }
 
     pub(crate) fn bottom(&self) -> isize {
@@ -51,7 +110,8 @@ impl Tetrimino {
         }
     }
 
-    pub(crate) fn move_to(&self, (left, top): (isize, isize)) -> Self {
+    /// Move so that the top left corner of the blocks is at the position.
+    pub fn move_to(&self, (left, top): (isize, isize)) -> Self {
         let blocks = self.blocks();
         let current_left = blocks.iter().map(|(x, _)| x).min().unwrap();
         let current_top = blocks.iter().map(|(_, y)| y).min().unwrap();
@@ -59,33 +119,70 @@ impl Tetrimino {
         self._move((self.pos.0 + diff.0, self.pos.1 + diff.1))
     }
 
-    pub(crate) fn move_left(&self, dist: isize) -> Self {
+    /// Move to the left by `dist` cells.
+    pub fn move_left(&self, dist: isize) -> Self {
         self._move((self.pos.0 - dist, self.pos.1))
     }
-    pub(crate) fn move_right(&self, dist: isize) -> Self {
+    /// Move to the right by `dist` cells.
+    pub fn move_right(&self, dist: isize) -> Self {
         self._move((self.pos.0 + dist, self.pos.1))
     }
-    pub(crate) fn move_up(&self, dist: isize) -> Self {
+    /// Move up by `dist` cells.
+    pub fn move_up(&self, dist: isize) -> Self {
         self._move((self.pos.0, self.pos.1 - dist))
     }
-    pub(crate) fn move_down(&self, dist: isize) -> Self {
+    /// Move down by `dist` cells.
+    pub fn move_down(&self, dist: isize) -> Self {
         self._move((self.pos.0, self.pos.1 + dist))
     }
 
-    pub(crate) fn rotate(&self, times: usize) -> Self {
+    /// Rotate clockwise `times` times in place, without any kicks.
+    pub fn rotate(&self, times: usize) -> Self {
         Self {
             kind: self.kind,
             rot: (self.rot + times) % self.kind.num_rot(),
             pos: self.pos,
         }
     }
+
+    /// Rotate counter-clockwise `times` times in place, without any kicks.
+    pub fn rotate_ccw(&self, times: usize) -> Self {
+        let num_rot = self.kind.num_rot();
+        self.rotate(num_rot - times % num_rot)
+    }
+
+    /// Mirror horizontally within a field of the width. The kind becomes
+    /// `BlockKind::mirrored`, and the rotation is reversed, so that the
+    /// blocks are the mirror image of this tetrimino.
+    ///
+    /// ```
+    /// use tetrice::{BlockKind, Tetrimino};
+    ///
+    /// let l = Tetrimino::new(BlockKind::L).rotate(1).move_to((0, 5));
+    /// let j = l.mirrored(10);
+    /// assert_eq!(j.kind(), BlockKind::J);
+    /// assert_eq!(j.rotation(), 3);
+    /// ```
+    pub fn mirrored(&self, width: usize) -> Self {
+        let kind = self.kind.mirrored();
+        let num_rot = kind.num_rot();
+        let blocks = self.blocks().map(|(x, y)| (width as isize - 1 - x, y));
+        // The shapes are the same, so the top left corners are matched.
+        let left = blocks.iter().map(|(x, _)| *x).min().unwrap();
+        let top = blocks.iter().map(|(_, y)| *y).min().unwrap();
+        Self::new(kind)
+            .rotate(num_rot - self.rot % num_rot)
+            .move_to((left, top))
+    }
 }
 
 const SHAPES: [&[[(usize, usize); 4]]; 7] = [
     &[[(0, 0), (1, 0), (0, 1), (1, 1)]],
     &[
         [(0, 1), (1, 1), (2, 1), (3, 1)],
-        [(1, 0), (1, 1), (1, 2), (1, 3)],
+        [(2, 0), (2, 1), (2, 2), (2, 3)],
+        [(3, 2), (2, 2), (1, 2), (0, 2)],
+        [(1, 3), (1, 2), (1, 1), (1, 0)],
     ],
     &[
         [(0, 0), (1, 0), (1, 1), (2, 1)],
@@ -120,7 +217,12 @@ const SHAPES: [&[[(usize, usize); 4]]; 7] = [
 ];
 
 /// The block kind of a tetrimino.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+///
+/// Only the seven tetriminos are supported. Every piece is made of four
+/// blocks, and the fields, the rotation systems and the bots rely on it, so
+/// custom pieces such as trominos or pentominos can't be added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BlockKind {
     O,
     I,
@@ -145,6 +247,51 @@ impl BlockKind {
         ]
     }
 
+    /// Get the letter of the kind, like `'T'`.
+    pub fn to_char(self) -> char {
+        match self {
+            Self::O => 'O',
+            Self::I => 'I',
+            Self::Z => 'Z',
+            Self::S => 'S',
+            Self::L => 'L',
+            Self::T => 'T',
+            Self::J => 'J',
+        }
+    }
+
+    /// Get the kind from its uppercase letter. Returns `None` for other
+    /// characters.
+    pub fn from_char(c: char) -> Option<Self> {
+        Self::all_as_array()
+            .into_iter()
+            .find(|kind| kind.to_char() == c)
+    }
+
+    /// Get the kind of the mirror image, which swaps S and Z, and L and J.
+    pub fn mirrored(self) -> Self {
+        match self {
+            Self::Z => Self::S,
+            Self::S => Self::Z,
+            Self::L => Self::J,
+            Self::J => Self::L,
+            kind => kind,
+        }
+    }
+
+    /// Get the color of the kind in the Tetris Guideline as RGB.
+    pub fn guideline_color(self) -> (u8, u8, u8) {
+        match self {
+            Self::O => (255, 255, 0),
+            Self::I => (0, 255, 255),
+            Self::Z => (255, 0, 0),
+            Self::S => (0, 255, 0),
+            Self::L => (255, 127, 0),
+            Self::T => (128, 0, 128),
+            Self::J => (0, 0, 255),
+        }
+    }
+
     fn data(&self) -> &[[(usize, usize); 4]] {
         match &self {
             Self::O => SHAPES[0],
@@ -157,7 +304,7 @@ impl BlockKind {
         }
     }
 
-    fn num_rot(&self) -> usize {
+    pub(crate) fn num_rot(&self) -> usize {
         self.data().len()
     }
 
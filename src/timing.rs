@@ -0,0 +1,121 @@
+use core::time::Duration;
+
+use crate::LockResult;
+
+// A delay during which no tetrimino is in play.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Delay {
+    // The cleared lines stay before they are removed.
+    Clearing,
+    // The entry delay (ARE) before the next tetrimino appears.
+    Are,
+}
+
+/// The speed at which the tetrimino falls automatically.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Gravity {
+    cells_per_second: f64,
+}
+
+/// The number of frames per second, which is used to convert G into cells per
+/// second.
+const FRAMES_PER_SECOND: f64 = 60.0;
+
+impl Gravity {
+    /// The fastest gravity, 20G, where the tetrimino reaches the bottom
+    /// instantly.
+    pub const MAX: Gravity = Gravity {
+        cells_per_second: 20.0 * FRAMES_PER_SECOND,
+    };
+
+    /// Create a gravity from the number of cells per second. Values faster
+    /// than `Gravity::MAX` are clamped.
+    pub fn cells_per_second(cells: f64) -> Self {
+        Self {
+            cells_per_second: cells.clamp(0.0, Self::MAX.cells_per_second),
+        }
+    }
+
+    /// Create a gravity from G, the number of cells per frame at 60 FPS.
+    pub fn from_g(g: f64) -> Self {
+        Self::cells_per_second(g * FRAMES_PER_SECOND)
+    }
+
+    /// Get the number of cells per second.
+    pub fn as_cells_per_second(&self) -> f64 {
+        self.cells_per_second
+    }
+}
+
+impl Default for Gravity {
+    /// One cell per second.
+    fn default() -> Self {
+        Self::cells_per_second(1.0)
+    }
+}
+
+/// The rule of locking a tetrimino touching down.
+///
+/// When the tetrimino touches down, it is saved after `delay` passes. Moving
+/// or rotating it successfully restarts the delay, up to `max_resets` times per
+/// tetrimino.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LockDelay {
+    /// The time until the tetrimino is saved.
+    pub delay: Duration,
+    /// How many times moving or rotating can restart the delay.
+    pub max_resets: usize,
+}
+
+impl Default for LockDelay {
+    /// 0.5 seconds with 15 resets, as in the Guideline.
+    fn default() -> Self {
+        Self {
+            delay: Duration::from_millis(500),
+            max_resets: 15,
+        }
+    }
+}
+
+/// The rule of the floor rising by itself, set by `Game::set_rising_floor`.
+///
+/// Every `interval`, a garbage row with a hole at a random column rises from
+/// the bottom, as `Game::raise_floor` does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RisingFloor {
+    /// The time between rises. If it is zero, the floor doesn't rise.
+    pub interval: Duration,
+    /// The seed deciding the holes of the rows.
+    pub seed: u64,
+}
+
+/// What the game is doing, found by `Game::phase`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Phase {
+    /// The tetrimino is in the air.
+    Falling,
+    /// The tetrimino is on the ground, and is saved when the lock delay
+    /// expires.
+    LockDelay,
+    /// The cleared lines stay during the line clear delay.
+    Clearing,
+    /// No tetrimino is in play during the entry delay (ARE).
+    Are,
+    /// The game has ended.
+    Over,
+}
+
+/// What happened in `Game::update`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TickResult {
+    /// The number of rows the tetrimino fell by gravity.
+    pub dropped: usize,
+    /// The result of saving the tetrimino if the lock delay expired.
+    pub locked: Option<LockResult>,
+    /// The number of rows the floor rose.
+    pub raised: usize,
+}
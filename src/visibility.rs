@@ -0,0 +1,114 @@
+use alloc::{vec, vec::Vec};
+use core::time::Duration;
+
+use crate::Field;
+
+/// Whether the locked blocks are shown, set by `Game::set_visibility`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Visibility {
+    /// Every block is shown.
+    #[default]
+    Visible,
+    /// The blocks of each tetrimino are hidden after the time since it was
+    /// saved, like the invisible credit roll of TGM. Zero hides them at once.
+    /// Garbage stays visible.
+    HideAfter(Duration),
+}
+
+// The time each block of the field was saved, following the rows as they
+// move. `None` is a cell without a saved block.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LockTimes {
+    rows: Vec<Vec<Option<Duration>>>,
+    hidden_height: usize,
+}
+
+impl LockTimes {
+    // Forget every time, fitting to the size of the field.
+    pub(crate) fn reset(&mut self, field: &Field) {
+        let width = field.width();
+        let rows = field.height() + field.hidden_height();
+        self.rows.clear();
+        self.rows.resize(rows, vec![None; width]);
+        self.hidden_height = field.hidden_height();
+    }
+
+    fn fits(&self, field: &Field) -> bool {
+        self.hidden_height == field.hidden_height()
+            && self.rows.len() == field.height() + field.hidden_height()
+            && self.rows.first().map_or(0, Vec::len) == field.width()
+    }
+
+    fn index(&self, (x, y): (isize, isize)) -> Option<(usize, usize)> {
+        let y = usize::try_from(y + self.hidden_height as isize).ok()?;
+        let x = usize::try_from(x).ok()?;
+        (y < self.rows.len() && x < self.rows[y].len()).then_some((x, y))
+    }
+
+    pub(crate) fn get(&self, pos: (isize, isize)) -> Option<Duration> {
+        let (x, y) = self.index(pos)?;
+        self.rows[y][x]
+    }
+
+    // Record the blocks saved at the time. The times start over if the field
+    // was replaced by another size.
+    pub(crate) fn record(&mut self, field: &Field, blocks: [(isize, isize); 4], time: Duration) {
+        if !self.fits(field) {
+            self.reset(field);
+        }
+        for pos in blocks {
+            if let Some((x, y)) = self.index(pos) {
+                self.rows[y][x] = Some(time);
+            }
+        }
+    }
+
+    // Remove the rows like `Field::remove_filled_lines`, given the removed y
+    // coordinates from top to bottom.
+    pub(crate) fn remove_rows(&mut self, removed: &[isize]) {
+        let indices: Vec<_> = removed.iter().filter_map(|y| self.index((0, *y))).collect();
+        let width = self.rows.first().map_or(0, Vec::len);
+        // The lower rows are removed first, so that the indices above stay.
+        for (_, y) in indices.iter().rev() {
+            self.rows.remove(*y);
+        }
+        for _ in &indices {
+            self.rows.insert(0, vec![None; width]);
+        }
+    }
+
+    // Move the blocks like `Field::drop_floating_groups`, given the groups
+    // in the order they fell and their distances.
+    pub(crate) fn move_groups(&mut self, moves: &[(Vec<(isize, isize)>, isize)]) {
+        for (group, distance) in moves {
+            let times: Vec<_> = group.iter().map(|pos| self.get(*pos)).collect();
+            for pos in group {
+                if let Some((x, y)) = self.index(*pos) {
+                    self.rows[y][x] = None;
+                }
+            }
+            for ((x, y), time) in group.iter().zip(times) {
+                if let Some((x, y)) = self.index((*x, y + distance)) {
+                    self.rows[y][x] = time;
+                }
+            }
+        }
+    }
+
+    // Mirror the rows like `Field::mirror`.
+    pub(crate) fn mirror(&mut self) {
+        for row in &mut self.rows {
+            row.reverse();
+        }
+    }
+
+    // Push up the rows like `Field::push_up`, adding `count` rows without
+    // saved blocks at the bottom.
+    pub(crate) fn push_up(&mut self, count: usize) {
+        let count = count.min(self.rows.len());
+        let width = self.rows.first().map_or(0, Vec::len);
+        self.rows.drain(..count);
+        self.rows.extend((0..count).map(|_| vec![None; width]));
+    }
+}
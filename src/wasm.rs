@@ -0,0 +1,139 @@
+//! Bindings for JavaScript, enabled by the `wasm` feature.
+//!
+//! `JsGame` wraps a game with the 7-bag randomizer, and uses numbers, strings
+//! and typed arrays so that it can be used from JavaScript directly.
+
+use std::time::Duration;
+
+use wasm_bindgen::prelude::*;
+
+use crate::selectors::Bag;
+use crate::{BlockKind, Game, Input, Tetrimino};
+
+/// A game for JavaScript.
+#[wasm_bindgen]
+pub struct JsGame {
+    game: Game<Bag>,
+}
+
+#[wasm_bindgen]
+impl JsGame {
+    /// Create a game with the 7-bag randomizer using the seed.
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: usize, height: usize, queue_size: usize, seed: u32) -> JsGame {
+        Self {
+            game: Game::with_seed(width, height, queue_size, seed as u64),
+        }
+    }
+
+    /// Get the width of the field.
+    pub fn width(&self) -> usize {
+        self.game.field().width()
+    }
+
+    /// Get the height of the visible area.
+    pub fn height(&self) -> usize {
+        self.game.field().height()
+    }
+
+    /// Get the cells of the visible area row by row from the top, where 0 is
+    /// empty, 1 to 7 are O, I, Z, S, L, T and J, 8 is garbage, 9 is solid
+    /// and 10 is a line being cleared.
+    pub fn cells(&self) -> Vec<u8> {
+        self.game
+            .field()
+            .visible()
+            .iter()
+            .flatten()
+            .map(|cell| cell.to_code())
+            .collect()
+    }
+
+    /// Get the visible area as a map string (see `Field::to_map_string`).
+    #[wasm_bindgen(js_name = mapString)]
+    pub fn map_string(&self) -> String {
+        self.game.field().to_map_string()
+    }
+
+    /// Get the kind of the current tetrimino as a letter.
+    pub fn kind(&self) -> String {
+        self.game.tetrimino().kind().to_char().to_string()
+    }
+
+    /// Get the positions of the blocks of the current tetrimino as
+    /// `[x0, y0, x1, y1, ...]`.
+    pub fn blocks(&self) -> Vec<i32> {
+        flatten_blocks(self.game.tetrimino())
+    }
+
+    /// Same as `blocks`, but for the ghost.
+    #[wasm_bindgen(js_name = ghostBlocks)]
+    pub fn ghost_blocks(&self) -> Vec<i32> {
+        flatten_blocks(&self.game.ghost())
+    }
+
+    /// Get the kinds of the next tetriminos as letters, like `"LIJ"`.
+    pub fn queue(&self) -> String {
+        self.game.queue_shapes().map(BlockKind::to_char).collect()
+    }
+
+    /// Get the kind of the held tetrimino as a letter, or `undefined`.
+    pub fn held(&self) -> Option<String> {
+        self.game.held().map(|t| t.kind().to_char().to_string())
+    }
+
+    /// Get the points.
+    pub fn score(&self) -> f64 {
+        self.game.score().points() as f64
+    }
+
+    /// Get the number of removed lines.
+    pub fn lines(&self) -> usize {
+        self.game.removed_lines()
+    }
+
+    /// Returns true if the game has ended.
+    #[wasm_bindgen(js_name = isEnd)]
+    pub fn is_end(&self) -> bool {
+        self.game.is_end()
+    }
+
+    /// Apply an input named in camel case, such as `"left"`, `"hardDrop"` or
+    /// `"rotateCw"`. Returns true if it changed the game, and false if it
+    /// didn't or the name is unknown.
+    pub fn apply(&mut self, input: &str) -> bool {
+        let input = match input {
+            "left" => Input::Left,
+            "right" => Input::Right,
+            "softDrop" => Input::SoftDrop,
+            "hardDrop" => Input::HardDrop,
+            "sonicDrop" => Input::SonicDrop,
+            "leftToWall" => Input::LeftToWall,
+            "rightToWall" => Input::RightToWall,
+            "rotateCw" => Input::RotateCw,
+            "rotateCcw" => Input::RotateCcw,
+            "rotate180" => Input::Rotate180,
+            "hold" => Input::Hold,
+            "lock" => Input::Lock,
+            _ => return false,
+        };
+        self.game.apply(input).applied
+    }
+
+    /// Advance the time by the milliseconds. Returns the number of lines
+    /// removed by the tetrimino locked during the time.
+    pub fn update(&mut self, millis: f64) -> usize {
+        let result = self
+            .game
+            .update(Duration::from_secs_f64(millis.max(0.0) / 1000.0));
+        result.locked.map_or(0, |locked| locked.lines())
+    }
+}
+
+fn flatten_blocks(tetrimino: &Tetrimino) -> Vec<i32> {
+    tetrimino
+        .blocks()
+        .iter()
+        .flat_map(|(x, y)| [*x as i32, *y as i32])
+        .collect()
+}